@@ -191,10 +191,9 @@ impl CrossPlatformTester {
             ("default", ProfileConfig::default()),
             (
                 "no_fallback",
-                ProfileConfig {
-                    linux_prefer_dbus: true,
-                    fallback_enabled: false,
-                },
+                ProfileConfig::default()
+                    .with_linux_prefer_dbus(true)
+                    .with_fallback_enabled(false),
             ),
         ];
 
@@ -203,10 +202,9 @@ impl CrossPlatformTester {
         if matches!(self.platform, Platform::Linux) {
             linux_configs.push((
                 "prefer_command",
-                ProfileConfig {
-                    linux_prefer_dbus: false,
-                    fallback_enabled: true,
-                },
+                ProfileConfig::default()
+                    .with_linux_prefer_dbus(false)
+                    .with_fallback_enabled(true),
             ));
         }
 
@@ -257,11 +255,11 @@ impl CrossPlatformTester {
         // Test 1: Invalid display ID
         let start = Instant::now();
         if let Ok(provider) = create_provider() {
-            let fake_display = display_icc::Display {
-                id: "nonexistent_display_12345".to_string(),
-                name: "Fake Display".to_string(),
-                is_primary: false,
-            };
+            let fake_display = display_icc::Display::new(
+                "nonexistent_display_12345".to_string(),
+                "Fake Display".to_string(),
+                false,
+            );
 
             match provider.get_profile(&fake_display) {
                 Ok(_) => {
@@ -384,15 +382,13 @@ impl CrossPlatformTester {
                 println!("   • Profile file system access");
 
                 // Test both D-Bus and command preferences
-                let dbus_config = ProfileConfig {
-                    linux_prefer_dbus: true,
-                    fallback_enabled: false,
-                };
-
-                let command_config = ProfileConfig {
-                    linux_prefer_dbus: false,
-                    fallback_enabled: false,
-                };
+                let dbus_config = ProfileConfig::default()
+                    .with_linux_prefer_dbus(true)
+                    .with_fallback_enabled(false);
+
+                let command_config = ProfileConfig::default()
+                    .with_linux_prefer_dbus(false)
+                    .with_fallback_enabled(false);
 
                 let dbus_result =
                     create_provider_with_config(dbus_config).and_then(|p| p.get_displays());
@@ -6,7 +6,7 @@
 //! - Caching and performance optimization
 //! - Multi-display handling
 //! - Error handling in GUI contexts
-//! - Profile change monitoring simulation
+//! - Reacting to display and profile changes
 //!
 //! This example uses a simulated GUI framework but shows real patterns
 //! that would apply to actual GUI frameworks like egui, iced, tauri, etc.
@@ -14,8 +14,8 @@
 //! Run with: cargo run --example gui_integration
 
 use display_icc::{
-    create_provider_with_config, Display, DisplayProfileProvider, ProfileConfig, ProfileError,
-    ProfileInfo,
+    create_provider_with_config, Display, DisplayEvent, DisplayProfileProvider, ProfileConfig,
+    ProfileError, ProfileInfo, ProfileWatcher,
 };
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -47,6 +47,8 @@ impl AppState {
 /// Profile manager for GUI applications
 struct ProfileManager {
     provider: Box<dyn DisplayProfileProvider>,
+    /// Change notifications, if the provider can watch
+    watcher: Option<ProfileWatcher>,
     state: Arc<Mutex<AppState>>,
 }
 
@@ -54,15 +56,29 @@ impl ProfileManager {
     /// Create a new profile manager with optimized configuration for GUI use
     fn new() -> Result<Self, ProfileError> {
         // Configuration optimized for GUI applications
-        let config = ProfileConfig {
-            linux_prefer_dbus: true, // Use faster D-Bus API on Linux
-            fallback_enabled: true,  // Ensure reliability
-        };
+        let config = ProfileConfig::default()
+            .with_linux_prefer_dbus(true) // Use faster D-Bus API on Linux
+            .with_fallback_enabled(true); // Ensure reliability
 
         let provider = create_provider_with_config(config)?;
         let state = Arc::new(Mutex::new(AppState::new()));
 
-        Ok(Self { provider, state })
+        // Start watching before the first load so that no change is missed
+        let watcher = match provider.watch() {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                eprintln!(
+                    "Note: change notifications unavailable ({e}), refreshing every 30 seconds"
+                );
+                None
+            }
+        };
+
+        Ok(Self {
+            provider,
+            watcher,
+            state,
+        })
     }
 
     /// Load display profiles asynchronously (simulated)
@@ -121,12 +137,48 @@ impl ProfileManager {
         }
     }
 
-    /// Check if profiles need refreshing (e.g., every 30 seconds)
+    /// Check if profiles need refreshing, for providers that cannot watch
     fn needs_refresh(&self) -> bool {
         let state = self.state.lock().unwrap();
         state.last_update.elapsed() > Duration::from_secs(30)
     }
 
+    /// Handle pending change notifications, reloading profiles if anything
+    /// changed; call once per frame. Returns whether profiles were reloaded.
+    fn process_changes(&self) -> Result<bool, ProfileError> {
+        let Some(watcher) = &self.watcher else {
+            // Without notifications, fall back to refreshing periodically
+            if self.needs_refresh() {
+                self.load_profiles_async()?;
+                return Ok(true);
+            }
+            return Ok(false);
+        };
+
+        let mut changed = false;
+        while let Some(event) = watcher.try_recv() {
+            match &event {
+                DisplayEvent::Added(display) => println!("🔌 Display connected: {}", display.name),
+                DisplayEvent::Removed(display) => {
+                    println!("⏏️  Display disconnected: {}", display.name)
+                }
+                DisplayEvent::ProfileChanged(display) => {
+                    println!("🎨 Profile changed for {}", display.name)
+                }
+                DisplayEvent::PrimaryChanged(display) => {
+                    println!("⭐ Primary display is now {}", display.name)
+                }
+                _ => {}
+            }
+            changed = true;
+        }
+
+        if changed {
+            self.load_profiles_async()?;
+        }
+        Ok(changed)
+    }
+
     /// Get profile for a specific display
     fn get_display_profile(&self, display_id: &str) -> Option<ProfileInfo> {
         let state = self.state.lock().unwrap();
@@ -282,9 +334,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        // Check if refresh is needed
-        if manager.needs_refresh() {
-            println!("🔄 Profiles need refresh (30+ seconds old)");
+        // React to displays being plugged in and profiles being changed
+        match manager.process_changes() {
+            Ok(true) => println!("🔄 Profiles reloaded after a change"),
+            Ok(false) => {}
+            Err(e) => eprintln!("❌ Reload failed: {}", e),
         }
 
         // Simulate frame delay
@@ -292,13 +346,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!();
     }
 
-    // Simulate profile monitoring (would be in background thread in real app)
-    println!("🔍 Simulating profile change monitoring...");
-    println!("   In a real GUI app, you would:");
-    println!("   • Monitor for display connection/disconnection events");
-    println!("   • Watch for profile changes in the system");
-    println!("   • Update the UI automatically when changes occur");
-    println!("   • Handle errors gracefully with user notifications");
+    println!("🔍 Change monitoring:");
+    if manager.watcher.is_some() {
+        println!("   • Display and profile changes arrive through watch()");
+        println!("   • Each frame drains pending events and reloads on change");
+    } else {
+        println!("   • This provider cannot watch, so profiles refresh every 30 seconds");
+    }
 
     println!("\n✨ GUI integration example completed!");
 
@@ -138,5 +138,9 @@ fn handle_error(error: &ProfileError) {
             eprintln!("  Help: Data parsing error: {}", msg);
             eprintln!("        The profile data might be corrupted or in an unsupported format.");
         }
+        ProfileError::Timeout(msg) => {
+            eprintln!("  Help: The system did not answer in time: {}", msg);
+            eprintln!("        The color management service may be busy or unresponsive.");
+        }
     }
 }
@@ -0,0 +1,392 @@
+//! Async API, with the `tokio` feature.
+//!
+//! [`AsyncDisplayProfileProvider`] mirrors the core of
+//! [`DisplayProfileProvider`] with methods that return futures, and
+//! [`DisplayEvents`] is a [`Stream`](futures_core::Stream) of change events.
+//! On Linux the provider queries colord over async D-Bus and runs `colormgr`
+//! as an async process; the remaining fallbacks, and every call of another
+//! provider wrapped in an [`AsyncAdapter`], run on tokio's blocking thread
+//! pool. The futures need a tokio runtime to make progress.
+
+use crate::{
+    Display, DisplayEvent, DisplayProfileProvider, ProfileError, ProfileInfo, ProfileWatcher,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How often the thread feeding a [`DisplayEvents`] checks whether it was dropped
+const CLOSED_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The async counterpart of [`DisplayProfileProvider`].
+///
+/// Errors and platform behaviour are those of the blocking methods of the
+/// same names.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use display_icc::{create_async_provider, AsyncDisplayProfileProvider};
+///
+/// # async fn example() -> Result<(), display_icc::ProfileError> {
+/// let provider = create_async_provider()?;
+/// for display in provider.get_displays().await? {
+///     let profile = provider.get_profile(&display).await?;
+///     let data = provider.get_profile_data(&display).await?;
+///     println!("{}: {} ({} bytes)", display.name, profile.name, data.len());
+/// }
+///
+/// let mut events = provider.events().await?;
+/// while let Some(event) = events.next().await {
+///     println!("{:?}", event);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait AsyncDisplayProfileProvider: Send + Sync {
+    /// Get all available displays in the system
+    fn get_displays(&self) -> impl Future<Output = Result<Vec<Display>, ProfileError>> + Send;
+
+    /// Get the primary display.
+    ///
+    /// The default implementation picks the display marked as primary from
+    /// [`get_displays`](Self::get_displays).
+    fn get_primary_display(&self) -> impl Future<Output = Result<Display, ProfileError>> + Send {
+        async {
+            self.get_displays()
+                .await?
+                .into_iter()
+                .find(|display| display.is_primary)
+                .ok_or_else(|| {
+                    ProfileError::DisplayNotFound("No primary display found".to_string())
+                })
+        }
+    }
+
+    /// Get profile information for a specific display
+    fn get_profile(
+        &self,
+        display: &Display,
+    ) -> impl Future<Output = Result<ProfileInfo, ProfileError>> + Send;
+
+    /// Get raw ICC profile data for a specific display
+    fn get_profile_data(
+        &self,
+        display: &Display,
+    ) -> impl Future<Output = Result<Vec<u8>, ProfileError>> + Send;
+
+    /// Watch for changes, as [`DisplayProfileProvider::watch`] does
+    fn events(&self) -> impl Future<Output = Result<DisplayEvents, ProfileError>> + Send;
+}
+
+/// Runs a blocking [`DisplayProfileProvider`] as an
+/// [`AsyncDisplayProfileProvider`], each call on tokio's blocking thread pool.
+///
+/// Clones share the provider.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use display_icc::{AsyncAdapter, AsyncDisplayProfileProvider, FileProfileProvider};
+///
+/// # async fn example() -> Result<(), display_icc::ProfileError> {
+/// let provider = AsyncAdapter::new(FileProfileProvider::load("displays.toml")?);
+/// let primary = provider.get_primary_display().await?;
+/// println!("{}: {}", primary.name, provider.get_profile(&primary).await?.name);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncAdapter<P> {
+    inner: Arc<P>,
+}
+
+impl<P> Clone for AsyncAdapter<P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<P: DisplayProfileProvider + Send + Sync + 'static> AsyncAdapter<P> {
+    /// Wrap `provider`
+    pub fn new(provider: P) -> Self {
+        Self {
+            inner: Arc::new(provider),
+        }
+    }
+
+    /// The wrapped provider
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Run `call` on the wrapped provider on the blocking thread pool
+    fn run<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&P) -> Result<T, ProfileError> + Send + 'static,
+    ) -> impl Future<Output = Result<T, ProfileError>> + Send + 'static {
+        let inner = Arc::clone(&self.inner);
+        blocking(move || call(&inner))
+    }
+}
+
+impl<P: DisplayProfileProvider + Send + Sync + 'static> AsyncDisplayProfileProvider
+    for AsyncAdapter<P>
+{
+    fn get_displays(&self) -> impl Future<Output = Result<Vec<Display>, ProfileError>> + Send {
+        self.run(|provider| provider.get_displays())
+    }
+
+    fn get_primary_display(&self) -> impl Future<Output = Result<Display, ProfileError>> + Send {
+        self.run(|provider| provider.get_primary_display())
+    }
+
+    fn get_profile(
+        &self,
+        display: &Display,
+    ) -> impl Future<Output = Result<ProfileInfo, ProfileError>> + Send {
+        let display = display.clone();
+        self.run(move |provider| provider.get_profile(&display))
+    }
+
+    fn get_profile_data(
+        &self,
+        display: &Display,
+    ) -> impl Future<Output = Result<Vec<u8>, ProfileError>> + Send {
+        let display = display.clone();
+        self.run(move |provider| provider.get_profile_data(&display))
+    }
+
+    fn events(&self) -> impl Future<Output = Result<DisplayEvents, ProfileError>> + Send {
+        let watcher = self.run(|provider| provider.watch());
+        async { watcher.await.map(DisplayEvents::from) }
+    }
+}
+
+/// Run `call` on tokio's blocking thread pool, passing on its panics
+pub(crate) async fn blocking<T: Send + 'static>(
+    call: impl FnOnce() -> Result<T, ProfileError> + Send + 'static,
+) -> Result<T, ProfileError> {
+    match tokio::task::spawn_blocking(call).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(ProfileError::SystemError(format!(
+            "Blocking task did not finish: {}",
+            e
+        ))),
+    }
+}
+
+/// A [`Stream`](futures_core::Stream) of [`DisplayEvent`]s, from
+/// [`AsyncDisplayProfileProvider::events`] or any [`ProfileWatcher`].
+///
+/// The watcher is read on a thread of its own. The stream ends when no more
+/// events can arrive; dropping it stops watching.
+#[derive(Debug)]
+pub struct DisplayEvents {
+    events: mpsc::UnboundedReceiver<DisplayEvent>,
+}
+
+impl DisplayEvents {
+    /// Wait for the next event, or `None` once no more can arrive
+    pub async fn next(&mut self) -> Option<DisplayEvent> {
+        self.events.recv().await
+    }
+}
+
+impl From<ProfileWatcher> for DisplayEvents {
+    fn from(watcher: ProfileWatcher) -> Self {
+        let (sender, events) = mpsc::unbounded_channel();
+        // Without a thread the watcher is dropped at once, ending the stream
+        let _ = thread::Builder::new()
+            .name("display_icc event stream".to_string())
+            .spawn(move || loop {
+                match watcher.poll(CLOSED_POLL_INTERVAL) {
+                    Ok(event) => {
+                        if sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) if !sender.is_closed() => {}
+                    Err(_) => return,
+                }
+            });
+        Self { events }
+    }
+}
+
+impl futures_core::Stream for DisplayEvents {
+    type Item = DisplayEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<DisplayEvent>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+/// Create the platform's async provider with the default configuration.
+///
+/// This is the async counterpart of [`create_provider`](crate::create_provider).
+pub fn create_async_provider() -> Result<impl AsyncDisplayProfileProvider, ProfileError> {
+    create_async_provider_with_config(crate::ProfileConfig::default())
+}
+
+/// Create the platform's async provider with custom configuration.
+///
+/// On Linux this is [`LinuxProfileProvider`](crate::LinuxProfileProvider)
+/// itself; elsewhere the platform provider in an [`AsyncAdapter`].
+pub fn create_async_provider_with_config(
+    config: crate::ProfileConfig,
+) -> Result<impl AsyncDisplayProfileProvider, ProfileError> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(AsyncAdapter::new(crate::MacOSProfileProvider::with_config(
+            config,
+        )))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Ok(crate::LinuxProfileProvider::with_config(config))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        Ok(AsyncAdapter::new(
+            crate::WindowsProfileProvider::with_config(config),
+        ))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        let _ = config;
+        Err::<AsyncAdapter<Box<dyn DisplayProfileProvider + Send + Sync>>, _>(
+            ProfileError::UnsupportedPlatform,
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::future::Future;
+
+    /// Run `future` to completion on a single-threaded runtime
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::block_on;
+    use super::*;
+    use crate::mock::{BuiltinProfile, MockMethod, MockProfileProvider};
+
+    #[test]
+    fn test_adapter() {
+        let mock = MockProfileProvider::with_test_data();
+        let provider = AsyncAdapter::new(mock.shared());
+        block_on(async {
+            let displays = provider.get_displays().await.unwrap();
+            assert_eq!(displays.len(), 2);
+            let primary = provider.get_primary_display().await.unwrap();
+            assert_eq!(primary.id, "primary");
+            assert_eq!(
+                provider.get_profile(&primary).await.unwrap(),
+                mock.get_profile(&primary).unwrap()
+            );
+            assert_eq!(
+                provider.get_profile_data(&primary).await.unwrap(),
+                mock.get_profile_data(&primary).unwrap()
+            );
+            assert!(matches!(
+                provider
+                    .get_profile(&Display::new("gone", "Gone", false))
+                    .await,
+                Err(ProfileError::ProfileNotAvailable(_))
+            ));
+        });
+        mock.assert_call_count(MockMethod::GetPrimaryDisplay, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "provider bug")]
+    fn test_adapter_passes_on_panics() {
+        struct Panicking;
+        impl DisplayProfileProvider for Panicking {
+            fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
+                panic!("provider bug")
+            }
+            fn get_primary_display(&self) -> Result<Display, ProfileError> {
+                unimplemented!()
+            }
+            fn get_profile(&self, _: &Display) -> Result<ProfileInfo, ProfileError> {
+                unimplemented!()
+            }
+            fn get_profile_data(&self, _: &Display) -> Result<Vec<u8>, ProfileError> {
+                unimplemented!()
+            }
+        }
+
+        let _ = block_on(AsyncAdapter::new(Panicking).get_displays());
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let mut mock = MockProfileProvider::with_test_data();
+        let provider = AsyncAdapter::new(mock.shared());
+        block_on(async {
+            let mut events = provider.events().await.unwrap();
+            mock.set_profile_data("secondary", BuiltinProfile::AdobeRgb.data());
+            let event = tokio::time::timeout(Duration::from_secs(2), events.next())
+                .await
+                .unwrap();
+            assert!(matches!(event, Some(DisplayEvent::ProfileChanged(d)) if d.id == "secondary"));
+        });
+
+        // Unsupported providers report so when asked for events
+        struct Silent;
+        impl DisplayProfileProvider for Silent {
+            fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
+                Ok(Vec::new())
+            }
+            fn get_primary_display(&self) -> Result<Display, ProfileError> {
+                Err(ProfileError::DisplayNotFound("none".to_string()))
+            }
+            fn get_profile(&self, d: &Display) -> Result<ProfileInfo, ProfileError> {
+                Err(ProfileError::DisplayNotFound(d.id.clone()))
+            }
+            fn get_profile_data(&self, d: &Display) -> Result<Vec<u8>, ProfileError> {
+                Err(ProfileError::DisplayNotFound(d.id.clone()))
+            }
+        }
+        assert!(matches!(
+            block_on(AsyncAdapter::new(Silent).events()),
+            Err(ProfileError::UnsupportedPlatform)
+        ));
+    }
+
+    #[test]
+    fn test_event_stream_ends_with_watcher() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut events = DisplayEvents::from(ProfileWatcher::new(receiver, ()));
+        sender
+            .send(DisplayEvent::Added(Display::new("DP-1", "Monitor", true)))
+            .unwrap();
+        drop(sender);
+        block_on(async {
+            assert!(matches!(events.next().await, Some(DisplayEvent::Added(_))));
+            assert_eq!(events.next().await, None);
+        });
+    }
+}
@@ -0,0 +1,505 @@
+//! Memoising wrapper around a provider.
+//!
+//! Backends recompute everything on every call; on Linux a single
+//! `get_profile_data` asks colord (or spawns `colormgr`) for the devices and
+//! then for the profile. [`CachedProvider`] keeps the results of successful
+//! calls for a configurable time, and can drop them early when told to or when
+//! a [`ProfileWatcher`] reports a change.
+
+use crate::{
+    AssignedProfile, Display, DisplayEvent, DisplayProfileProvider, ProfileError, ProfileInfo,
+    ProfileWatcher,
+};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the invalidation thread checks whether its cache is still in use
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A value and when it was fetched
+#[derive(Debug)]
+struct Entry<T> {
+    value: T,
+    fetched: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    /// Bumped on every invalidation, so that a result fetched across one is
+    /// not stored
+    generation: u64,
+    displays: Option<Entry<Vec<Display>>>,
+    primary: Option<Entry<Display>>,
+    /// Per-display entries by display ID
+    profiles: HashMap<String, Entry<ProfileInfo>>,
+    profile_data: HashMap<String, Entry<Vec<u8>>>,
+    assigned: HashMap<String, Entry<Vec<AssignedProfile>>>,
+}
+
+impl Cache {
+    fn clear(&mut self) {
+        *self = Self {
+            generation: self.generation + 1,
+            ..Self::default()
+        };
+    }
+
+    fn clear_display(&mut self, display_id: &str) {
+        self.generation += 1;
+        self.profiles.remove(display_id);
+        self.profile_data.remove(display_id);
+        self.assigned.remove(display_id);
+    }
+
+    /// Apply a change reported by a watcher
+    fn apply(&mut self, event: &DisplayEvent) {
+        match event {
+            DisplayEvent::ProfileChanged(display) => self.clear_display(&display.id),
+            DisplayEvent::Added(display) | DisplayEvent::Removed(display) => {
+                self.clear_display(&display.id);
+                self.displays = None;
+                self.primary = None;
+            }
+            DisplayEvent::PrimaryChanged(_) => {
+                self.displays = None;
+                self.primary = None;
+            }
+        }
+    }
+}
+
+/// A [`DisplayProfileProvider`] that remembers the results of another.
+///
+/// Successful results are kept for the time to live given at construction and
+/// then fetched again; errors are never kept. Per-display results are keyed by
+/// [`Display::id`]. [`invalidate`](Self::invalidate) and
+/// [`invalidate_display`](Self::invalidate_display) drop results early, and
+/// [`invalidate_on`](Self::invalidate_on) does so whenever a
+/// [`ProfileWatcher`] reports a change. Watching a cached provider drops the
+/// results each event affects before passing it on.
+///
+/// [`get_assigned_profile_data`](DisplayProfileProvider::get_assigned_profile_data)
+/// is passed straight through, as profile files are read directly.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use display_icc::{create_provider, CachedProvider, DisplayProfileProvider};
+/// use std::time::Duration;
+///
+/// # fn example() -> Result<(), display_icc::ProfileError> {
+/// let provider = CachedProvider::new(create_provider()?, Duration::from_secs(30));
+///
+/// for display in provider.get_displays()? {
+///     // Only the first call for each display reaches the system
+///     let profile = provider.get_profile(&display)?;
+///     let data = provider.get_profile_data(&display)?;
+///     println!("{}: {} ({} bytes)", display.name, profile.name, data.len());
+/// }
+///
+/// // After assigning a new profile, forget the old one
+/// # let display = provider.get_primary_display()?;
+/// provider.invalidate_display(&display.id);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CachedProvider<P> {
+    inner: P,
+    ttl: Duration,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl<P: DisplayProfileProvider> CachedProvider<P> {
+    /// Wrap `inner`, keeping each result for `ttl`
+    pub fn new(inner: P, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: Arc::new(Mutex::new(Cache::default())),
+        }
+    }
+
+    /// The wrapped provider
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Unwrap the provider, dropping everything cached
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    /// How long results are kept
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Drop every cached result
+    pub fn invalidate(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// Drop the cached profile information and data of the display with `display_id`
+    pub fn invalidate_display(&self, display_id: &str) {
+        self.cache.lock().unwrap().clear_display(display_id);
+    }
+
+    /// Drop cached results as `watcher` reports changes, from a background
+    /// thread that lives as long as the cache.
+    ///
+    /// Hotplug drops the display list and the primary display along with the
+    /// display's own results, a new primary display drops both of those, and a
+    /// profile change only the display's own results. If the
+    /// watcher stops delivering events, for example because the system service
+    /// went away, everything is dropped once and results expire by their time
+    /// to live from then on.
+    pub fn invalidate_on(self, watcher: ProfileWatcher) -> Result<Self, ProfileError> {
+        let cache = Arc::downgrade(&self.cache);
+        thread::Builder::new()
+            .name("display_icc cache invalidation".to_string())
+            .spawn(move || relay(watcher, cache, None))?;
+        Ok(self)
+    }
+
+    /// Drop cached results as the wrapped provider reports changes through
+    /// its [`watch`](DisplayProfileProvider::watch); see
+    /// [`invalidate_on`](Self::invalidate_on).
+    pub fn invalidate_on_changes(self) -> Result<Self, ProfileError> {
+        let watcher = self.inner.watch()?;
+        self.invalidate_on(watcher)
+    }
+
+    /// Return the cached value, or fetch and (unless invalidated meanwhile) cache it
+    fn cached<T: Clone>(
+        &self,
+        slot: impl Fn(&mut Cache) -> &mut Option<Entry<T>>,
+        fetch: impl FnOnce() -> Result<T, ProfileError>,
+    ) -> Result<T, ProfileError> {
+        let generation = {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(entry) = slot(&mut cache) {
+                if entry.fetched.elapsed() < self.ttl {
+                    return Ok(entry.value.clone());
+                }
+            }
+            cache.generation
+        };
+
+        let value = fetch()?;
+        let mut cache = self.cache.lock().unwrap();
+        if cache.generation == generation {
+            *slot(&mut cache) = Some(Entry {
+                value: value.clone(),
+                fetched: Instant::now(),
+            });
+        }
+        Ok(value)
+    }
+
+    /// [`cached`](Self::cached) for a per-display map
+    fn cached_for<T: Clone>(
+        &self,
+        display: &Display,
+        map: impl Fn(&mut Cache) -> &mut HashMap<String, Entry<T>>,
+        fetch: impl FnOnce() -> Result<T, ProfileError>,
+    ) -> Result<T, ProfileError> {
+        let generation = {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(entry) = map(&mut cache).get(&display.id) {
+                if entry.fetched.elapsed() < self.ttl {
+                    return Ok(entry.value.clone());
+                }
+            }
+            cache.generation
+        };
+
+        let value = fetch()?;
+        let mut cache = self.cache.lock().unwrap();
+        if cache.generation == generation {
+            map(&mut cache).insert(
+                display.id.clone(),
+                Entry {
+                    value: value.clone(),
+                    fetched: Instant::now(),
+                },
+            );
+        }
+        Ok(value)
+    }
+}
+
+/// Apply `watcher`'s events to `cache`, passing them on to `forward` if given.
+///
+/// Runs until the watcher ends, which drops everything cached, or until
+/// nobody is left to notice: the returned watcher's stop channel disconnects
+/// when forwarding, and the cache is dropped otherwise.
+fn relay(
+    watcher: ProfileWatcher,
+    cache: Weak<Mutex<Cache>>,
+    forward: Option<(Sender<DisplayEvent>, Receiver<()>)>,
+) {
+    loop {
+        match watcher.poll(WATCH_POLL_INTERVAL) {
+            Ok(event) => {
+                if let Some(cache) = cache.upgrade() {
+                    cache.lock().unwrap().apply(&event);
+                }
+                if let Some((sender, _)) = &forward {
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                if let Some(cache) = cache.upgrade() {
+                    cache.lock().unwrap().clear();
+                }
+                return;
+            }
+        }
+
+        let listening = match &forward {
+            Some((_, stopped)) => !matches!(stopped.try_recv(), Err(TryRecvError::Disconnected)),
+            None => cache.strong_count() > 0,
+        };
+        if !listening {
+            return;
+        }
+    }
+}
+
+impl<P: DisplayProfileProvider> DisplayProfileProvider for CachedProvider<P> {
+    fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
+        self.cached(|cache| &mut cache.displays, || self.inner.get_displays())
+    }
+
+    fn get_primary_display(&self) -> Result<Display, ProfileError> {
+        self.cached(
+            |cache| &mut cache.primary,
+            || self.inner.get_primary_display(),
+        )
+    }
+
+    fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
+        self.cached_for(
+            display,
+            |cache| &mut cache.profiles,
+            || self.inner.get_profile(display),
+        )
+    }
+
+    fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
+        self.cached_for(
+            display,
+            |cache| &mut cache.profile_data,
+            || self.inner.get_profile_data(display),
+        )
+    }
+
+    fn get_profiles(&self, display: &Display) -> Result<Vec<AssignedProfile>, ProfileError> {
+        self.cached_for(
+            display,
+            |cache| &mut cache.assigned,
+            || self.inner.get_profiles(display),
+        )
+    }
+
+    fn get_assigned_profile_data(
+        &self,
+        display: &Display,
+        profile: &AssignedProfile,
+    ) -> Result<Vec<u8>, ProfileError> {
+        self.inner.get_assigned_profile_data(display, profile)
+    }
+
+    /// Watch the wrapped provider, dropping the cached results an event
+    /// affects before passing the event on, so that handlers querying this
+    /// provider see the change.
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        let watcher = self.inner.watch()?;
+        let cache = Arc::downgrade(&self.cache);
+        let (sender, events) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel::<()>();
+        thread::Builder::new()
+            .name("display_icc cache watch".to_string())
+            .spawn(move || relay(watcher, cache, Some((sender, stopped))))?;
+        Ok(ProfileWatcher::new(events, stop))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockMethod, MockProfileProvider};
+    use std::sync::mpsc;
+
+    /// A provider with the mock's test data, and a handle on its call log
+    fn cached(ttl: Duration) -> (CachedProvider<MockProfileProvider>, MockProfileProvider) {
+        let mock = MockProfileProvider::with_test_data();
+        (CachedProvider::new(mock.shared(), ttl), mock)
+    }
+
+    #[test]
+    fn test_results_are_cached() {
+        let (provider, calls) = cached(Duration::from_secs(60));
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(provider.get_displays().unwrap(), displays);
+        for _ in 0..3 {
+            provider.get_primary_display().unwrap();
+            provider.get_profile(&displays[0]).unwrap();
+            provider.get_profile_data(&displays[0]).unwrap();
+            provider.get_profiles(&displays[1]).unwrap();
+        }
+        provider.find_display("id:secondary").unwrap();
+
+        calls.assert_calls(&[
+            MockMethod::GetDisplays,
+            MockMethod::GetPrimaryDisplay,
+            MockMethod::GetProfile,
+            MockMethod::GetProfileData,
+            MockMethod::GetProfiles,
+        ]);
+    }
+
+    #[test]
+    fn test_errors_are_not_cached() {
+        let mut mock = MockProfileProvider::with_test_data();
+        let unknown = Display::new("unknown", "Unknown", false);
+        mock.set_method_failure(
+            MockMethod::GetProfileData,
+            Some("primary"),
+            ProfileError::IoError("busy".to_string()),
+        );
+        let provider = CachedProvider::new(mock.shared(), Duration::from_secs(60));
+
+        let primary = provider.get_primary_display().unwrap();
+        assert!(provider.get_profile_data(&primary).is_err());
+        assert!(provider.get_profile_data(&primary).is_err());
+        assert!(provider.get_profile(&unknown).is_err());
+        assert!(provider.get_profile(&unknown).is_err());
+        mock.assert_call_count(MockMethod::GetProfileData, 2);
+        mock.assert_call_count(MockMethod::GetProfile, 2);
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let (provider, calls) = cached(Duration::from_millis(50));
+        let primary = provider.get_primary_display().unwrap();
+        provider.get_profile(&primary).unwrap();
+        provider.get_profile(&primary).unwrap();
+        calls.assert_call_count(MockMethod::GetProfile, 1);
+
+        thread::sleep(Duration::from_millis(60));
+        provider.get_profile(&primary).unwrap();
+        calls.assert_call_count(MockMethod::GetProfile, 2);
+
+        // A zero time to live disables caching
+        let (provider, calls) = cached(Duration::ZERO);
+        provider.get_displays().unwrap();
+        provider.get_displays().unwrap();
+        calls.assert_call_count(MockMethod::GetDisplays, 2);
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let (provider, calls) = cached(Duration::from_secs(60));
+        let displays = provider.get_displays().unwrap();
+        for display in &displays {
+            provider.get_profile(display).unwrap();
+        }
+
+        provider.invalidate_display("primary");
+        for display in &displays {
+            provider.get_profile(display).unwrap();
+        }
+        provider.get_displays().unwrap();
+        calls.assert_call_count(MockMethod::GetProfile, 3);
+        calls.assert_called_with(MockMethod::GetProfile, "primary");
+        calls.assert_call_count(MockMethod::GetDisplays, 1);
+
+        provider.invalidate();
+        provider.get_displays().unwrap();
+        provider.get_profile(&displays[1]).unwrap();
+        calls.assert_call_count(MockMethod::GetDisplays, 2);
+        calls.assert_call_count(MockMethod::GetProfile, 4);
+    }
+
+    #[test]
+    fn test_invalidate_on_watcher_events() {
+        let (provider, calls) = cached(Duration::from_secs(60));
+        let (sender, events) = mpsc::channel();
+        let provider = provider
+            .invalidate_on(ProfileWatcher::new(events, ()))
+            .unwrap();
+
+        let displays = provider.get_displays().unwrap();
+        provider.get_profile(&displays[0]).unwrap();
+        provider.get_profile(&displays[1]).unwrap();
+
+        /// Wait for the invalidation thread to have handled the event
+        fn settle() {
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        sender
+            .send(DisplayEvent::ProfileChanged(displays[1].clone()))
+            .unwrap();
+        settle();
+        provider.get_profile(&displays[0]).unwrap();
+        provider.get_profile(&displays[1]).unwrap();
+        provider.get_displays().unwrap();
+        calls.assert_call_count(MockMethod::GetProfile, 3);
+        calls.assert_call_count(MockMethod::GetDisplays, 1);
+
+        sender
+            .send(DisplayEvent::Added(Display::new("third", "Third", false)))
+            .unwrap();
+        settle();
+        provider.get_displays().unwrap();
+        calls.assert_call_count(MockMethod::GetDisplays, 2);
+
+        // Once the watcher ends, everything is fetched again
+        drop(sender);
+        settle();
+        provider.get_displays().unwrap();
+        provider.get_profile(&displays[0]).unwrap();
+        calls.assert_call_count(MockMethod::GetDisplays, 3);
+        calls.assert_call_count(MockMethod::GetProfile, 4);
+    }
+
+    #[test]
+    fn test_watch_invalidates_before_forwarding() {
+        let (provider, mut mock) = cached(Duration::from_secs(60));
+        let watcher = provider.watch().unwrap();
+        let primary = provider.get_primary_display().unwrap();
+        assert_eq!(
+            provider.get_profile(&primary).unwrap().name,
+            "sRGB IEC61966-2.1"
+        );
+
+        let mut replacement = provider.get_profile(&primary).unwrap();
+        replacement.name = "Calibrated".to_string();
+        mock.set_profile("primary", replacement);
+        mock.set_primary_display("secondary");
+
+        // Whichever event arrives first, its handler sees what changed
+        let event = watcher.recv_timeout(Duration::from_secs(2)).unwrap();
+        match event {
+            DisplayEvent::ProfileChanged(display) => {
+                assert_eq!(provider.get_profile(&display).unwrap().name, "Calibrated")
+            }
+            DisplayEvent::PrimaryChanged(display) => {
+                assert_eq!(provider.get_primary_display().unwrap(), display)
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        while watcher.recv_timeout(Duration::from_millis(100)).is_some() {}
+        assert_eq!(provider.get_profile(&primary).unwrap().name, "Calibrated");
+        assert_eq!(provider.get_primary_display().unwrap().id, "secondary");
+    }
+}
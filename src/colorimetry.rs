@@ -0,0 +1,380 @@
+//! Colorimetric summary of a display ICC profile.
+//!
+//! [`Colorimetry::from_icc`] reads the tags of a matrix/TRC display profile and
+//! reports the values most users care about: the display white point (with its
+//! correlated color temperature and distance from the Planckian locus), the
+//! chromaticities of the primaries, the effective gamma of each channel, the
+//! luminance and whether the profile carries a `vcgt` calibration curve.
+//!
+//! White point and primaries are reported for the display itself, not for the
+//! D50 profile connection space: the `chad` tag (or, for older profiles, a
+//! Bradford adaptation from the media white point) is undone before conversion
+//! to CIE 1931 xy.
+
+use crate::icc::{self, TagTable, D50};
+use crate::ProfileError;
+
+/// A CIE 1931 xy chromaticity coordinate
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Chromaticity {
+    /// CIE 1931 x coordinate
+    pub x: f64,
+    /// CIE 1931 y coordinate
+    pub y: f64,
+}
+
+impl Chromaticity {
+    /// Convert a CIE XYZ triple to xy, returning `None` for black
+    pub fn from_xyz(xyz: [f64; 3]) -> Option<Self> {
+        let sum = xyz[0] + xyz[1] + xyz[2];
+        if sum.abs() < 1e-12 {
+            return None;
+        }
+        Some(Self {
+            x: xyz[0] / sum,
+            y: xyz[1] / sum,
+        })
+    }
+}
+
+/// The display white point with its correlated color temperature
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WhitePoint {
+    /// CIE 1931 x coordinate
+    pub x: f64,
+    /// CIE 1931 y coordinate
+    pub y: f64,
+    /// Correlated color temperature in kelvin, if within 1000–15000 K
+    pub cct: Option<f64>,
+    /// Signed distance from the Planckian locus in CIE 1960 uv (positive is greenish)
+    pub duv: Option<f64>,
+}
+
+impl WhitePoint {
+    /// Build a white point from xy, computing CCT and Duv
+    pub fn from_chromaticity(xy: Chromaticity) -> Self {
+        let (cct, duv) = match cct_duv(xy) {
+            Some((cct, duv)) => (Some(cct), Some(duv)),
+            None => (None, None),
+        };
+
+        Self {
+            x: xy.x,
+            y: xy.y,
+            cct,
+            duv,
+        }
+    }
+}
+
+/// Chromaticities of the red, green and blue primaries
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Primaries {
+    /// Red primary
+    pub red: Chromaticity,
+    /// Green primary
+    pub green: Chromaticity,
+    /// Blue primary
+    pub blue: Chromaticity,
+}
+
+/// Estimated power-law gamma of each channel's tone curve
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ChannelGamma {
+    /// Red channel gamma
+    pub red: f64,
+    /// Green channel gamma
+    pub green: f64,
+    /// Blue channel gamma
+    pub blue: f64,
+}
+
+/// Colorimetric summary of a display profile.
+///
+/// Each field is `None` when the profile lacks the tags needed to compute it,
+/// for example LUT-based profiles have no primaries or tone curves.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use display_icc::{get_primary_display_profile_data, Colorimetry};
+///
+/// # fn example() -> Result<(), display_icc::ProfileError> {
+/// let icc_data = get_primary_display_profile_data()?;
+/// let colorimetry = Colorimetry::from_icc(&icc_data)?;
+///
+/// if let Some(white) = colorimetry.white_point {
+///     println!("White point: x={:.4} y={:.4}", white.x, white.y);
+///     if let Some(cct) = white.cct {
+///         println!("CCT: {:.0} K", cct);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Colorimetry {
+    /// Display white point
+    pub white_point: Option<WhitePoint>,
+    /// Chromaticities of the primaries
+    pub primaries: Option<Primaries>,
+    /// Estimated gamma per channel
+    pub gamma: Option<ChannelGamma>,
+    /// White luminance in cd/m² from the `lumi` tag
+    pub luminance: Option<f64>,
+    /// Whether the profile carries a `vcgt` video card calibration curve
+    pub has_vcgt: bool,
+}
+
+impl Colorimetry {
+    /// Compute the colorimetric summary from raw ICC profile data
+    pub fn from_icc(data: &[u8]) -> Result<Self, ProfileError> {
+        let tags = TagTable::parse(data)?;
+
+        let media_white = tags.get(b"wtpt").and_then(icc::read_xyz);
+        let to_display = adaptation_to_display(&tags, media_white);
+
+        let white_xyz = match (&to_display, media_white) {
+            (Some(matrix), Some(white)) if tags.contains(b"chad") => {
+                Some(icc::mul_vector(matrix, white))
+            }
+            (_, white) => white,
+        };
+        let white_point = white_xyz
+            .and_then(Chromaticity::from_xyz)
+            .map(WhitePoint::from_chromaticity);
+
+        let colorant = |signature: &[u8; 4]| -> Option<Chromaticity> {
+            let pcs = tags.get(signature).and_then(icc::read_xyz)?;
+            let xyz = match &to_display {
+                Some(matrix) => icc::mul_vector(matrix, pcs),
+                None => pcs,
+            };
+            Chromaticity::from_xyz(xyz)
+        };
+        let primaries = match (colorant(b"rXYZ"), colorant(b"gXYZ"), colorant(b"bXYZ")) {
+            (Some(red), Some(green), Some(blue)) => Some(Primaries { red, green, blue }),
+            _ => None,
+        };
+
+        let channel_gamma = |signature: &[u8; 4]| -> Option<f64> {
+            tags.get(signature)
+                .and_then(icc::read_curve)
+                .and_then(|curve| curve.estimate_gamma())
+        };
+        let gamma = match (
+            channel_gamma(b"rTRC"),
+            channel_gamma(b"gTRC"),
+            channel_gamma(b"bTRC"),
+        ) {
+            (Some(red), Some(green), Some(blue)) => Some(ChannelGamma { red, green, blue }),
+            _ => None,
+        };
+
+        let luminance = tags.get(b"lumi").and_then(icc::read_xyz).map(|xyz| xyz[1]);
+
+        Ok(Self {
+            white_point,
+            primaries,
+            gamma,
+            luminance,
+            has_vcgt: tags.contains(b"vcgt"),
+        })
+    }
+}
+
+/// Matrix converting PCS-relative XYZ back to the display's own white.
+///
+/// Uses the inverse of `chad` when present. Otherwise, if the media white point
+/// differs from D50 (v2 profiles), a Bradford adaptation from D50 to it is used.
+fn adaptation_to_display(
+    tags: &TagTable<'_>,
+    media_white: Option<[f64; 3]>,
+) -> Option<icc::Matrix3> {
+    if let Some(chad) = tags.get(b"chad").and_then(icc::read_matrix) {
+        return icc::invert(&chad);
+    }
+
+    let white = media_white?;
+    let is_d50 = white
+        .iter()
+        .zip(D50.iter())
+        .all(|(a, b)| (a - b).abs() < 1e-3);
+    if is_d50 {
+        None
+    } else {
+        icc::bradford(D50, white)
+    }
+}
+
+/// Convert xy to CIE 1960 uv
+fn xy_to_uv(xy: Chromaticity) -> (f64, f64) {
+    let denominator = -2.0 * xy.x + 12.0 * xy.y + 3.0;
+    (4.0 * xy.x / denominator, 6.0 * xy.y / denominator)
+}
+
+/// Planckian locus in CIE 1960 uv (Krystek 1985 approximation, 1000–15000 K)
+fn planckian_uv(t: f64) -> (f64, f64) {
+    let u = (0.860117757 + 1.54118254e-4 * t + 1.28641212e-7 * t * t)
+        / (1.0 + 8.42420235e-4 * t + 7.08145163e-7 * t * t);
+    let v = (0.317398726 + 4.22806245e-5 * t + 4.20481691e-8 * t * t)
+        / (1.0 - 2.89741816e-5 * t + 1.61456053e-7 * t * t);
+    (u, v)
+}
+
+/// Correlated color temperature and Duv of a chromaticity.
+///
+/// Finds the closest point on the Planckian locus by a coarse scan in mired
+/// space followed by golden-section refinement. Returns `None` if the closest
+/// point lies at either end of the supported range or Duv exceeds 0.05, where
+/// CCT is not meaningful.
+fn cct_duv(xy: Chromaticity) -> Option<(f64, f64)> {
+    const MIN_T: f64 = 1000.0;
+    const MAX_T: f64 = 15000.0;
+
+    let (u, v) = xy_to_uv(xy);
+    let distance = |t: f64| {
+        let (ut, vt) = planckian_uv(t);
+        ((u - ut).powi(2) + (v - vt).powi(2)).sqrt()
+    };
+
+    // Coarse scan in mireds, which are roughly perceptually uniform
+    let steps = 200;
+    let mired = |i: usize| 1e6 / MAX_T + (1e6 / MIN_T - 1e6 / MAX_T) * i as f64 / steps as f64;
+    let best = (0..=steps)
+        .min_by(|&a, &b| distance(1e6 / mired(a)).total_cmp(&distance(1e6 / mired(b))))?;
+    if best == 0 || best == steps {
+        return None;
+    }
+
+    // Golden-section search between the neighbouring samples
+    let (mut low, mut high) = (1e6 / mired(best + 1), 1e6 / mired(best - 1));
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..60 {
+        let a = high - ratio * (high - low);
+        let b = low + ratio * (high - low);
+        if distance(a) < distance(b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+
+    let cct = (low + high) / 2.0;
+    let (_, vt) = planckian_uv(cct);
+    let duv = distance(cct).copysign(v - vt);
+    if duv.abs() > 0.05 {
+        return None;
+    }
+
+    Some((cct, duv))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icc::test_support::*;
+
+    /// sRGB colorants adapted to D50, as found in typical v4 sRGB profiles
+    const SRGB_RXYZ: [f64; 3] = [0.4361, 0.2225, 0.0139];
+    const SRGB_GXYZ: [f64; 3] = [0.3851, 0.7169, 0.0971];
+    const SRGB_BXYZ: [f64; 3] = [0.1431, 0.0606, 0.7141];
+
+    /// Bradford D65 -> D50 matrix as stored in the `chad` tag of sRGB profiles
+    const SRGB_CHAD: [[f64; 3]; 3] = [
+        [1.0479, 0.0229, -0.0502],
+        [0.0296, 0.9904, -0.0171],
+        [-0.0092, 0.0151, 0.7519],
+    ];
+
+    fn srgb_profile() -> Vec<u8> {
+        profile(&[
+            (b"wtpt", xyz(D50)),
+            (b"chad", sf32(SRGB_CHAD)),
+            (b"rXYZ", xyz(SRGB_RXYZ)),
+            (b"gXYZ", xyz(SRGB_GXYZ)),
+            (b"bXYZ", xyz(SRGB_BXYZ)),
+            (b"rTRC", gamma_curve(2.2)),
+            (b"gTRC", gamma_curve(2.2)),
+            (b"bTRC", gamma_curve(2.2)),
+            (b"lumi", xyz([0.0, 120.0, 0.0])),
+        ])
+    }
+
+    #[test]
+    fn test_cct_of_standard_illuminants() {
+        let (cct, duv) = cct_duv(Chromaticity {
+            x: 0.3127,
+            y: 0.3290,
+        })
+        .unwrap();
+        assert!((cct - 6504.0).abs() < 15.0, "D65 CCT was {}", cct);
+        assert!((duv - 0.0032).abs() < 0.0005, "D65 Duv was {}", duv);
+
+        let (cct, _) = cct_duv(Chromaticity {
+            x: 0.3457,
+            y: 0.3585,
+        })
+        .unwrap();
+        assert!((cct - 5003.0).abs() < 15.0, "D50 CCT was {}", cct);
+    }
+
+    #[test]
+    fn test_cct_out_of_range() {
+        // Pure green primary is nowhere near the Planckian locus
+        assert!(cct_duv(Chromaticity { x: 0.3, y: 0.6 }).is_none());
+    }
+
+    #[test]
+    fn test_srgb_profile_colorimetry() {
+        let colorimetry = Colorimetry::from_icc(&srgb_profile()).unwrap();
+
+        let white = colorimetry.white_point.unwrap();
+        assert!((white.x - 0.3127).abs() < 0.001, "white x was {}", white.x);
+        assert!((white.y - 0.3290).abs() < 0.001, "white y was {}", white.y);
+        assert!((white.cct.unwrap() - 6500.0).abs() < 50.0);
+
+        let primaries = colorimetry.primaries.unwrap();
+        assert!((primaries.red.x - 0.64).abs() < 0.002);
+        assert!((primaries.red.y - 0.33).abs() < 0.002);
+        assert!((primaries.green.x - 0.30).abs() < 0.002);
+        assert!((primaries.green.y - 0.60).abs() < 0.002);
+        assert!((primaries.blue.x - 0.15).abs() < 0.002);
+        assert!((primaries.blue.y - 0.06).abs() < 0.002);
+
+        let gamma = colorimetry.gamma.unwrap();
+        assert!((gamma.red - 2.2).abs() < 0.01);
+        assert!((gamma.blue - 2.2).abs() < 0.01);
+
+        assert_eq!(colorimetry.luminance, Some(120.0));
+        assert!(!colorimetry.has_vcgt);
+    }
+
+    #[test]
+    fn test_v2_profile_without_chad_uses_media_white() {
+        let d65 = [0.9505, 1.0, 1.0891];
+        let data = profile(&[(b"wtpt", xyz(d65)), (b"vcgt", vec![0; 12])]);
+        let colorimetry = Colorimetry::from_icc(&data).unwrap();
+
+        let white = colorimetry.white_point.unwrap();
+        assert!((white.x - 0.3127).abs() < 0.001);
+        assert!(colorimetry.primaries.is_none());
+        assert!(colorimetry.gamma.is_none());
+        assert!(colorimetry.has_vcgt);
+    }
+
+    #[test]
+    fn test_header_only_profile_is_rejected() {
+        assert!(Colorimetry::from_icc(&[0u8; 128]).is_err());
+    }
+}
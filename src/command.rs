@@ -0,0 +1,335 @@
+//! External command execution for the command-line backends.
+//!
+//! Backends that shell out (such as `colormgr` on Linux) go through a
+//! [`CommandRunner`] instead of calling [`std::process::Command`] directly, so
+//! callers can sandbox or log the commands and tests can replay recorded output
+//! with a fixture-backed runner.
+
+#[cfg(feature = "tokio")]
+use std::future::Future;
+use std::io::{self, Read};
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often a running child is checked for exit or timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Captured result of running an external command
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    /// Whether the command exited successfully
+    pub success: bool,
+    /// Standard output
+    pub stdout: Vec<u8>,
+    /// Standard error
+    pub stderr: Vec<u8>,
+}
+
+/// A command running without blocking, from [`CommandRunner::run_async`]
+#[cfg(feature = "tokio")]
+pub type CommandFuture<'a> = Pin<Box<dyn Future<Output = io::Result<CommandOutput>> + Send + 'a>>;
+
+/// Runs external commands on behalf of a backend
+pub trait CommandRunner: Send + Sync {
+    /// Run `program` with `args` and wait for it to finish.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::NotFound`] if the program is not
+    /// installed, or [`io::ErrorKind::TimedOut`] if it did not finish in time.
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput>;
+
+    /// Like [`run`](Self::run), but waiting without blocking the async runtime.
+    ///
+    /// The default implementation runs the command synchronously, which suits
+    /// runners that answer without starting a process.
+    #[cfg(feature = "tokio")]
+    fn run_async<'a>(&'a self, program: &'a str, args: &'a [&'a str]) -> CommandFuture<'a> {
+        Box::pin(std::future::ready(self.run(program, args)))
+    }
+}
+
+/// Runs commands as child processes.
+///
+/// Children run with `LC_ALL=C` and without `LANGUAGE`, so tools print their
+/// untranslated labels whatever the user's locale.
+///
+/// A child still running after the timeout is killed.
+#[derive(Debug)]
+pub struct ProcessRunner {
+    timeout: Duration,
+}
+
+impl ProcessRunner {
+    /// Create a runner that kills commands running longer than `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl CommandRunner for ProcessRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        let mut child = Command::new(program)
+            .args(args)
+            .env("LC_ALL", "C")
+            .env_remove("LANGUAGE")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Drain both pipes while waiting so a chatty child cannot block on a full pipe
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                // The readers are left to finish on their own: a grandchild may
+                // still hold the pipes open after the child is gone
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{} did not finish within {:?}", program, self.timeout),
+                ));
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        };
+
+        Ok(CommandOutput {
+            success: status.success(),
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    fn run_async<'a>(&'a self, program: &'a str, args: &'a [&'a str]) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let child = tokio::process::Command::new(program)
+                .args(args)
+                .env("LC_ALL", "C")
+                .env_remove("LANGUAGE")
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()?;
+
+            // Dropping the unfinished child on timeout kills it
+            match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+                Ok(output) => {
+                    let output = output?;
+                    Ok(CommandOutput {
+                        success: output.status.success(),
+                        stdout: output.stdout,
+                        stderr: output.stderr,
+                    })
+                }
+                Err(_) => Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{} did not finish within {:?}", program, self.timeout),
+                )),
+            }
+        })
+    }
+}
+
+/// Read a child's pipe to the end on a separate thread
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+#[cfg(test)]
+pub(crate) use fixture::FixtureRunner;
+
+#[cfg(test)]
+mod fixture {
+    use super::{CommandOutput, CommandRunner};
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    /// Replays recorded command output from a fixture directory.
+    ///
+    /// `program arg1 arg2` is answered with the contents of `program_arg1_arg2.txt`,
+    /// with characters other than ASCII letters, digits, `.`, `-` and `_` replaced
+    /// by `_`. Commands without a recording fail as if the program were not
+    /// installed.
+    ///
+    /// Profile paths on `Filename:` lines are moved under the fixture's `root/`
+    /// directory, so transcripts stay verbatim while the profiles they point to
+    /// are read from the checked-in tree.
+    pub(crate) struct FixtureRunner {
+        dir: PathBuf,
+    }
+
+    impl FixtureRunner {
+        /// Create a runner for a directory under `tests/fixtures`
+        pub(crate) fn new(fixture: &str) -> Self {
+            Self {
+                dir: Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/fixtures")
+                    .join(fixture),
+            }
+        }
+
+        fn transcript_path(&self, program: &str, args: &[&str]) -> PathBuf {
+            let name: String = std::iter::once(program)
+                .chain(args.iter().copied())
+                .collect::<Vec<_>>()
+                .join("_")
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+
+            self.dir.join(format!("{}.txt", name))
+        }
+
+        fn rebase_filenames(&self, transcript: &str) -> String {
+            let root = self.dir.join("root");
+
+            transcript
+                .lines()
+                .map(|line| match line.split_once("Filename:") {
+                    Some((label, value)) if value.trim().starts_with('/') => {
+                        let path = value.trim_start();
+                        let padding = &value[..value.len() - path.len()];
+                        let rebased = root.join(path.trim_end().trim_start_matches('/'));
+                        format!("{}Filename:{}{}", label, padding, rebased.display())
+                    }
+                    _ => line.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+
+    impl CommandRunner for FixtureRunner {
+        fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+            let path = self.transcript_path(program, args);
+            let transcript = std::fs::read_to_string(&path).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no recorded output at {}", path.display()),
+                )
+            })?;
+
+            Ok(CommandOutput {
+                success: true,
+                stdout: self.rebase_filenames(&transcript).into_bytes(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runner() -> ProcessRunner {
+        ProcessRunner::new(Duration::from_secs(5))
+    }
+
+    #[test]
+    fn test_process_runner_missing_program() {
+        let err = runner()
+            .run("display-icc-no-such-program", &[])
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_process_runner_forces_c_locale() {
+        let output = runner()
+            .run("sh", &["-c", "echo \"$LC_ALL:${LANGUAGE-unset}\""])
+            .unwrap();
+        assert!(output.success);
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "C:unset");
+    }
+
+    #[test]
+    fn test_process_runner_captures_output() {
+        let output = runner()
+            .run("sh", &["-c", "echo out; echo err >&2; exit 3"])
+            .unwrap();
+        assert!(!output.success);
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn test_process_runner_kills_on_timeout() {
+        let start = Instant::now();
+        let err = ProcessRunner::new(Duration::from_millis(100))
+            .run("sleep", &["10"])
+            .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_process_runner_async() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let output = runner()
+                .run_async("sh", &["-c", "echo \"$LC_ALL\"; echo err >&2; exit 3"])
+                .await
+                .unwrap();
+            assert!(!output.success);
+            assert_eq!(output.stdout, b"C\n");
+            assert_eq!(output.stderr, b"err\n");
+
+            let missing = runner().run_async("display-icc-no-such-program", &[]).await;
+            assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+            let start = Instant::now();
+            let err = ProcessRunner::new(Duration::from_millis(100))
+                .run_async("sleep", &["10"])
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+            assert!(start.elapsed() < Duration::from_secs(5));
+        });
+    }
+
+    #[test]
+    fn test_fixture_runner_replays_and_rebases() {
+        let runner = FixtureRunner::new("colormgr/ubuntu-22.04");
+
+        let output = runner.run("colormgr", &["--version"]).unwrap();
+        assert!(output.success);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("1.4.6"));
+
+        let devices = runner.run("colormgr", &["get-devices"]).unwrap();
+        let devices = String::from_utf8(devices.stdout).unwrap();
+        assert!(devices.contains("Device ID:"));
+
+        let unknown = runner.run("colormgr", &["get-sensors"]).unwrap_err();
+        assert_eq!(unknown.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,647 @@
+//! Display identification from EDID data.
+//!
+//! [`Edid::parse`] decodes the 128-byte base block every monitor reports:
+//! the manufacturer's PNP ID, product code, serial number, manufacture date
+//! and physical size, plus the name and serial string descriptors and the
+//! preferred (native) timing. CTA-861 and DisplayID extension blocks are
+//! consulted for whatever the base block leaves out, such as the native timing
+//! of panels whose base block only carries legacy modes or the product name of
+//! DisplayID-only eDP panels.
+//!
+//! The base block also carries the panel's chromaticities and gamma, from
+//! which [`profile_from_edid`] builds a matrix/TRC ICC profile for displays
+//! nobody has profiled.
+//!
+//! Checksums are not verified: the kernel hands out EDIDs as the monitor
+//! sent them, and enough monitors ship with wrong checksums that rejecting
+//! them would lose more than it protects against.
+
+use crate::icc;
+use crate::{Chromaticity, Primaries, ProfileError};
+
+/// The fixed pattern every EDID base block starts with
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+/// Size of the base block and of each extension block
+const BLOCK_SIZE: usize = 128;
+
+/// Extension block tags
+const CTA_861: u8 = 0x02;
+const DISPLAY_ID: u8 = 0x70;
+
+/// Identification and geometry decoded from an EDID
+///
+/// # Examples
+///
+/// ```
+/// use display_icc::{Display, Edid};
+///
+/// # fn example(display: &Display) -> Result<(), display_icc::ProfileError> {
+/// if let Some(data) = display.edid() {
+///     let edid = Edid::parse(data)?;
+///     println!("{} {:04x}", edid.manufacturer, edid.product_code);
+///     if let Some((width, height)) = edid.native_resolution {
+///         println!("native {}x{}", width, height);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Edid {
+    /// Three-letter PNP manufacturer ID, e.g. `DEL` or `BOE`
+    pub manufacturer: String,
+    /// Manufacturer's product code
+    pub product_code: u16,
+    /// Numeric serial number, if the monitor reports one
+    pub serial_number: Option<u32>,
+    /// Serial number string from the display serial descriptor
+    pub serial: Option<String>,
+    /// Product name from the display name descriptor, e.g. `DELL U2720Q`
+    pub name: Option<String>,
+    /// Week of manufacture (1–54), if reported
+    pub manufacture_week: Option<u8>,
+    /// Year of manufacture, or the model year when no week is given
+    pub manufacture_year: Option<u16>,
+    /// Physical size of the image area as `[width, height]` in millimetres
+    pub size_mm: Option<(u32, u32)>,
+    /// Resolution of the preferred timing as `[width, height]` in pixels
+    pub native_resolution: Option<(u32, u32)>,
+    /// Chromaticities of the panel's primaries, if reported
+    pub primaries: Option<Primaries>,
+    /// Chromaticity of the panel's white point, if reported
+    pub white_point: Option<Chromaticity>,
+    /// Display transfer characteristic (gamma), if reported
+    pub gamma: Option<f64>,
+}
+
+impl Edid {
+    /// Decode an EDID, including any extension blocks that follow the base block
+    pub fn parse(data: &[u8]) -> Result<Self, ProfileError> {
+        if data.len() < BLOCK_SIZE || data[..8] != HEADER {
+            return Err(ProfileError::ParseError(
+                "Not an EDID: missing base block header".to_string(),
+            ));
+        }
+        let base = &data[..BLOCK_SIZE];
+
+        let mut edid = Self {
+            manufacturer: pnp_id(u16::from_be_bytes([base[8], base[9]])),
+            product_code: u16::from_le_bytes([base[10], base[11]]),
+            serial_number: Some(u32::from_le_bytes([base[12], base[13], base[14], base[15]]))
+                .filter(|&serial| serial != 0),
+            serial: None,
+            name: None,
+            manufacture_week: None,
+            manufacture_year: None,
+            size_mm: None,
+            native_resolution: None,
+            primaries: None,
+            white_point: None,
+            gamma: None,
+        };
+
+        // Week 0xff flags the year as a model year
+        match base[16] {
+            0 | 0xff => {}
+            week => edid.manufacture_week = Some(week),
+        }
+        if base[17] != 0 {
+            edid.manufacture_year = Some(1990 + u16::from(base[17]));
+        }
+
+        // Byte 23 holds gamma × 100 − 100, 0xff meaning it is given elsewhere
+        if base[23] != 0xff {
+            edid.gamma = Some((f64::from(base[23]) + 100.0) / 100.0);
+        }
+
+        // Ten-bit chromaticities: the high eight bits in bytes 27–34, the low
+        // two bits of each packed into bytes 25 and 26
+        let chromaticity = |index: usize| {
+            let low = (base[25 + index / 4] >> (6 - 2 * (index % 4))) & 0x03;
+            let value = (u16::from(base[27 + index]) << 2) | u16::from(low);
+            f64::from(value) / 1024.0
+        };
+        let xy = |index: usize| Chromaticity {
+            x: chromaticity(index),
+            y: chromaticity(index + 1),
+        };
+        if base[25..35].iter().any(|&b| b != 0) {
+            edid.primaries = Some(Primaries {
+                red: xy(0),
+                green: xy(2),
+                blue: xy(4),
+            });
+            edid.white_point = Some(xy(6));
+        }
+
+        for descriptor in base[54..126].chunks_exact(18) {
+            match descriptor {
+                [0, 0, 0, 0xfc, _, text @ ..] => edid.name = descriptor_text(text),
+                [0, 0, 0, 0xff, _, text @ ..] => edid.serial = descriptor_text(text),
+                [0, 0, ..] => {}
+                timing if edid.native_resolution.is_none() => {
+                    let timing = DetailedTiming::parse(timing);
+                    edid.native_resolution = Some(timing.resolution);
+                    edid.size_mm = timing.size_mm;
+                }
+                _ => {}
+            }
+        }
+
+        let extensions = usize::from(base[126]);
+        for block in data[BLOCK_SIZE..].chunks_exact(BLOCK_SIZE).take(extensions) {
+            match block[0] {
+                CTA_861 => edid.merge_cta(block),
+                DISPLAY_ID => edid.merge_display_id(block),
+                _ => {}
+            }
+        }
+
+        // Timings give the image size in millimetres; the base block only has centimetres
+        if edid.size_mm.is_none() && base[21] != 0 && base[22] != 0 {
+            edid.size_mm = Some((u32::from(base[21]) * 10, u32::from(base[22]) * 10));
+        }
+
+        Ok(edid)
+    }
+
+    /// Take the first detailed timing of a CTA-861 block when the base block had none
+    fn merge_cta(&mut self, block: &[u8]) {
+        // Byte 2 is where the detailed timings start, 0 meaning there are none
+        let start = usize::from(block[2]);
+        if !(4..BLOCK_SIZE - 1).contains(&start) {
+            return;
+        }
+        let first = block[start..BLOCK_SIZE - 1]
+            .chunks_exact(18)
+            .find(|timing| timing[0] != 0 || timing[1] != 0);
+        if let (None, Some(timing)) = (self.native_resolution, first) {
+            let timing = DetailedTiming::parse(timing);
+            self.native_resolution = Some(timing.resolution);
+            if self.size_mm.is_none() {
+                self.size_mm = timing.size_mm;
+            }
+        }
+    }
+
+    /// Take the product name and preferred timing of a DisplayID 1.3 or 2.0 section
+    fn merge_display_id(&mut self, block: &[u8]) {
+        // Section header: version, payload length, product type, extension count
+        let length = usize::from(block[2]);
+        let end = (5 + length).min(BLOCK_SIZE - 1);
+        let mut offset = 5;
+
+        while offset + 3 <= end {
+            let tag = block[offset];
+            let size = usize::from(block[offset + 2]);
+            let Some(payload) = block.get(offset + 3..offset + 3 + size) else {
+                break;
+            };
+            if tag == 0 && size == 0 {
+                // Padding after the last data block
+                break;
+            }
+
+            match tag {
+                // Product identification (1.x and 2.0): ID, product code, serial,
+                // week, year, then a length-prefixed name
+                0x00 | 0x20 if payload.len() >= 12 => {
+                    let name_length = usize::from(payload[11]);
+                    if self.name.is_none() {
+                        self.name = payload.get(12..12 + name_length).and_then(descriptor_text);
+                    }
+                }
+                // Type I (1.x) and Type VII (2.0) detailed timings, 20 bytes each
+                0x03 | 0x22 if self.native_resolution.is_none() => {
+                    let timings: Vec<&[u8]> = payload.chunks_exact(20).collect();
+                    // Bit 7 of the options byte marks the preferred timing
+                    let preferred = timings
+                        .iter()
+                        .find(|timing| timing[3] & 0x80 != 0)
+                        .or(timings.first());
+                    if let Some(timing) = preferred {
+                        let width = u32::from(u16::from_le_bytes([timing[4], timing[5]])) + 1;
+                        let height = u32::from(u16::from_le_bytes([timing[12], timing[13]])) + 1;
+                        self.native_resolution = Some((width, height));
+                    }
+                }
+                _ => {}
+            }
+            offset += 3 + size;
+        }
+    }
+}
+
+/// Build an ICC profile from the colorimetry an EDID reports.
+///
+/// The result is a v4 matrix/TRC display profile: the primaries and white
+/// point of the base block, adapted to D50 with Bradford, and the reported
+/// gamma as a pure power curve for each channel (2.2 when the EDID gives
+/// none). It is tagged with the monitor's name and manufacturer (`dmdd`,
+/// `dmnd`) and described as `<name> (EDID)`.
+///
+/// Such a profile is only as good as the factory values, and many monitors
+/// report generic sRGB ones; it is a better default than assuming sRGB, not a
+/// substitute for measuring the display. The profile is dated with the
+/// manufacture year, so the same EDID always yields the same bytes.
+///
+/// Fails with [`ProfileError::ParseError`] if `data` is not an EDID or
+/// reports no usable chromaticities.
+///
+/// # Examples
+///
+/// ```
+/// use display_icc::{profile_from_edid, Colorimetry, Display};
+///
+/// # fn example(display: &Display) -> Result<(), display_icc::ProfileError> {
+/// if let Some(edid) = display.edid() {
+///     let icc = profile_from_edid(edid)?;
+///     let colorimetry = Colorimetry::from_icc(&icc)?;
+///     println!("{:?}", colorimetry.primaries);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn profile_from_edid(data: &[u8]) -> Result<Vec<u8>, ProfileError> {
+    let edid = Edid::parse(data)?;
+    let no_colorimetry =
+        || ProfileError::ParseError("EDID reports no usable chromaticities".to_string());
+    let (Some(primaries), Some(white)) = (edid.primaries, edid.white_point) else {
+        return Err(no_colorimetry());
+    };
+
+    let name = edid
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{} {:04x}", edid.manufacturer, edid.product_code));
+    let year = edid.manufacture_year.unwrap_or(1990);
+
+    let mut tags = vec![
+        (b"desc", icc::mluc(&format!("{} (EDID)", name))),
+        (b"cprt", icc::mluc("No copyright, use freely")),
+        (b"dmnd", icc::mluc(&edid.manufacturer)),
+        (b"dmdd", icc::mluc(&name)),
+    ];
+    tags.extend(
+        icc::matrix_tags(primaries, white, edid.gamma.unwrap_or(2.2)).ok_or_else(no_colorimetry)?,
+    );
+    Ok(icc::write_profile(&tags, [year, 1, 1, 0, 0, 0]))
+}
+
+/// The parts of an 18-byte detailed timing descriptor used for identification
+struct DetailedTiming {
+    resolution: (u32, u32),
+    size_mm: Option<(u32, u32)>,
+}
+
+impl DetailedTiming {
+    fn parse(timing: &[u8]) -> Self {
+        let width = u32::from(timing[2]) | (u32::from(timing[4] & 0xf0) << 4);
+        let height = u32::from(timing[5]) | (u32::from(timing[7] & 0xf0) << 4);
+        let width_mm = u32::from(timing[12]) | (u32::from(timing[14] & 0xf0) << 4);
+        let height_mm = u32::from(timing[13]) | (u32::from(timing[14] & 0x0f) << 8);
+
+        Self {
+            resolution: (width, height),
+            size_mm: (width_mm != 0 && height_mm != 0).then_some((width_mm, height_mm)),
+        }
+    }
+}
+
+/// Decode the PNP ID packed into three 5-bit letters, `A` being 1
+fn pnp_id(packed: u16) -> String {
+    [10, 5, 0]
+        .iter()
+        .map(|shift| match (packed >> shift) & 0x1f {
+            letter @ 1..=26 => char::from(b'A' + letter as u8 - 1),
+            _ => '?',
+        })
+        .collect()
+}
+
+/// Text of a descriptor, which ends at a newline and is padded with spaces
+fn descriptor_text(text: &[u8]) -> Option<String> {
+    let end = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+    let text: String = text[..end]
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() {
+                char::from(b)
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    //! Helpers for assembling EDIDs in tests
+
+    /// sRGB primaries and D65 white
+    pub(crate) const SRGB: [(f64, f64); 4] =
+        [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06), (0.3127, 0.3290)];
+
+    /// A monitor as described by an EDID base block
+    pub(crate) struct TestMonitor<'a> {
+        pub manufacturer: &'a str,
+        pub product_code: u16,
+        pub serial_number: u32,
+        pub name: &'a str,
+        pub serial: &'a str,
+        pub resolution: (u16, u16),
+        pub size_mm: (u16, u16),
+        /// Red, green, blue and white xy, in that order
+        pub chromaticities: [(f64, f64); 4],
+        pub gamma: f64,
+    }
+
+    impl Default for TestMonitor<'_> {
+        fn default() -> Self {
+            Self {
+                manufacturer: "DEL",
+                product_code: 0xa0e8,
+                serial_number: 0x4c4d3253,
+                name: "DELL U2720Q",
+                serial: "8LX2N63",
+                resolution: (3840, 2160),
+                size_mm: (597, 336),
+                chromaticities: SRGB,
+                gamma: 2.2,
+            }
+        }
+    }
+
+    /// An 18-byte detailed timing descriptor for `resolution` at `size_mm`
+    pub(crate) fn detailed_timing(resolution: (u16, u16), size_mm: (u16, u16)) -> [u8; 18] {
+        let ((width, height), (width_mm, height_mm)) = (resolution, size_mm);
+        let mut timing = [0u8; 18];
+        timing[0..2].copy_from_slice(&59400u16.to_le_bytes());
+        timing[2] = width as u8;
+        timing[4] = ((width >> 8) as u8) << 4;
+        timing[5] = height as u8;
+        timing[7] = ((height >> 8) as u8) << 4;
+        timing[12] = width_mm as u8;
+        timing[13] = height_mm as u8;
+        timing[14] = (((width_mm >> 8) as u8) << 4) | (height_mm >> 8) as u8;
+        timing
+    }
+
+    /// An 18-byte text descriptor with tag `tag`
+    pub(crate) fn text_descriptor(tag: u8, text: &str) -> [u8; 18] {
+        let mut descriptor = [0x20u8; 18];
+        descriptor[..5].copy_from_slice(&[0, 0, 0, tag, 0]);
+        let text = text.as_bytes();
+        descriptor[5..5 + text.len()].copy_from_slice(text);
+        if text.len() < 13 {
+            descriptor[5 + text.len()] = b'\n';
+        }
+        descriptor
+    }
+
+    /// Set the checksum byte so that the block sums to zero
+    pub(crate) fn checksum(block: &mut [u8]) {
+        let sum = block[..127].iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        block[127] = 0u8.wrapping_sub(sum);
+    }
+
+    /// An EDID base block for `monitor`, followed by `extensions`
+    pub(crate) fn edid(monitor: &TestMonitor, extensions: &[[u8; 128]]) -> Vec<u8> {
+        let mut block = [0u8; 128];
+        block[..8].copy_from_slice(&super::HEADER);
+        let letters = monitor.manufacturer.as_bytes();
+        let packed = letters.iter().fold(0u16, |packed, &letter| {
+            (packed << 5) | u16::from(letter - b'A' + 1)
+        });
+        block[8..10].copy_from_slice(&packed.to_be_bytes());
+        block[10..12].copy_from_slice(&monitor.product_code.to_le_bytes());
+        block[12..16].copy_from_slice(&monitor.serial_number.to_le_bytes());
+        block[16] = 12;
+        block[17] = 30;
+        block[18..20].copy_from_slice(&[1, 4]);
+        block[21] = (monitor.size_mm.0 / 10) as u8;
+        block[22] = (monitor.size_mm.1 / 10) as u8;
+        block[23] = (monitor.gamma * 100.0 - 100.0).round() as u8;
+        let values = monitor.chromaticities.iter().flat_map(|&(x, y)| [x, y]);
+        for (index, value) in values.enumerate() {
+            let value = (value * 1024.0).round() as u16;
+            block[27 + index] = (value >> 2) as u8;
+            block[25 + index / 4] |= ((value & 0x03) as u8) << (6 - 2 * (index % 4));
+        }
+
+        let descriptors = [
+            detailed_timing(monitor.resolution, monitor.size_mm),
+            text_descriptor(0xfc, monitor.name),
+            text_descriptor(0xff, monitor.serial),
+            text_descriptor(0x10, ""),
+        ];
+        for (index, descriptor) in descriptors.iter().enumerate() {
+            block[54 + index * 18..72 + index * 18].copy_from_slice(descriptor);
+        }
+        block[126] = extensions.len() as u8;
+        checksum(&mut block);
+
+        let mut data = block.to_vec();
+        for extension in extensions {
+            data.extend_from_slice(extension);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::*;
+    use super::*;
+
+    #[test]
+    fn test_parse_base_block() {
+        let edid = Edid::parse(&edid(&TestMonitor::default(), &[])).unwrap();
+        assert_eq!(edid.manufacturer, "DEL");
+        assert_eq!(edid.product_code, 0xa0e8);
+        assert_eq!(edid.serial_number, Some(0x4c4d3253));
+        assert_eq!(edid.serial.as_deref(), Some("8LX2N63"));
+        assert_eq!(edid.name.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(edid.manufacture_week, Some(12));
+        assert_eq!(edid.manufacture_year, Some(2020));
+        assert_eq!(edid.size_mm, Some((597, 336)));
+        assert_eq!(edid.native_resolution, Some((3840, 2160)));
+        assert_eq!(edid.gamma, Some(2.2));
+
+        // Ten bits leave the chromaticities within 1/2048 of the sRGB values
+        let primaries = edid.primaries.unwrap();
+        let white = edid.white_point.unwrap();
+        let decoded = [primaries.red, primaries.green, primaries.blue, white];
+        for (xy, (x, y)) in decoded.iter().zip(SRGB) {
+            assert!((xy.x - x).abs() < 0.0005 && (xy.y - y).abs() < 0.0005);
+        }
+    }
+
+    #[test]
+    fn test_parse_without_colorimetry() {
+        let mut data = edid(&TestMonitor::default(), &[]);
+        data[23] = 0xff;
+        data[25..35].fill(0);
+
+        let edid = Edid::parse(&data).unwrap();
+        assert_eq!(edid.gamma, None);
+        assert_eq!(edid.primaries, None);
+        assert_eq!(edid.white_point, None);
+        assert!(matches!(
+            profile_from_edid(&data),
+            Err(ProfileError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_profile_from_edid() {
+        // A wide-gamut panel with a D65 white and gamma 2.4
+        let monitor = TestMonitor {
+            chromaticities: [(0.68, 0.32), (0.265, 0.69), (0.15, 0.06), (0.3127, 0.3290)],
+            gamma: 2.4,
+            ..TestMonitor::default()
+        };
+        let data = edid(&monitor, &[]);
+        let icc = profile_from_edid(&data).unwrap();
+
+        let header = crate::parse_icc_header(&icc).unwrap();
+        assert_eq!(header.profile_size as usize, icc.len());
+        assert_eq!(header.version, (4, 3));
+        assert_eq!(header.device_class, "mntr");
+        assert_eq!(
+            header.creation_datetime.as_deref(),
+            Some("2020-01-01 00:00:00")
+        );
+
+        let tags = icc::TagTable::parse(&icc).unwrap();
+        let text = |signature: &[u8; 4]| tags.get(signature).and_then(icc::read_text);
+        assert_eq!(text(b"desc").as_deref(), Some("DELL U2720Q (EDID)"));
+        assert_eq!(text(b"dmnd").as_deref(), Some("DEL"));
+        assert_eq!(text(b"dmdd").as_deref(), Some("DELL U2720Q"));
+        assert!(text(b"cprt").is_some());
+
+        // The profile ID is the MD5 of the profile with the ID zeroed
+        let mut unsigned = icc.clone();
+        unsigned[84..100].fill(0);
+        assert_eq!(icc[84..100], crate::md5::digest(&unsigned));
+
+        // The display's own colorimetry comes back out, through the chad tag
+        let colorimetry = crate::Colorimetry::from_icc(&icc).unwrap();
+        let primaries = colorimetry.primaries.unwrap();
+        let white = colorimetry.white_point.unwrap();
+        let decoded = [
+            (primaries.red.x, primaries.red.y),
+            (primaries.green.x, primaries.green.y),
+            (primaries.blue.x, primaries.blue.y),
+            (white.x, white.y),
+        ];
+        for ((x, y), (expected_x, expected_y)) in decoded.iter().zip(monitor.chromaticities) {
+            assert!((x - expected_x).abs() < 0.002, "x {} != {}", x, expected_x);
+            assert!((y - expected_y).abs() < 0.002, "y {} != {}", y, expected_y);
+        }
+        let gamma = colorimetry.gamma.unwrap();
+        assert!((gamma.green - 2.4).abs() < 0.01);
+
+        // Generation is deterministic
+        assert_eq!(profile_from_edid(&data).unwrap(), icc);
+    }
+
+    #[test]
+    fn test_parse_rejects_non_edid() {
+        assert!(matches!(
+            Edid::parse(&[0u8; 128]),
+            Err(ProfileError::ParseError(_))
+        ));
+        let data = edid(&TestMonitor::default(), &[]);
+        assert!(Edid::parse(&data[..100]).is_err());
+    }
+
+    #[test]
+    fn test_parse_without_descriptors() {
+        let mut data = edid(
+            &TestMonitor {
+                serial_number: 0,
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        // Replace the name and serial descriptors with dummies, and the date with a model year
+        data[16] = 0xff;
+        for offset in [72, 90] {
+            data[offset..offset + 18].copy_from_slice(&text_descriptor(0x10, ""));
+        }
+
+        let edid = Edid::parse(&data).unwrap();
+        assert_eq!(edid.serial_number, None);
+        assert_eq!(edid.serial, None);
+        assert_eq!(edid.name, None);
+        assert_eq!(edid.manufacture_week, None);
+        assert_eq!(edid.manufacture_year, Some(2020));
+    }
+
+    #[test]
+    fn test_parse_cta_extension() {
+        let mut data = edid(&TestMonitor::default(), &[]);
+        // A base block with only a dummy where the preferred timing would be
+        data[54..72].copy_from_slice(&text_descriptor(0x10, ""));
+
+        let mut cta = [0u8; 128];
+        cta[..4].copy_from_slice(&[CTA_861, 3, 4, 0]);
+        cta[4..22].copy_from_slice(&detailed_timing((2560, 1440), (597, 336)));
+        checksum(&mut cta);
+        data[126] = 1;
+        data.extend_from_slice(&cta);
+
+        let edid = Edid::parse(&data).unwrap();
+        assert_eq!(edid.native_resolution, Some((2560, 1440)));
+        assert_eq!(edid.size_mm, Some((597, 336)));
+    }
+
+    #[test]
+    fn test_parse_display_id_extension() {
+        let mut data = edid(
+            &TestMonitor {
+                name: "",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        data[54..72].copy_from_slice(&text_descriptor(0x10, ""));
+        data[72..90].copy_from_slice(&text_descriptor(0x10, ""));
+
+        // DisplayID 1.3 with a product identification and a Type I timing block
+        let name = b"LQ134R1";
+        let mut product = vec![0x00, 0x00, (12 + name.len()) as u8];
+        product.extend_from_slice(b"SHP");
+        product.extend_from_slice(&[0x4a, 0x15, 0, 0, 0, 0, 1, 21, name.len() as u8]);
+        product.extend_from_slice(name);
+        let mut timing = vec![0x03, 0x00, 20, 0x38, 0x76, 0x0d, 0x80];
+        timing.extend_from_slice(&(3839u16).to_le_bytes());
+        timing.extend_from_slice(&[0; 6]);
+        timing.extend_from_slice(&(2399u16).to_le_bytes());
+        timing.extend_from_slice(&[0; 6]);
+
+        let mut display_id = [0u8; 128];
+        let payload: Vec<u8> = [product, timing].concat();
+        display_id[..5].copy_from_slice(&[DISPLAY_ID, 0x13, payload.len() as u8, 0, 0]);
+        display_id[5..5 + payload.len()].copy_from_slice(&payload);
+        data[126] = 1;
+        data.extend_from_slice(&display_id);
+
+        let edid = Edid::parse(&data).unwrap();
+        assert_eq!(edid.name.as_deref(), Some("LQ134R1"));
+        assert_eq!(edid.native_resolution, Some((3840, 2400)));
+    }
+
+    #[test]
+    fn test_pnp_id() {
+        assert_eq!(pnp_id(0x10ac), "DEL");
+        assert_eq!(pnp_id(0x09e5), "BOE");
+        assert_eq!(pnp_id(0), "???");
+    }
+}
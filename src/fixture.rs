@@ -0,0 +1,332 @@
+//! A provider serving displays described in a file.
+//!
+//! [`FileProfileProvider`] reads a JSON or TOML description of displays and
+//! the ICC profiles assigned to them, so that tests, CI jobs and screenshots
+//! see the same displays on every machine. Each display takes the fields of
+//! [`Display`] in its JSON form, plus an optional `profile` path that is
+//! resolved relative to the description file:
+//!
+//! ```toml
+//! [[displays]]
+//! id = "DP-1"
+//! name = "DELL U2415"
+//! is_primary = true
+//! vendor = "DEL"
+//! resolution = [1920, 1200]
+//! profile = "profiles/dell-u2415.icc"
+//!
+//! [[displays]]
+//! id = "HDMI-A-1"
+//! name = "Projector"
+//! is_primary = false
+//! ```
+//!
+//! The same in JSON is `{"displays": [{"id": "DP-1", ...}]}`. The format
+//! follows the file extension; TOML needs the `toml` feature.
+
+use crate::{icc, Display, DisplayProfileProvider, ProfileError, ProfileInfo, ProfileWatcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+/// Description file contents
+#[derive(Debug, serde::Deserialize)]
+struct Description {
+    #[serde(default)]
+    displays: Vec<DisplayEntry>,
+}
+
+/// One display and its profile
+#[derive(Debug, serde::Deserialize)]
+struct DisplayEntry {
+    #[serde(flatten)]
+    display: Display,
+    /// ICC profile file, relative to the description file
+    #[serde(default)]
+    profile: Option<PathBuf>,
+}
+
+/// A display with the profile loaded for it
+#[derive(Debug, Clone)]
+struct FixtureDisplay {
+    display: Display,
+    profile: Option<(ProfileInfo, Vec<u8>)>,
+}
+
+/// A [`DisplayProfileProvider`] serving the displays in a description file.
+///
+/// Profiles are read when the description is loaded, so a missing or
+/// unreadable profile is reported by [`load`](Self::load) rather than by the
+/// first query.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use display_icc::{DisplayProfileProvider, FileProfileProvider};
+///
+/// # fn example() -> Result<(), display_icc::ProfileError> {
+/// let provider = FileProfileProvider::load("tests/fixtures/provider/displays.json")?;
+/// let primary = provider.get_primary_display()?;
+/// println!("{}: {}", primary.name, provider.get_profile(&primary)?.name);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileProfileProvider {
+    displays: Vec<FixtureDisplay>,
+}
+
+impl FileProfileProvider {
+    /// Load the description at `path`, as TOML for a `.toml` file and as JSON
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// - [`ProfileError::IoError`] if the description or a profile cannot be read
+    /// - [`ProfileError::ParseError`] if the description is malformed, or TOML
+    ///   without the `toml` feature
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ProfileError::IoError(format!("{}: {}", path.display(), e)))?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+
+        let result = if path.extension().is_some_and(|ext| ext == "toml") {
+            Self::from_toml(&text, base_dir)
+        } else {
+            Self::from_json(&text, base_dir)
+        };
+        result.map_err(|e| match e {
+            ProfileError::ParseError(msg) => {
+                ProfileError::ParseError(format!("{}: {}", path.display(), msg))
+            }
+            e => e,
+        })
+    }
+
+    /// Parse a JSON description, resolving profile paths against `base_dir`
+    pub fn from_json(text: &str, base_dir: &Path) -> Result<Self, ProfileError> {
+        let description: Description =
+            serde_json::from_str(text).map_err(|e| ProfileError::ParseError(e.to_string()))?;
+        Self::from_description(description, base_dir)
+    }
+
+    /// Parse a TOML description, resolving profile paths against `base_dir`
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str, base_dir: &Path) -> Result<Self, ProfileError> {
+        let description: Description =
+            toml::from_str(text).map_err(|e| ProfileError::ParseError(e.to_string()))?;
+        Self::from_description(description, base_dir)
+    }
+
+    /// Without the `toml` feature, TOML descriptions cannot be read
+    #[cfg(not(feature = "toml"))]
+    pub fn from_toml(_text: &str, _base_dir: &Path) -> Result<Self, ProfileError> {
+        Err(ProfileError::ParseError(
+            "TOML descriptions need the `toml` feature".to_string(),
+        ))
+    }
+
+    fn from_description(description: Description, base_dir: &Path) -> Result<Self, ProfileError> {
+        let mut displays: Vec<FixtureDisplay> = Vec::new();
+        for entry in description.displays {
+            if displays.iter().any(|d| d.display.id == entry.display.id) {
+                return Err(ProfileError::ParseError(format!(
+                    "display {} is described twice",
+                    entry.display.id
+                )));
+            }
+
+            let profile = match entry.profile {
+                Some(profile) => {
+                    let path = base_dir.join(profile);
+                    let data = std::fs::read(&path)
+                        .map_err(|e| ProfileError::IoError(format!("{}: {}", path.display(), e)))?;
+                    Some((icc::profile_info(&data, Some(&path)), data))
+                }
+                None => None,
+            };
+            displays.push(FixtureDisplay {
+                display: entry.display,
+                profile,
+            });
+        }
+        Ok(Self { displays })
+    }
+
+    /// The described display with the same ID as `display`
+    fn find(&self, display: &Display) -> Result<&FixtureDisplay, ProfileError> {
+        self.displays
+            .iter()
+            .find(|d| d.display.id == display.id)
+            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))
+    }
+
+    /// The profile of `display`, if it has one
+    fn profile(&self, display: &Display) -> Result<&(ProfileInfo, Vec<u8>), ProfileError> {
+        self.find(display)?
+            .profile
+            .as_ref()
+            .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()))
+    }
+}
+
+impl DisplayProfileProvider for FileProfileProvider {
+    fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
+        Ok(self.displays.iter().map(|d| d.display.clone()).collect())
+    }
+
+    fn get_primary_display(&self) -> Result<Display, ProfileError> {
+        self.displays
+            .iter()
+            .map(|d| &d.display)
+            .find(|d| d.is_primary)
+            .cloned()
+            .ok_or_else(|| ProfileError::DisplayNotFound("No primary display found".to_string()))
+    }
+
+    fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
+        self.profile(display).map(|(info, _)| info.clone())
+    }
+
+    fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
+        self.profile(display).map(|(_, data)| data.clone())
+    }
+
+    /// The described displays never change, so the watcher stays silent
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        let (sender, events) = mpsc::channel();
+        Ok(ProfileWatcher::new(events, sender))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icc::test_support::{mluc, profile};
+    use crate::ColorSpace;
+    use tempfile::TempDir;
+
+    /// A description directory with one profile, `profiles/dell.icc`
+    fn fixture_dir() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("profiles")).unwrap();
+        std::fs::write(
+            dir.path().join("profiles/dell.icc"),
+            profile(&[(b"desc", mluc("DELL U2415 calibrated"))]),
+        )
+        .unwrap();
+        dir
+    }
+
+    fn check(provider: &FileProfileProvider, dir: &Path) {
+        let displays = provider.get_displays().unwrap();
+        let ids: Vec<&str> = displays.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["DP-1", "HDMI-A-1"]);
+        assert_eq!(displays[0].vendor(), Some("DEL"));
+        assert_eq!(displays[0].resolution(), Some((1920, 1200)));
+
+        let primary = provider.get_primary_display().unwrap();
+        assert_eq!(primary.id, "DP-1");
+        let info = provider.get_profile(&primary).unwrap();
+        assert_eq!(info.name, "DELL U2415 calibrated");
+        assert_eq!(info.color_space, ColorSpace::RGB);
+        assert_eq!(info.file_path, Some(dir.join("profiles/dell.icc")));
+        assert_eq!(
+            provider.get_profile_data(&primary).unwrap(),
+            std::fs::read(dir.join("profiles/dell.icc")).unwrap()
+        );
+
+        // The projector has no profile, and unknown displays are not found
+        assert!(matches!(
+            provider.get_profile(&displays[1]),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+        assert!(provider.get_profiles(&displays[1]).unwrap().is_empty());
+        assert!(matches!(
+            provider.get_profile_data(&Display::new("DP-2", "Gone", false)),
+            Err(ProfileError::DisplayNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_json() {
+        let dir = fixture_dir();
+        let path = dir.path().join("displays.json");
+        std::fs::write(
+            &path,
+            r#"{"displays": [
+                {"id": "DP-1", "name": "DELL U2415", "is_primary": true, "vendor": "DEL",
+                 "resolution": [1920, 1200], "profile": "profiles/dell.icc"},
+                {"id": "HDMI-A-1", "name": "Projector", "is_primary": false}
+            ]}"#,
+        )
+        .unwrap();
+
+        check(&FileProfileProvider::load(&path).unwrap(), dir.path());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_load_toml() {
+        let dir = fixture_dir();
+        let path = dir.path().join("displays.toml");
+        std::fs::write(
+            &path,
+            r#"
+            [[displays]]
+            id = "DP-1"
+            name = "DELL U2415"
+            is_primary = true
+            vendor = "DEL"
+            resolution = [1920, 1200]
+            profile = "profiles/dell.icc"
+
+            [[displays]]
+            id = "HDMI-A-1"
+            name = "Projector"
+            is_primary = false
+            "#,
+        )
+        .unwrap();
+
+        check(&FileProfileProvider::load(&path).unwrap(), dir.path());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = fixture_dir();
+        let base = dir.path();
+        assert!(matches!(
+            FileProfileProvider::load(base.join("missing.json")),
+            Err(ProfileError::IoError(_))
+        ));
+        assert!(matches!(
+            FileProfileProvider::from_json(r#"{"displays": [{"id": "DP-1"}]}"#, base),
+            Err(ProfileError::ParseError(msg)) if msg.contains("name")
+        ));
+        assert!(matches!(
+            FileProfileProvider::from_json(
+                r#"{"displays": [{"id": "DP-1", "name": "A", "is_primary": true,
+                                  "profile": "profiles/gone.icc"}]}"#,
+                base
+            ),
+            Err(ProfileError::IoError(msg)) if msg.contains("gone.icc")
+        ));
+        assert!(matches!(
+            FileProfileProvider::from_json(
+                r#"{"displays": [{"id": "DP-1", "name": "A", "is_primary": true},
+                                 {"id": "DP-1", "name": "B", "is_primary": false}]}"#,
+                base
+            ),
+            Err(ProfileError::ParseError(msg)) if msg.contains("described twice")
+        ));
+
+        // Without a primary display, there is none to report
+        let provider = FileProfileProvider::from_json(r#"{"displays": []}"#, base).unwrap();
+        assert!(provider.get_displays().unwrap().is_empty());
+        assert!(matches!(
+            provider.get_primary_display(),
+            Err(ProfileError::DisplayNotFound(_))
+        ));
+    }
+}
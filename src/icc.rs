@@ -0,0 +1,701 @@
+//! Low-level ICC tag table and tag type decoding
+
+use crate::{md5, Chromaticity, ColorSpace, Primaries, ProfileError, ProfileInfo};
+use std::path::Path;
+
+/// Size of the fixed ICC profile header
+pub(crate) const HEADER_SIZE: usize = 128;
+
+/// D50 illuminant used as the profile connection space white
+pub(crate) const D50: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+/// A 3x3 matrix stored row-major
+pub(crate) type Matrix3 = [[f64; 3]; 3];
+
+/// Read a big-endian u16 at `offset`, if in bounds
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+/// Read a big-endian u32 at `offset`, if in bounds
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Read an s15Fixed16Number at `offset`, if in bounds
+fn read_s15f16(data: &[u8], offset: usize) -> Option<f64> {
+    read_u32(data, offset).map(|raw| raw as i32 as f64 / 65536.0)
+}
+
+/// Parsed ICC tag table giving access to the raw bytes of each tag
+pub(crate) struct TagTable<'a> {
+    data: &'a [u8],
+    entries: Vec<([u8; 4], usize, usize)>,
+}
+
+impl<'a> TagTable<'a> {
+    /// Parse the tag table following the 128-byte header
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ProfileError> {
+        let count = read_u32(data, HEADER_SIZE).ok_or_else(|| {
+            ProfileError::ParseError(format!(
+                "ICC profile data too short for tag table: {} bytes",
+                data.len()
+            ))
+        })? as usize;
+
+        let table_end = HEADER_SIZE + 4 + count.saturating_mul(12);
+        if table_end > data.len() {
+            return Err(ProfileError::ParseError(format!(
+                "ICC tag table declares {} tags but profile is only {} bytes",
+                count,
+                data.len()
+            )));
+        }
+
+        let mut entries = Vec::with_capacity(count);
+        for index in 0..count {
+            let entry = HEADER_SIZE + 4 + index * 12;
+            let mut signature = [0u8; 4];
+            signature.copy_from_slice(&data[entry..entry + 4]);
+            let offset = read_u32(data, entry + 4).unwrap_or(0) as usize;
+            let size = read_u32(data, entry + 8).unwrap_or(0) as usize;
+
+            if offset.checked_add(size).is_none_or(|end| end > data.len()) {
+                return Err(ProfileError::ParseError(format!(
+                    "ICC tag '{}' at offset {} with size {} exceeds profile length {}",
+                    String::from_utf8_lossy(&signature),
+                    offset,
+                    size,
+                    data.len()
+                )));
+            }
+
+            entries.push((signature, offset, size));
+        }
+
+        Ok(Self { data, entries })
+    }
+
+    /// Get the raw bytes of a tag by signature
+    pub(crate) fn get(&self, signature: &[u8; 4]) -> Option<&'a [u8]> {
+        self.entries
+            .iter()
+            .find(|(sig, _, _)| sig == signature)
+            .map(|&(_, offset, size)| &self.data[offset..offset + size])
+    }
+
+    /// Check whether a tag is present
+    pub(crate) fn contains(&self, signature: &[u8; 4]) -> bool {
+        self.entries.iter().any(|(sig, _, _)| sig == signature)
+    }
+}
+
+/// Decode the first value of an `XYZ ` type tag
+pub(crate) fn read_xyz(tag: &[u8]) -> Option<[f64; 3]> {
+    if tag.get(0..4)? != b"XYZ " {
+        return None;
+    }
+
+    Some([
+        read_s15f16(tag, 8)?,
+        read_s15f16(tag, 12)?,
+        read_s15f16(tag, 16)?,
+    ])
+}
+
+/// Decode an `sf32` tag holding a 3x3 matrix (e.g. `chad`)
+pub(crate) fn read_matrix(tag: &[u8]) -> Option<Matrix3> {
+    if tag.get(0..4)? != b"sf32" {
+        return None;
+    }
+
+    let mut matrix = [[0.0; 3]; 3];
+    for (index, value) in matrix.iter_mut().flatten().enumerate() {
+        *value = read_s15f16(tag, 8 + index * 4)?;
+    }
+    Some(matrix)
+}
+
+/// Decode a text tag such as `desc`: a v4 `mluc` (its first record) or a v2
+/// `desc` text description
+pub(crate) fn read_text(tag: &[u8]) -> Option<String> {
+    let text = match tag.get(0..4)? {
+        b"mluc" => {
+            if read_u32(tag, 8)? == 0 {
+                return None;
+            }
+            let length = read_u32(tag, 20)? as usize;
+            let offset = read_u32(tag, 24)? as usize;
+            let units: Vec<u16> = tag
+                .get(offset..offset.checked_add(length)?)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        b"desc" => {
+            let length = read_u32(tag, 8)? as usize;
+            let ascii = tag.get(12..12usize.checked_add(length)?)?;
+            String::from_utf8_lossy(ascii).into_owned()
+        }
+        _ => return None,
+    };
+
+    let text = text.trim_end_matches('\0').trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Decode a `dict` tag, such as colord's `meta` tag, into name/value pairs.
+///
+/// Names and values are UTF-16BE strings at offsets relative to the tag;
+/// records may also carry display names, which are skipped.
+pub(crate) fn read_dict(tag: &[u8]) -> Option<Vec<(String, String)>> {
+    if tag.get(0..4)? != b"dict" {
+        return None;
+    }
+    let count = read_u32(tag, 8)? as usize;
+    let record_size = read_u32(tag, 12)? as usize;
+    if ![16, 24, 32].contains(&record_size) {
+        return None;
+    }
+
+    let string = |offset: usize| -> Option<String> {
+        let start = read_u32(tag, offset)? as usize;
+        let length = read_u32(tag, offset + 4)? as usize;
+        let units: Vec<u16> = tag
+            .get(start..start.checked_add(length)?)?
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        Some(String::from_utf16_lossy(&units))
+    };
+    (0..count)
+        .map(|index| {
+            let record = 16 + index * record_size;
+            Some((string(record)?, string(record + 8)?))
+        })
+        .collect()
+}
+
+/// A decoded tone reproduction curve
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ToneCurve {
+    /// Pure power function (`curv` with zero or one entry)
+    Gamma(f64),
+    /// Sampled curve (`curv` with two or more entries)
+    Table(Vec<u16>),
+    /// Parametric curve (`para`) with its function type and parameters
+    Parametric(u16, Vec<f64>),
+}
+
+impl ToneCurve {
+    /// Evaluate the curve for an input in `0.0..=1.0`
+    pub(crate) fn eval(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            ToneCurve::Gamma(gamma) => x.powf(*gamma),
+            ToneCurve::Table(table) => {
+                let position = x * (table.len() - 1) as f64;
+                let index = position.floor() as usize;
+                let next = (index + 1).min(table.len() - 1);
+                let fraction = position - index as f64;
+                let low = table[index] as f64 / 65535.0;
+                let high = table[next] as f64 / 65535.0;
+                low + (high - low) * fraction
+            }
+            ToneCurve::Parametric(kind, p) => {
+                let param = |i: usize| p.get(i).copied().unwrap_or(0.0);
+                let (g, a, b, c, d, e, f) = (
+                    param(0),
+                    param(1),
+                    param(2),
+                    param(3),
+                    param(4),
+                    param(5),
+                    param(6),
+                );
+                let y = match kind {
+                    0 => x.powf(g),
+                    1 => {
+                        if x >= -b / a {
+                            (a * x + b).powf(g)
+                        } else {
+                            0.0
+                        }
+                    }
+                    2 => {
+                        if x >= -b / a {
+                            (a * x + b).powf(g) + c
+                        } else {
+                            c
+                        }
+                    }
+                    3 => {
+                        if x >= d {
+                            (a * x + b).powf(g)
+                        } else {
+                            c * x
+                        }
+                    }
+                    4 => {
+                        if x >= d {
+                            (a * x + b).powf(g) + e
+                        } else {
+                            c * x + f
+                        }
+                    }
+                    _ => x,
+                };
+                y.clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// Estimate the equivalent power-law gamma of the curve.
+    ///
+    /// Pure power curves return their exponent. Other curves report the
+    /// "effective gamma" at 50% input, `ln(f(0.5)) / ln(0.5)`, which is the
+    /// figure calibration tools quote for curves with a linear toe such as sRGB.
+    pub(crate) fn estimate_gamma(&self) -> Option<f64> {
+        if let ToneCurve::Gamma(gamma) = self {
+            return Some(*gamma);
+        }
+
+        let y = self.eval(0.5);
+        if y <= 0.0 || y >= 1.0 {
+            return None;
+        }
+        Some(y.ln() / 0.5f64.ln())
+    }
+}
+
+/// Decode a `curv` or `para` tone curve tag
+pub(crate) fn read_curve(tag: &[u8]) -> Option<ToneCurve> {
+    match tag.get(0..4)? {
+        b"curv" => {
+            let count = read_u32(tag, 8)? as usize;
+            match count {
+                0 => Some(ToneCurve::Gamma(1.0)),
+                1 => Some(ToneCurve::Gamma(read_u16(tag, 12)? as f64 / 256.0)),
+                _ => {
+                    let table = (0..count)
+                        .map(|i| read_u16(tag, 12 + i * 2))
+                        .collect::<Option<Vec<u16>>>()?;
+                    Some(ToneCurve::Table(table))
+                }
+            }
+        }
+        b"para" => {
+            let kind = read_u16(tag, 8)?;
+            let count = match kind {
+                0 => 1,
+                1 => 3,
+                2 => 4,
+                3 => 5,
+                4 => 7,
+                _ => return None,
+            };
+            let params = (0..count)
+                .map(|i| read_s15f16(tag, 12 + i * 4))
+                .collect::<Option<Vec<f64>>>()?;
+            Some(ToneCurve::Parametric(kind, params))
+        }
+        _ => None,
+    }
+}
+
+/// Multiply a 3x3 matrix by a column vector
+pub(crate) fn mul_vector(m: &Matrix3, v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+/// Multiply two 3x3 matrices
+pub(crate) fn mul_matrix(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut result = [[0.0; 3]; 3];
+    for (row, result_row) in result.iter_mut().enumerate() {
+        for (col, value) in result_row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[row][k] * b[k][col]).sum();
+        }
+    }
+    result
+}
+
+/// Invert a 3x3 matrix, returning `None` if it is singular
+pub(crate) fn invert(m: &Matrix3) -> Option<Matrix3> {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+
+    if det.abs() < 1e-12 {
+        return None;
+    }
+
+    Some([
+        [
+            (m[1][1] * m[2][2] - m[1][2] * m[2][1]) / det,
+            (m[0][2] * m[2][1] - m[0][1] * m[2][2]) / det,
+            (m[0][1] * m[1][2] - m[0][2] * m[1][1]) / det,
+        ],
+        [
+            (m[1][2] * m[2][0] - m[1][0] * m[2][2]) / det,
+            (m[0][0] * m[2][2] - m[0][2] * m[2][0]) / det,
+            (m[0][2] * m[1][0] - m[0][0] * m[1][2]) / det,
+        ],
+        [
+            (m[1][0] * m[2][1] - m[1][1] * m[2][0]) / det,
+            (m[0][1] * m[2][0] - m[0][0] * m[2][1]) / det,
+            (m[0][0] * m[1][1] - m[0][1] * m[1][0]) / det,
+        ],
+    ])
+}
+
+/// Bradford chromatic adaptation matrix from `source` white to `destination` white
+pub(crate) fn bradford(source: [f64; 3], destination: [f64; 3]) -> Option<Matrix3> {
+    const BRADFORD: Matrix3 = [
+        [0.8951, 0.2664, -0.1614],
+        [-0.7502, 1.7135, 0.0367],
+        [0.0389, -0.0685, 1.0296],
+    ];
+
+    let cone_source = mul_vector(&BRADFORD, source);
+    let cone_destination = mul_vector(&BRADFORD, destination);
+    if cone_source.iter().any(|v| v.abs() < 1e-12) {
+        return None;
+    }
+
+    let scale = [
+        [cone_destination[0] / cone_source[0], 0.0, 0.0],
+        [0.0, cone_destination[1] / cone_source[1], 0.0],
+        [0.0, 0.0, cone_destination[2] / cone_source[2]],
+    ];
+
+    Some(mul_matrix(
+        &invert(&BRADFORD)?,
+        &mul_matrix(&scale, &BRADFORD),
+    ))
+}
+
+/// Encode an `XYZ ` tag
+pub(crate) fn xyz(value: [f64; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ \0\0\0\0".to_vec();
+    for v in value {
+        tag.extend_from_slice(&((v * 65536.0).round() as i32).to_be_bytes());
+    }
+    tag
+}
+
+/// Encode an `sf32` matrix tag
+pub(crate) fn sf32(matrix: [[f64; 3]; 3]) -> Vec<u8> {
+    let mut tag = b"sf32\0\0\0\0".to_vec();
+    for v in matrix.iter().flatten() {
+        tag.extend_from_slice(&((v * 65536.0).round() as i32).to_be_bytes());
+    }
+    tag
+}
+
+/// Encode a single-gamma `curv` tag
+pub(crate) fn gamma_curve(gamma: f64) -> Vec<u8> {
+    let mut tag = b"curv\0\0\0\0".to_vec();
+    tag.extend_from_slice(&1u32.to_be_bytes());
+    tag.extend_from_slice(&((gamma * 256.0).round() as u16).to_be_bytes());
+    tag
+}
+
+/// Encode a single-record `mluc` text tag
+pub(crate) fn mluc(text: &str) -> Vec<u8> {
+    let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    let mut tag = b"mluc\0\0\0\0".to_vec();
+    tag.extend_from_slice(&1u32.to_be_bytes());
+    tag.extend_from_slice(&12u32.to_be_bytes());
+    tag.extend_from_slice(b"enUS");
+    tag.extend_from_slice(&(utf16.len() as u32).to_be_bytes());
+    tag.extend_from_slice(&28u32.to_be_bytes());
+    tag.extend_from_slice(&utf16);
+    tag
+}
+
+/// Profile information from ICC data: the name from the `desc` tag, else the
+/// file name, and the color space from the header
+pub(crate) fn profile_info(data: &[u8], path: Option<&Path>) -> ProfileInfo {
+    let name = TagTable::parse(data)
+        .ok()
+        .and_then(|tags| tags.get(b"desc").and_then(read_text))
+        .or_else(|| Some(path?.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "Unknown Profile".to_string());
+    let color_space = match data.get(16..20) {
+        Some(b"RGB ") => ColorSpace::RGB,
+        Some(b"Lab ") => ColorSpace::Lab,
+        _ => ColorSpace::Unknown,
+    };
+    ProfileInfo {
+        name,
+        description: None,
+        file_path: path.map(Path::to_path_buf),
+        color_space,
+    }
+}
+
+/// The colorant, white point and tone curve tags of a matrix/TRC display
+/// profile with the given primaries, white point and power-law gamma.
+///
+/// The colorants are adapted to D50 with Bradford and the adaptation stored
+/// in `chad`. Returns `None` for degenerate chromaticities.
+pub(crate) fn matrix_tags(
+    primaries: Primaries,
+    white: Chromaticity,
+    gamma: f64,
+) -> Option<Vec<(&'static [u8; 4], Vec<u8>)>> {
+    // Columns of the RGB to XYZ matrix are the primaries scaled so that
+    // full-scale RGB comes out as the white point with Y = 1
+    let to_xyz = |xy: Chromaticity| -> Option<[f64; 3]> {
+        (xy.y > 0.0).then(|| [xy.x / xy.y, 1.0, (1.0 - xy.x - xy.y) / xy.y])
+    };
+    let (red, green, blue, white) = (
+        to_xyz(primaries.red)?,
+        to_xyz(primaries.green)?,
+        to_xyz(primaries.blue)?,
+        to_xyz(white)?,
+    );
+    let colorants = [
+        [red[0], green[0], blue[0]],
+        [red[1], green[1], blue[1]],
+        [red[2], green[2], blue[2]],
+    ];
+    let scale = mul_vector(&invert(&colorants)?, white);
+    let adaptation = bradford(white, D50)?;
+    let colorant = |channel: usize| {
+        mul_vector(
+            &adaptation,
+            [
+                colorants[0][channel] * scale[channel],
+                colorants[1][channel] * scale[channel],
+                colorants[2][channel] * scale[channel],
+            ],
+        )
+    };
+
+    let trc = gamma_curve(gamma);
+    Some(vec![
+        (b"wtpt", xyz(D50)),
+        (b"chad", sf32(adaptation)),
+        (b"rXYZ", xyz(colorant(0))),
+        (b"gXYZ", xyz(colorant(1))),
+        (b"bXYZ", xyz(colorant(2))),
+        (b"rTRC", trc.clone()),
+        (b"gTRC", trc.clone()),
+        (b"bTRC", trc),
+    ])
+}
+
+/// Assemble a v4.3 RGB display profile from a list of tags.
+///
+/// The header is dated `date` (year, month, day, hours, minutes, seconds) and
+/// carries the profile ID, the MD5 of the profile with the flags, rendering
+/// intent and ID fields zeroed.
+pub(crate) fn write_profile(tags: &[(&[u8; 4], Vec<u8>)], date: [u16; 6]) -> Vec<u8> {
+    let mut data = vec![0u8; HEADER_SIZE];
+    data[8..12].copy_from_slice(&0x04300000u32.to_be_bytes());
+    data[12..16].copy_from_slice(b"mntr");
+    data[16..20].copy_from_slice(b"RGB ");
+    data[20..24].copy_from_slice(b"XYZ ");
+    for (index, value) in date.iter().enumerate() {
+        data[24 + index * 2..26 + index * 2].copy_from_slice(&value.to_be_bytes());
+    }
+    data[36..40].copy_from_slice(b"acsp");
+    data[68..80].copy_from_slice(&xyz(D50)[8..20]);
+
+    // Tags are 4-byte aligned, and identical tags share their data
+    let table_size = 4 + tags.len() * 12;
+    data.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+    let mut body: Vec<u8> = Vec::new();
+    let mut written: Vec<(&[u8], usize)> = Vec::new();
+    for (signature, tag) in tags {
+        let offset = match written.iter().find(|(data, _)| *data == &tag[..]) {
+            Some(&(_, offset)) => offset,
+            None => {
+                let offset = HEADER_SIZE + table_size + body.len();
+                body.extend_from_slice(tag);
+                while !body.len().is_multiple_of(4) {
+                    body.push(0);
+                }
+                written.push((tag, offset));
+                offset
+            }
+        };
+        data.extend_from_slice(*signature);
+        data.extend_from_slice(&(offset as u32).to_be_bytes());
+        data.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+    }
+    data.extend_from_slice(&body);
+
+    let size = data.len() as u32;
+    data[0..4].copy_from_slice(&size.to_be_bytes());
+    let id = md5::digest(&data);
+    data[84..100].copy_from_slice(&id);
+    data
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    //! Helpers for assembling small ICC profiles in tests
+
+    pub(crate) use super::{gamma_curve, mluc, sf32, xyz};
+
+    /// Encode a `dict` tag with 16-byte records
+    pub(crate) fn dict(entries: &[(&str, &str)]) -> Vec<u8> {
+        let utf16 =
+            |text: &str| -> Vec<u8> { text.encode_utf16().flat_map(u16::to_be_bytes).collect() };
+        let mut tag = b"dict\0\0\0\0".to_vec();
+        tag.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        tag.extend_from_slice(&16u32.to_be_bytes());
+        let mut strings = Vec::new();
+        let mut offset = 16 + entries.len() * 16;
+        for (name, value) in entries {
+            for text in [utf16(name), utf16(value)] {
+                tag.extend_from_slice(&(offset as u32).to_be_bytes());
+                tag.extend_from_slice(&(text.len() as u32).to_be_bytes());
+                offset += text.len();
+                strings.extend_from_slice(&text);
+            }
+        }
+        tag.extend_from_slice(&strings);
+        tag
+    }
+
+    /// Assemble a display RGB profile from a list of tags
+    pub(crate) fn profile(tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = vec![0u8; super::HEADER_SIZE];
+        data[8..12].copy_from_slice(&0x04300000u32.to_be_bytes());
+        data[12..16].copy_from_slice(b"mntr");
+        data[16..20].copy_from_slice(b"RGB ");
+        data[20..24].copy_from_slice(b"XYZ ");
+        data[36..40].copy_from_slice(b"acsp");
+
+        data.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+        let mut offset = super::HEADER_SIZE + 4 + tags.len() * 12;
+        let mut body = Vec::new();
+        for (signature, tag) in tags {
+            data.extend_from_slice(*signature);
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+            body.extend_from_slice(tag);
+            while body.len() % 4 != 0 {
+                body.push(0);
+            }
+            offset = super::HEADER_SIZE + 4 + tags.len() * 12 + body.len();
+        }
+        data.extend_from_slice(&body);
+
+        let size = data.len() as u32;
+        data[0..4].copy_from_slice(&size.to_be_bytes());
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::*;
+    use super::*;
+
+    #[test]
+    fn test_tag_table_lookup() {
+        let data = profile(&[(b"wtpt", xyz(D50)), (b"rTRC", gamma_curve(2.2))]);
+        let table = TagTable::parse(&data).unwrap();
+
+        assert!(table.contains(b"wtpt"));
+        assert!(!table.contains(b"vcgt"));
+
+        let white = read_xyz(table.get(b"wtpt").unwrap()).unwrap();
+        assert!((white[0] - D50[0]).abs() < 1e-4);
+        assert!((white[2] - D50[2]).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_tag_table_rejects_out_of_bounds() {
+        let mut data = profile(&[(b"wtpt", xyz(D50))]);
+        data.truncate(data.len() - 8);
+
+        match TagTable::parse(&data) {
+            Err(ProfileError::ParseError(msg)) => assert!(msg.contains("wtpt")),
+            _ => panic!("Expected ParseError"),
+        }
+    }
+
+    #[test]
+    fn test_tone_curve_gamma_estimates() {
+        assert_eq!(
+            read_curve(&gamma_curve(2.2)).unwrap().estimate_gamma(),
+            Some(2.19921875)
+        );
+
+        // sRGB transfer function as a type 3 parametric curve
+        let srgb = ToneCurve::Parametric(
+            3,
+            vec![2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045],
+        );
+        let gamma = srgb.estimate_gamma().unwrap();
+        assert!((gamma - 2.22).abs() < 0.01, "sRGB gamma was {}", gamma);
+
+        let table = ToneCurve::Table(
+            (0..=255)
+                .map(|i| ((i as f64 / 255.0).powf(1.8) * 65535.0) as u16)
+                .collect(),
+        );
+        assert!((table.estimate_gamma().unwrap() - 1.8).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_matrix_inverse_round_trip() {
+        let m = [
+            [0.4361, 0.3851, 0.1431],
+            [0.2225, 0.7169, 0.0606],
+            [0.0139, 0.0971, 0.7141],
+        ];
+        let identity = mul_matrix(&m, &invert(&m).unwrap());
+
+        for (row, values) in identity.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                let expected = if row == col { 1.0 } else { 0.0 };
+                assert!((value - expected).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_text() {
+        assert_eq!(
+            read_text(&mluc("Dell U2415")).as_deref(),
+            Some("Dell U2415")
+        );
+
+        let mut desc = b"desc\0\0\0\0".to_vec();
+        desc.extend_from_slice(&5u32.to_be_bytes());
+        desc.extend_from_slice(b"sRGB\0");
+        assert_eq!(read_text(&desc).as_deref(), Some("sRGB"));
+
+        assert_eq!(read_text(&mluc("")), None);
+        assert_eq!(read_text(&xyz(D50)), None);
+        assert_eq!(read_text(&mluc("truncated")[..30]), None);
+    }
+
+    #[test]
+    fn test_read_dict() {
+        let tag = dict(&[("DATA_source", "edid"), ("EDID_md5", "3c9f5d0e")]);
+        assert_eq!(
+            read_dict(&tag),
+            Some(vec![
+                ("DATA_source".to_string(), "edid".to_string()),
+                ("EDID_md5".to_string(), "3c9f5d0e".to_string()),
+            ])
+        );
+        assert_eq!(read_dict(&dict(&[])), Some(Vec::new()));
+        assert_eq!(read_dict(&tag[..40]), None);
+        assert_eq!(read_dict(&mluc("meta")), None);
+    }
+}
@@ -13,6 +13,8 @@
 //! - **Raw ICC data access**: Get both profile metadata and raw ICC binary data
 //! - **Robust error handling**: Comprehensive error types with fallback mechanisms
 //! - **CLI interface**: Command-line tool for quick profile inspection
+//! - **Async API**: `AsyncDisplayProfileProvider` and a stream of change events
+//!   (`tokio` feature), with async D-Bus and process I/O on Linux
 //!
 //! ## Platform-Specific Behavior
 //!
@@ -24,9 +26,26 @@
 //!
 //! ### Linux
 //! - Primary: Uses `colormgr` command-line tool
-//! - Secondary: D-Bus API integration with colord daemon
-//! - Fallback: File system scanning in `/usr/share/color/icc/`
+//! - Secondary: D-Bus API integration with colord daemon (`dbus-support` feature,
+//!   a pure-Rust client that needs no system D-Bus library)
+//! - Optional: X11 `_ICC_PROFILE` root window properties when colord has no
+//!   profile (`x11` feature), as read by GIMP, Firefox and mpv
+//! - Optional: on Wayland sessions, each output's image description from the
+//!   compositor's `wp_color_management_v1` protocol (`wayland` feature), either
+//!   an ICC profile or a parametric description of primaries and transfer function
+//! - Monitor EDIDs from `/sys/class/drm` fill in names, serials and connectors,
+//!   and can be decoded with [`Edid`]
+//! - Fallback: without colord, the connected DRM connectors in sysfs, with
+//!   profiles assigned in a mapping file
+//!   ([`ProfileConfig::linux_profile_map`]), found by EDID checksum or made
+//!   for the monitor's model or manufacturer
+//! - Fallback: for displays colord has no profile for, a profile on disk made
+//!   for the monitor, else one generated from its EDID colorimetry
+//!   ([`profile_from_edid`]), flagged as EDID-derived
+//! - Fallback: File system scanning in `/usr/share/color/icc/`, choosing the
+//!   RGB display profile that best matches the monitor, or else sRGB
 //! - Requires colord/colormgr to be installed for full functionality
+//! - Profiles can be imported and assigned through [`DisplayProfileManager`]
 //!
 //! ### Windows
 //! - Uses Win32 API (`GetColorDirectory`, `EnumColorProfiles`)
@@ -68,10 +87,9 @@
 //! use display_icc::{ProfileConfig, create_provider_with_config, ProfileError};
 //!
 //! fn main() -> Result<(), ProfileError> {
-//!     let config = ProfileConfig {
-//!         linux_prefer_dbus: false, // Linux: use colormgr command instead of D-Bus
-//!         fallback_enabled: true,   // Enable fallback mechanisms
-//!     };
+//!     let config = ProfileConfig::default()
+//!         .with_linux_prefer_dbus(false) // Linux: use colormgr command instead of D-Bus
+//!         .with_fallback_enabled(true); // Enable fallback mechanisms
 //!
 //!     let provider = create_provider_with_config(config)?;
 //!     let displays = provider.get_displays()?;
@@ -164,8 +182,56 @@
 //! # Verbose output with ICC header information
 //! display_icc --verbose
 //! ```
-
-use std::path::PathBuf;
+//!
+//! ## Serialization
+//!
+//! With the `serde` feature (enabled by default), [`Display`], [`ProfileInfo`],
+//! [`ColorSpace`], [`IccHeader`] and [`ProfileConfig`] implement `Serialize` and
+//! `Deserialize`. The JSON emitted by the CLI is produced from these same types,
+//! so the field names below are part of the public API:
+//!
+//! | Type | JSON representation |
+//! |------|---------------------|
+//! | [`Display`] | `{"id": string, "name": string, "is_primary": bool, ...}`; each optional metadata field (`vendor`, `connector`, `resolution`, ...) is a key that is `null` when unknown, and `edid` is a hex string |
+//! | [`ProfileInfo`] | `{"name": string, "description": string \| null, "file_path": string \| null, "color_space": ColorSpace}` |
+//! | [`ColorSpace`] | `"RGB"`, `"Lab"` or `"Unknown"` (same as its `Display` output) |
+//! | [`IccHeader`] | object with one key per field; `version` is `[major, minor]`, `flags` is a number |
+//! | [`ProfileConfig`] | object with one key per field; missing keys take their default value |
+//!
+//! The complete documents printed by each CLI subcommand are defined in the [`report`]
+//! module and versioned through [`report::SCHEMA_VERSION`]. With the `schema` feature
+//! (which enables `serde`), [`report::json_schema`] generates their JSON Schema.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde")]
+//! # fn main() -> Result<(), serde_json::Error> {
+//! use display_icc::Display;
+//!
+//! let display: Display = serde_json::from_str(
+//!     r#"{"id": "69733382", "name": "Built-in Retina Display", "is_primary": true}"#,
+//! )?;
+//! assert!(display.is_primary);
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "serde"))]
+//! # fn main() {}
+//! ```
+//!
+//! ## Testing
+//!
+//! With the `testing` feature, the `mock` module exports `MockProfileProvider`,
+//! a [`DisplayProfileProvider`] that serves displays and ICC profiles set up
+//! through a builder, can fail or be slow on demand, and records its calls
+//! for assertions.
+//!
+//! [`FileProfileProvider`] (with the `serde` feature) serves the displays and
+//! profiles described in a JSON or TOML file instead. The CLI uses it with
+//! `--provider fixture:FILE` or `DISPLAY_ICC_PROVIDER=fixture:FILE`, which is
+//! how its own tests run the same on every machine.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
 // Platform-specific modules with conditional compilation
@@ -175,32 +241,90 @@ mod macos;
 #[cfg(target_os = "linux")]
 mod linux;
 
+// External command execution, replaceable through `LinuxProfileProvider::with_runner`
+#[cfg(target_os = "linux")]
+mod command;
+
 #[cfg(target_os = "windows")]
 mod windows;
 
-// Mock module for testing
-#[cfg(test)]
-mod mock;
+// ICC tag decoding shared by the colorimetry helpers
+mod icc;
+
+mod colorimetry;
+
+// Display identification from EDID data
+mod edid;
+
+// Checksums for EDID matching and ICC profile IDs
+mod md5;
+
+// Change notifications
+mod watch;
+
+// Memoising wrapper for any provider
+mod cache;
+
+// Async API over the providers, with the `tokio` feature
+#[cfg(feature = "tokio")]
+mod asynchronous;
+
+// Profile directory resolution and scanning for the file system fallbacks
+mod search_path;
+
+// Mock providers for testing, public with the `testing` feature
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
+
+// Documents printed by the CLI, serializable with the `serde` feature
+pub mod report;
+
+// Displays described in a JSON or TOML file, for tests and CI
+#[cfg(feature = "serde")]
+mod fixture;
 
 // Re-export platform-specific implementations
 #[cfg(target_os = "macos")]
 use macos::MacOSProfileProvider;
 
+#[cfg(all(target_os = "linux", feature = "tokio"))]
+pub use command::CommandFuture;
 #[cfg(target_os = "linux")]
-use linux::LinuxProfileProvider;
+pub use command::{CommandOutput, CommandRunner, ProcessRunner};
+#[cfg(target_os = "linux")]
+pub use linux::LinuxProfileProvider;
 
 #[cfg(target_os = "windows")]
 use windows::WindowsProfileProvider;
 
+#[cfg(feature = "tokio")]
+pub use asynchronous::{
+    create_async_provider, create_async_provider_with_config, AsyncAdapter,
+    AsyncDisplayProfileProvider, DisplayEvents,
+};
+pub use cache::CachedProvider;
+pub use colorimetry::{ChannelGamma, Chromaticity, Colorimetry, Primaries, WhitePoint};
+pub use edid::{profile_from_edid, Edid};
+#[cfg(feature = "serde")]
+pub use fixture::FileProfileProvider;
+pub use watch::{DisplayEvent, ProfileWatcher};
+
 /// Represents a display device in the system.
 ///
 /// This struct contains information about a physical or virtual display device,
 /// including its unique identifier, human-readable name, and primary status.
+/// Backends that know more about the display also fill in optional metadata such
+/// as the vendor, connector, geometry or raw EDID, available through accessors.
+///
+/// The struct is `#[non_exhaustive]` so that more metadata can be added without
+/// breaking changes; create one with [`Display::new`] and the `with_*` methods.
 ///
 /// # Platform-Specific Behavior
 ///
 /// - **macOS**: `id` is the CGDirectDisplayID as a string, `name` comes from display info
-/// - **Linux**: `id` is the colormgr device ID, `name` is extracted from device properties
+/// - **Linux**: `id` is the colormgr device ID, `name` is extracted from device properties;
+///   without colord, `id` is `drm-<connector>-<EDID hash>`, or `drm-card<N>-<connector>`
+///   for a monitor that sent no EDID
 /// - **Windows**: `id` is the display device name, `name` is the friendly display name
 ///
 /// # Examples
@@ -208,22 +332,30 @@ use windows::WindowsProfileProvider;
 /// ```rust
 /// use display_icc::Display;
 ///
-/// let display = Display {
-///     id: "69733382".to_string(),
-///     name: "Built-in Retina Display".to_string(),
-///     is_primary: true,
-/// };
+/// let display = Display::new("69733382", "Built-in Retina Display", true)
+///     .with_resolution(3024, 1964)
+///     .with_builtin(true);
 ///
 /// assert!(display.is_primary);
 /// assert_eq!(display.name, "Built-in Retina Display");
+/// assert_eq!(display.resolution(), Some((3024, 1964)));
+/// assert_eq!(display.vendor(), None);
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(description = "A display device in the system")
+)]
 pub struct Display {
     /// Unique identifier for the display.
     ///
     /// This is a platform-specific identifier that uniquely identifies the display
-    /// within the system. The format varies by platform but is guaranteed to be
-    /// consistent for the same physical display across program runs.
+    /// within the system while it stays connected. It may change across reboots
+    /// or reconnects; to remember a display, store its
+    /// [`stable_id`](Self::stable_id) instead.
     pub id: String,
 
     /// Human-readable name of the display.
@@ -237,6 +369,383 @@ pub struct Display {
     /// The primary display is typically where the desktop wallpaper is shown
     /// and where new windows appear by default. Only one display can be primary.
     pub is_primary: bool,
+
+    /// Manufacturer of the display.
+    #[cfg_attr(feature = "serde", serde(default))]
+    vendor: Option<String>,
+
+    /// Model name of the display.
+    #[cfg_attr(feature = "serde", serde(default))]
+    model: Option<String>,
+
+    /// Serial number of the display.
+    #[cfg_attr(feature = "serde", serde(default))]
+    serial: Option<String>,
+
+    /// Name of the output the display is connected to (e.g. `DP-1`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    connector: Option<String>,
+
+    /// Current resolution as `[width, height]` in pixels.
+    #[cfg_attr(feature = "serde", serde(default))]
+    resolution: Option<(u32, u32)>,
+
+    /// Position of the top-left corner as `[x, y]` in the desktop coordinate space.
+    #[cfg_attr(feature = "serde", serde(default))]
+    position: Option<(i32, i32)>,
+
+    /// Scale factor applied to the display (e.g. `2.0` for HiDPI).
+    // Kept in thousandths, and the refresh rate in millihertz, so that
+    // `Display` stays `Eq`
+    #[cfg_attr(feature = "serde", serde(default, with = "thousandths"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<f64>"))]
+    scale: Option<u32>,
+
+    /// Refresh rate of the current mode in Hz.
+    #[cfg_attr(feature = "serde", serde(default, with = "thousandths"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<f64>"))]
+    refresh_rate: Option<u32>,
+
+    /// Whether the display is built into the device rather than external.
+    #[cfg_attr(feature = "serde", serde(default))]
+    builtin: Option<bool>,
+
+    /// Raw EDID block, serialized as a hex string.
+    #[cfg_attr(feature = "serde", serde(default, with = "edid_hex"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    edid: Option<Vec<u8>>,
+
+    /// How the primary display was determined; set on the primary display only.
+    #[cfg_attr(feature = "serde", serde(default))]
+    primary_source: Option<PrimarySource>,
+}
+
+impl Display {
+    /// Create a display with no optional metadata
+    pub fn new(id: impl Into<String>, name: impl Into<String>, is_primary: bool) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            is_primary,
+            vendor: None,
+            model: None,
+            serial: None,
+            connector: None,
+            resolution: None,
+            position: None,
+            scale: None,
+            refresh_rate: None,
+            builtin: None,
+            edid: None,
+            primary_source: None,
+        }
+    }
+
+    /// Manufacturer of the display, if known
+    pub fn vendor(&self) -> Option<&str> {
+        self.vendor.as_deref()
+    }
+
+    /// Model name of the display, if known
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Serial number of the display, if known
+    pub fn serial(&self) -> Option<&str> {
+        self.serial.as_deref()
+    }
+
+    /// Name of the connector the display is attached to (e.g. `DP-1` or `eDP-1`), if known
+    pub fn connector(&self) -> Option<&str> {
+        self.connector.as_deref()
+    }
+
+    /// Current resolution as `(width, height)` in pixels, if known
+    pub fn resolution(&self) -> Option<(u32, u32)> {
+        self.resolution
+    }
+
+    /// Position of the display's top-left corner in desktop coordinates, if known
+    pub fn position(&self) -> Option<(i32, i32)> {
+        self.position
+    }
+
+    /// Scale factor applied to the display, if known
+    pub fn scale(&self) -> Option<f64> {
+        self.scale.map(from_thousandths)
+    }
+
+    /// Refresh rate of the current mode in Hz, if known
+    pub fn refresh_rate(&self) -> Option<f64> {
+        self.refresh_rate.map(from_thousandths)
+    }
+
+    /// Whether the display is built in (`true`) or external (`false`), if known
+    pub fn is_builtin(&self) -> Option<bool> {
+        self.builtin
+    }
+
+    /// Raw EDID bytes reported by the display, if available
+    pub fn edid(&self) -> Option<&[u8]> {
+        self.edid.as_deref()
+    }
+
+    /// How the backend decided this is the primary display, if it is
+    pub fn primary_source(&self) -> Option<PrimarySource> {
+        self.primary_source
+    }
+
+    /// Identifier of the physical display that survives reboots, reconnects
+    /// and switching between backends.
+    ///
+    /// [`id`](Self::id) is whatever the backend uses (a CoreGraphics display
+    /// number, a colord device ID) and may change when the display is
+    /// reconnected. The stable ID is derived from what the monitor reports
+    /// about itself, in the first of these forms that applies:
+    ///
+    /// | Form | When |
+    /// |------|------|
+    /// | `edid:<vendor>:<product>:<serial>` | the EDID carries a serial number |
+    /// | `edid:<vendor>:<product>@<connector>` | it has none, so the connector tells identical monitors apart |
+    /// | `edid:<vendor>:<product>` | neither serial nor connector is known |
+    /// | `connector:<connector>` | there is no EDID, but the connector is known |
+    /// | `id:<id>` | nothing better is known; only as stable as `id` |
+    ///
+    /// `<vendor>` is the three-letter PNP ID, `<product>` the product code as
+    /// four lowercase hex digits and `<serial>` the serial descriptor text, or
+    /// else the numeric serial as `0x` and eight hex digits. Whitespace, `:`
+    /// and `@` in the serial are replaced by `_`.
+    ///
+    /// Use [`DisplayProfileProvider::find_display`] to find the display again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use display_icc::Display;
+    ///
+    /// let display = Display::new("xrandr-DP-1", "DP-1", true).with_connector("DP-1");
+    /// assert_eq!(display.stable_id(), "connector:DP-1");
+    ///
+    /// let display = Display::new("69733382", "Built-in Retina Display", true);
+    /// assert_eq!(display.stable_id(), "id:69733382");
+    /// ```
+    pub fn stable_id(&self) -> String {
+        let edid = self.edid().and_then(|data| Edid::parse(data).ok());
+        match (edid, self.connector()) {
+            (Some(edid), connector) => {
+                let product = format!("edid:{}:{:04x}", edid.manufacturer, edid.product_code);
+                let serial = edid
+                    .serial
+                    .or_else(|| edid.serial_number.map(|serial| format!("0x{:08x}", serial)));
+                match (serial, connector) {
+                    (Some(serial), _) => {
+                        let serial: String = serial
+                            .chars()
+                            .map(|c| match c {
+                                ':' | '@' => '_',
+                                c if c.is_whitespace() => '_',
+                                c => c,
+                            })
+                            .collect();
+                        format!("{}:{}", product, serial)
+                    }
+                    (None, Some(connector)) => format!("{}@{}", product, connector),
+                    (None, None) => product,
+                }
+            }
+            (None, Some(connector)) => format!("connector:{}", connector),
+            (None, None) => format!("id:{}", self.id),
+        }
+    }
+
+    /// Set the manufacturer
+    pub fn with_vendor(mut self, vendor: impl Into<String>) -> Self {
+        self.vendor = Some(vendor.into());
+        self
+    }
+
+    /// Set the model name
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the serial number
+    pub fn with_serial(mut self, serial: impl Into<String>) -> Self {
+        self.serial = Some(serial.into());
+        self
+    }
+
+    /// Set the connector name
+    pub fn with_connector(mut self, connector: impl Into<String>) -> Self {
+        self.connector = Some(connector.into());
+        self
+    }
+
+    /// Set the resolution in pixels
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        self.resolution = Some((width, height));
+        self
+    }
+
+    /// Set the position in desktop coordinates
+    pub fn with_position(mut self, x: i32, y: i32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Set the scale factor
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = Some(to_thousandths(scale));
+        self
+    }
+
+    /// Set the refresh rate in Hz
+    pub fn with_refresh_rate(mut self, refresh_rate: f64) -> Self {
+        self.refresh_rate = Some(to_thousandths(refresh_rate));
+        self
+    }
+
+    /// Set whether the display is built in
+    pub fn with_builtin(mut self, builtin: bool) -> Self {
+        self.builtin = Some(builtin);
+        self
+    }
+
+    /// Set the raw EDID bytes
+    pub fn with_edid(mut self, edid: Vec<u8>) -> Self {
+        self.edid = Some(edid);
+        self
+    }
+
+    /// Mark the display as primary, recording how that was decided
+    pub fn with_primary_source(mut self, source: PrimarySource) -> Self {
+        self.is_primary = true;
+        self.primary_source = Some(source);
+        self
+    }
+}
+
+/// How a backend decided which display is the primary one.
+///
+/// Reported by [`Display::primary_source`] on the primary display. On Linux the
+/// sources are tried in the order the variants are listed here, from an explicit
+/// [`ProfileConfig::primary_display`] down to simply picking the first display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum PrimarySource {
+    /// Selected by [`ProfileConfig::primary_display`].
+    Config,
+    /// Reported by the operating system (macOS `CGDisplayIsMain`, Windows
+    /// `MONITORINFOF_PRIMARY`).
+    System,
+    /// colord device metadata (`OutputPriority=primary`), set by the desktop's
+    /// color plugin on both X11 and Wayland sessions.
+    Colord,
+    /// The X11 RandR primary output.
+    #[cfg_attr(feature = "serde", serde(rename = "randr"))]
+    RandR,
+    /// No better information was available, so the first display was picked.
+    FirstDisplay,
+}
+
+impl std::fmt::Display for PrimarySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrimarySource::Config => write!(f, "configuration"),
+            PrimarySource::System => write!(f, "system"),
+            PrimarySource::Colord => write!(f, "colord"),
+            PrimarySource::RandR => write!(f, "RandR"),
+            PrimarySource::FirstDisplay => write!(f, "first display"),
+        }
+    }
+}
+
+/// A positive quantity in thousandths of a unit, rounded to the nearest one
+fn to_thousandths(value: f64) -> u32 {
+    (value * 1000.0).round() as u32
+}
+
+/// The quantity stored by [`to_thousandths`]
+fn from_thousandths(thousandths: u32) -> f64 {
+    f64::from(thousandths) / 1000.0
+}
+
+/// Serializes a quantity kept in thousandths as a plain number of units
+#[cfg(feature = "serde")]
+mod thousandths {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(thousandths) => serializer.serialize_some(&super::from_thousandths(*thousandths)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u32>, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.map(super::to_thousandths))
+    }
+}
+
+/// Serializes EDID bytes as a lowercase hex string, the form tools like
+/// `edid-decode` and `xrandr --verbose` use.
+#[cfg(feature = "serde")]
+mod edid_hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::fmt::Write;
+
+    pub fn serialize<S: Serializer>(
+        edid: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match edid {
+            Some(bytes) => {
+                let mut hex = String::with_capacity(bytes.len() * 2);
+                for byte in bytes {
+                    let _ = write!(hex, "{:02x}", byte);
+                }
+                serializer.serialize_some(&hex)
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        let Some(hex) = Option::<String>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+
+        if hex.len() % 2 != 0 {
+            return Err(D::Error::custom("EDID hex string has an odd length"));
+        }
+
+        hex.as_bytes()
+            .chunks_exact(2)
+            .map(|pair| Ok((hex_digit(pair[0])? << 4) | hex_digit(pair[1])?))
+            .collect::<Result<Vec<u8>, _>>()
+            .map(Some)
+    }
+
+    fn hex_digit<E: Error>(byte: u8) -> Result<u8, E> {
+        match byte {
+            b'0'..=b'9' => Ok(byte - b'0'),
+            b'a'..=b'f' => Ok(byte - b'a' + 10),
+            b'A'..=b'F' => Ok(byte - b'A' + 10),
+            _ => Err(E::custom(format!(
+                "invalid character in EDID hex string: {:?}",
+                char::from(byte)
+            ))),
+        }
+    }
 }
 
 /// Information about an ICC color profile associated with a display.
@@ -267,6 +776,12 @@ pub struct Display {
 /// assert!(profile.file_path.is_some());
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(description = "Information about an ICC color profile associated with a display")
+)]
 pub struct ProfileInfo {
     /// Name of the color profile.
     ///
@@ -298,6 +813,75 @@ pub struct ProfileInfo {
     pub color_space: ColorSpace,
 }
 
+/// A profile assigned to a display, as returned by [`DisplayProfileProvider::get_profiles`].
+///
+/// A display can have several profiles assigned at once. On Linux, colord keeps
+/// them ordered by relevance (for example a calibrated profile, then one generated
+/// from EDID, then a standard color space), and the first one is the default that
+/// applications should use.
+///
+/// # Examples
+///
+/// ```rust
+/// use display_icc::{AssignedProfile, ColorSpace, ProfileInfo};
+///
+/// let assigned = AssignedProfile::new(
+///     ProfileInfo {
+///         name: "sRGB".to_string(),
+///         description: None,
+///         file_path: None,
+///         color_space: ColorSpace::RGB,
+///     },
+///     true,
+/// );
+///
+/// assert!(assigned.is_default);
+/// assert!(assigned.metadata.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(description = "A profile assigned to a display, in relevance order")
+)]
+pub struct AssignedProfile {
+    /// Profile information, as [`DisplayProfileProvider::get_profile`] would report it.
+    pub profile: ProfileInfo,
+
+    /// Backend-specific profile identifier (e.g. the colord profile ID), if any.
+    pub id: Option<String>,
+
+    /// Whether this is the default profile for the display.
+    ///
+    /// Exactly one profile in a non-empty list is the default.
+    pub is_default: bool,
+
+    /// Backend-specific profile kind (e.g. colord's `display-device`), if known.
+    pub kind: Option<String>,
+
+    /// Additional key/value metadata attached to the profile.
+    ///
+    /// On Linux this is colord's profile metadata, such as `DATA_source`
+    /// (`calib`, `edid` or `standard`) or `EDID_md5`. For Wayland image
+    /// descriptions it holds the description's parameters, keyed by protocol
+    /// event: `primaries`, `tf_named`, `luminances` and so on.
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl AssignedProfile {
+    /// Create an assigned profile with no identifier, kind or metadata
+    pub fn new(profile: ProfileInfo, is_default: bool) -> Self {
+        Self {
+            profile,
+            id: None,
+            is_default,
+            kind: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+}
+
 /// Supported color spaces for ICC profiles.
 ///
 /// This enum represents the primary color spaces that display ICC profiles
@@ -318,6 +902,12 @@ pub struct ProfileInfo {
 /// assert_eq!(format!("{}", unknown_space), "Unknown");
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(description = "Color space of an ICC profile")
+)]
 pub enum ColorSpace {
     /// RGB color space (most common).
     ///
@@ -357,6 +947,10 @@ impl std::fmt::Display for ColorSpace {
 /// This struct allows customization of how the library retrieves ICC profiles
 /// on different platforms. Different options may have no effect on certain platforms.
 ///
+/// The struct is `#[non_exhaustive]` so that more options can be added without
+/// breaking changes; start from [`ProfileConfig::default`] and use the `with_*`
+/// methods.
+///
 /// # Examples
 ///
 /// ```rust
@@ -368,18 +962,24 @@ impl std::fmt::Display for ColorSpace {
 /// assert!(default_config.fallback_enabled);
 ///
 /// // Custom configuration for performance (Linux: use D-Bus)
-/// let fast_config = ProfileConfig {
-///     linux_prefer_dbus: true,
-///     fallback_enabled: false,  // Skip fallbacks for speed
-/// };
+/// let fast_config = ProfileConfig::default()
+///     .with_linux_prefer_dbus(true)
+///     .with_fallback_enabled(false); // Skip fallbacks for speed
 ///
 /// // Custom configuration for reliability (Linux: use colormgr command)
-/// let reliable_config = ProfileConfig {
-///     linux_prefer_dbus: false, // Use command-line tools on Linux
-///     fallback_enabled: true,   // Try all available methods
-/// };
+/// let reliable_config = ProfileConfig::default()
+///     .with_linux_prefer_dbus(false) // Use command-line tools on Linux
+///     .with_fallback_enabled(true); // Try all available methods
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(description = "Configuration options for profile retrieval behavior")
+)]
+#[non_exhaustive]
 pub struct ProfileConfig {
     /// Linux: prefer D-Bus API over colormgr command.
     ///
@@ -399,11 +999,68 @@ pub struct ProfileConfig {
     ///
     /// **Fallback behaviors**:
     /// - **macOS**: Fall back to known Apple display profiles
-    /// - **Linux**: Fall back from D-Bus to colormgr to file system scanning
+    /// - **Linux**: Fall back from D-Bus to colormgr to the DRM connectors in
+    ///   sysfs to file system scanning (which picks the display profile that
+    ///   best matches the monitor), and offer a profile generated from the
+    ///   monitor's EDID for displays with none assigned
     /// - **Windows**: Fall back from API to registry to directory scanning
     ///
     /// **Default**: `true`
     pub fallback_enabled: bool,
+
+    /// Additional directories to search for ICC profiles.
+    ///
+    /// These are searched before the Linux standard locations when the
+    /// library scans the file system for profiles. See
+    /// [`profile_search_paths`](Self::profile_search_paths) for the full list.
+    ///
+    /// **Platform effect**: Linux file system fallback.
+    ///
+    /// **Default**: empty
+    pub extra_profile_dirs: Vec<PathBuf>,
+
+    /// Display to treat as primary, by display ID or connector name (e.g. `DP-1`).
+    ///
+    /// Overrides the platform's own notion of the primary display. If no display
+    /// matches, the platform's detection is used as usual.
+    ///
+    /// **Platform effect**: Linux only. On Linux the primary display is otherwise
+    /// taken from colord's `OutputPriority` metadata, then the X11 RandR primary
+    /// output, then the first display; see [`PrimarySource`].
+    ///
+    /// **Default**: `None`
+    pub primary_display: Option<String>,
+
+    /// Time limit for each external command, D-Bus call and Wayland query.
+    ///
+    /// A command still running at the deadline is killed and the call fails
+    /// with a timeout error, so a wedged colord daemon cannot hang the caller.
+    /// Serialized as `timeout_ms`.
+    ///
+    /// **Platform effect**: Linux (`colormgr`, `xrandr`, colord D-Bus calls, X
+    /// server requests and reading image descriptions from the Wayland
+    /// compositor).
+    ///
+    /// **Default**: 5 seconds
+    #[cfg_attr(feature = "serde", serde(rename = "timeout_ms", with = "duration_ms"))]
+    #[cfg_attr(feature = "schema", schemars(with = "u64"))]
+    pub timeout: Duration,
+
+    /// Linux: file assigning profiles to displays found through DRM.
+    ///
+    /// Without colord, displays are enumerated from the DRM connectors in
+    /// sysfs, and their profiles come from this file: one
+    /// `<display> = <profile>` line per display, the display named by its ID,
+    /// its connector (e.g. `DP-1`) or the MD5 of its EDID. Relative profile
+    /// paths are looked up in the [profile search paths](Self::profile_search_paths).
+    /// A display without an entry gets the `edid-<md5>.icc` profile from the
+    /// search paths, as GNOME names the profiles it generates, or else a
+    /// profile there made for its model or manufacturer.
+    ///
+    /// **Platform effect**: Linux only.
+    ///
+    /// **Default**: `None`, meaning `$XDG_CONFIG_HOME/display_icc/profiles.conf`
+    pub linux_profile_map: Option<PathBuf>,
 }
 
 impl Default for ProfileConfig {
@@ -411,10 +1068,97 @@ impl Default for ProfileConfig {
         Self {
             linux_prefer_dbus: true,
             fallback_enabled: true,
+            extra_profile_dirs: Vec::new(),
+            primary_display: None,
+            timeout: Duration::from_secs(5),
+            linux_profile_map: None,
         }
     }
 }
 
+/// Serializes a [`Duration`] as whole milliseconds
+#[cfg(feature = "serde")]
+mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+impl ProfileConfig {
+    /// Set whether Linux prefers the colord D-Bus API over `colormgr`
+    pub fn with_linux_prefer_dbus(mut self, linux_prefer_dbus: bool) -> Self {
+        self.linux_prefer_dbus = linux_prefer_dbus;
+        self
+    }
+
+    /// Set whether fallback mechanisms are tried
+    pub fn with_fallback_enabled(mut self, fallback_enabled: bool) -> Self {
+        self.fallback_enabled = fallback_enabled;
+        self
+    }
+
+    /// Set the additional directories to search for ICC profiles
+    pub fn with_extra_profile_dirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.extra_profile_dirs = dirs;
+        self
+    }
+
+    /// Set the display to treat as primary, by display ID or connector name
+    pub fn with_primary_display(mut self, display: impl Into<String>) -> Self {
+        self.primary_display = Some(display.into());
+        self
+    }
+
+    /// Set the time limit for each external command and system call
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the Linux file assigning profiles to displays found through DRM
+    pub fn with_linux_profile_map(mut self, path: impl Into<PathBuf>) -> Self {
+        self.linux_profile_map = Some(path.into());
+        self
+    }
+
+    /// Directories searched for ICC profiles, in search order.
+    ///
+    /// This is [`extra_profile_dirs`](Self::extra_profile_dirs) followed, on
+    /// Linux, by the standard locations, with duplicates removed. The Linux
+    /// standard locations follow the XDG Base Directory specification:
+    ///
+    /// 1. `$XDG_DATA_HOME/icc` (default `~/.local/share/icc`)
+    /// 2. `~/.color/icc`
+    /// 3. `color/icc` under each of `$XDG_DATA_DIRS` (default `/usr/local/share:/usr/share`)
+    /// 4. `/var/lib/colord/icc` and `/var/lib/color/icc`
+    ///
+    /// On macOS and Windows, where profiles come from the system's colour
+    /// management rather than a file system scan, this is just the extra
+    /// directories. Directories are listed whether or not they exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use display_icc::ProfileConfig;
+    /// use std::path::PathBuf;
+    ///
+    /// let config =
+    ///     ProfileConfig::default().with_extra_profile_dirs(vec![PathBuf::from("/opt/profiles")]);
+    ///
+    /// assert_eq!(config.profile_search_paths()[0], PathBuf::from("/opt/profiles"));
+    /// ```
+    pub fn profile_search_paths(&self) -> Vec<PathBuf> {
+        search_path::resolve_search_paths(&self.extra_profile_dirs, |name| std::env::var_os(name))
+    }
+}
+
 /// Errors that can occur during profile retrieval operations.
 ///
 /// This enum covers all possible error conditions that can arise when working
@@ -445,6 +1189,9 @@ impl Default for ProfileConfig {
 ///     Err(ProfileError::ParseError(msg)) => {
 ///         eprintln!("Failed to parse profile data: {}", msg);
 ///     }
+///     Err(ProfileError::Timeout(msg)) => {
+///         eprintln!("The system did not answer in time: {}", msg);
+///     }
 /// }
 /// ```
 #[derive(Debug, Error, Clone)]
@@ -497,11 +1244,22 @@ pub enum ProfileError {
     /// - Registry data is in an unexpected format (Windows)
     #[error("Parse error: {0}")]
     ParseError(String),
+
+    /// An external command or system service did not answer in time.
+    ///
+    /// This error occurs when a call exceeds [`ProfileConfig::timeout`], such as
+    /// `colormgr` or a D-Bus call hanging on an unresponsive colord daemon
+    /// (Linux). Commands that time out are killed.
+    #[error("Timed out: {0}")]
+    Timeout(String),
 }
 
 impl From<std::io::Error> for ProfileError {
     fn from(error: std::io::Error) -> Self {
-        ProfileError::IoError(error.to_string())
+        match error.kind() {
+            std::io::ErrorKind::TimedOut => ProfileError::Timeout(error.to_string()),
+            _ => ProfileError::IoError(error.to_string()),
+        }
     }
 }
 
@@ -627,6 +1385,282 @@ pub trait DisplayProfileProvider {
     /// - **Linux**: Reads ICC files from file system based on colormgr associations
     /// - **Windows**: Reads ICC files from Windows color directory
     fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError>;
+
+    /// Get every profile assigned to a specific display.
+    ///
+    /// Returns the profiles in relevance order, with the default profile (the one
+    /// [`get_profile`](Self::get_profile) reports) first and marked with
+    /// [`AssignedProfile::is_default`].
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - The display to get the profiles for
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<AssignedProfile>)` - Assigned profiles, possibly empty
+    /// - `Err(ProfileError::DisplayNotFound)` - If the display no longer exists
+    /// - `Err(ProfileError)` - If profile retrieval fails
+    ///
+    /// # Platform Behavior
+    ///
+    /// - **Linux**: Returns every profile colord associates with the device, in colord's order
+    /// - **macOS / Windows**: Only the active profile is known, so the default
+    ///   implementation wraps [`get_profile`](Self::get_profile)
+    fn get_profiles(&self, display: &Display) -> Result<Vec<AssignedProfile>, ProfileError> {
+        match self.get_profile(display) {
+            Ok(profile) => Ok(vec![AssignedProfile::new(profile, true)]),
+            Err(ProfileError::ProfileNotAvailable(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Find a display by its [stable ID](Display::stable_id), such as one saved
+    /// in an earlier session.
+    ///
+    /// A display with exactly that stable ID is preferred. A monitor without
+    /// a serial number that has moved to another connector is still found,
+    /// as long as it is the only one of its model connected.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Display)` - The display with the stable ID
+    /// - `Err(ProfileError::DisplayNotFound)` - If no connected display matches
+    /// - `Err(ProfileError)` - If display enumeration fails
+    fn find_display(&self, stable_id: &str) -> Result<Display, ProfileError> {
+        resolve_stable_id(self.get_displays()?, stable_id)
+            .ok_or_else(|| ProfileError::DisplayNotFound(stable_id.to_string()))
+    }
+
+    /// Get raw ICC profile data for one of the profiles assigned to a display.
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - The display the profile is assigned to
+    /// * `profile` - A profile returned by [`get_profiles`](Self::get_profiles) for `display`
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<u8>)` - Raw ICC profile binary data
+    /// - `Err(ProfileError::ProfileNotAvailable)` - If the profile has no data that can be read
+    /// - `Err(ProfileError::IoError)` - If the profile file cannot be read
+    ///
+    /// The default implementation reads the profile's file if it has one, and
+    /// otherwise falls back to [`get_profile_data`](Self::get_profile_data) for
+    /// the default profile.
+    fn get_assigned_profile_data(
+        &self,
+        display: &Display,
+        profile: &AssignedProfile,
+    ) -> Result<Vec<u8>, ProfileError> {
+        if let Some(path) = &profile.profile.file_path {
+            return std::fs::read(path).map_err(|e| ProfileError::IoError(e.to_string()));
+        }
+
+        if profile.is_default {
+            return self.get_profile_data(display);
+        }
+
+        Err(ProfileError::ProfileNotAvailable(format!(
+            "Profile '{}' has no file",
+            profile.profile.name
+        )))
+    }
+
+    /// Watch for displays being connected or disconnected, for profile
+    /// changes and for a new primary display.
+    ///
+    /// Events arrive on the returned [`ProfileWatcher`] until it is dropped.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ProfileWatcher)` - A watcher delivering [`DisplayEvent`]s
+    /// - `Err(ProfileError::UnsupportedPlatform)` - If the provider cannot watch
+    /// - `Err(ProfileError)` - If watching cannot start
+    ///
+    /// # Platform Behavior
+    ///
+    /// - **Linux**: Listens to colord's D-Bus signals with the `dbus-support`
+    ///   feature, including primary display changes from colord metadata;
+    ///   otherwise, or when colord is unavailable, polls
+    /// - **macOS / Windows**: Polls, comparing snapshots every two seconds
+    ///
+    /// The default implementation returns `UnsupportedPlatform`; providers
+    /// without change notifications of their own can return
+    /// [`ProfileWatcher::polling`] over a provider the watcher can own.
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        Err(ProfileError::UnsupportedPlatform)
+    }
+}
+
+/// Boxed providers, such as those from [`create_provider`], forward every call
+impl<P: DisplayProfileProvider + ?Sized> DisplayProfileProvider for Box<P> {
+    fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
+        (**self).get_displays()
+    }
+
+    fn get_primary_display(&self) -> Result<Display, ProfileError> {
+        (**self).get_primary_display()
+    }
+
+    fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
+        (**self).get_profile(display)
+    }
+
+    fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
+        (**self).get_profile_data(display)
+    }
+
+    fn get_profiles(&self, display: &Display) -> Result<Vec<AssignedProfile>, ProfileError> {
+        (**self).get_profiles(display)
+    }
+
+    fn find_display(&self, stable_id: &str) -> Result<Display, ProfileError> {
+        (**self).find_display(stable_id)
+    }
+
+    fn get_assigned_profile_data(
+        &self,
+        display: &Display,
+        profile: &AssignedProfile,
+    ) -> Result<Vec<u8>, ProfileError> {
+        (**self).get_assigned_profile_data(display, profile)
+    }
+
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        (**self).watch()
+    }
+}
+
+/// The display `stable_id` refers to, as described for [`DisplayProfileProvider::find_display`]
+fn resolve_stable_id(displays: Vec<Display>, stable_id: &str) -> Option<Display> {
+    let stable_ids: Vec<String> = displays.iter().map(Display::stable_id).collect();
+    if let Some(index) = stable_ids.iter().position(|id| id == stable_id) {
+        return displays.into_iter().nth(index);
+    }
+
+    // `edid:<vendor>:<product>`, with or without a connector, names a model
+    let model = stable_id.split('@').next()?;
+    if !model.starts_with("edid:") || model.matches(':').count() != 2 {
+        return None;
+    }
+    let mut matches = stable_ids.iter().enumerate().filter(|(_, id)| {
+        id.strip_prefix(model)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('@'))
+    });
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => displays.into_iter().nth(index),
+        _ => None,
+    }
+}
+
+/// Trait for changing which ICC profiles are assigned to displays.
+///
+/// Kept separate from the read-only [`DisplayProfileProvider`], as writing needs
+/// more from the system: on Linux, a colord daemon that accepts changes from
+/// the calling user. Only the Linux backend implements it.
+///
+/// Profiles are referred to by their backend ID, as reported in
+/// [`AssignedProfile::id`] and returned when a profile is imported or created.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # #[cfg(target_os = "linux")]
+/// # fn main() -> Result<(), display_icc::ProfileError> {
+/// use display_icc::{DisplayProfileManager, DisplayProfileProvider, LinuxProfileProvider};
+/// use std::path::Path;
+///
+/// let provider = LinuxProfileProvider::new();
+/// let display = provider.get_primary_display()?;
+///
+/// // Copy a freshly generated profile into the user store and make it default
+/// let id = provider.install_profile(&display, Path::new("calibrated.icc"))?;
+/// println!("Installed {} on {}", id, display.name);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(target_os = "linux"))]
+/// # fn main() {}
+/// ```
+pub trait DisplayProfileManager {
+    /// Copy an ICC profile into the user's profile store and register it.
+    ///
+    /// Returns the ID of the registered profile, which is not yet assigned to
+    /// any display.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(String)` - The profile ID
+    /// - `Err(ProfileError::ParseError)` - If the file is not an ICC profile
+    /// - `Err(ProfileError::IoError)` - If the file cannot be read or copied
+    /// - `Err(ProfileError)` - If registering the profile fails
+    ///
+    /// # Platform Behavior
+    ///
+    /// - **Linux**: Copies into `$XDG_DATA_HOME/icc`, like `colormgr import-profile`
+    fn import_profile(&self, path: &Path) -> Result<String, ProfileError>;
+
+    /// Register an ICC profile file where it is, without copying it.
+    ///
+    /// Returns the ID of the profile, which is the existing ID if the file is
+    /// already registered.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(String)` - The profile ID
+    /// - `Err(ProfileError::ParseError)` - If the file is not an ICC profile
+    /// - `Err(ProfileError)` - If registering the profile fails
+    ///
+    /// # Platform Behavior
+    ///
+    /// - **Linux**: Creates a persistent colord profile pointing at the file
+    fn create_profile(&self, path: &Path) -> Result<String, ProfileError>;
+
+    /// Assign a registered profile to a display, after its existing profiles.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - If the profile was assigned
+    /// - `Err(ProfileError::DisplayNotFound)` - If the display no longer exists
+    /// - `Err(ProfileError::ProfileNotAvailable)` - If no profile has the ID
+    /// - `Err(ProfileError)` - If the change is refused, for example because
+    ///   the profile is already assigned
+    fn add_profile(&self, display: &Display, profile_id: &str) -> Result<(), ProfileError>;
+
+    /// Make an assigned profile the display's default.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - If the profile is now the default
+    /// - `Err(ProfileError::DisplayNotFound)` - If the display no longer exists
+    /// - `Err(ProfileError::ProfileNotAvailable)` - If no profile has the ID
+    /// - `Err(ProfileError)` - If the change is refused
+    fn make_profile_default(&self, display: &Display, profile_id: &str)
+        -> Result<(), ProfileError>;
+
+    /// Unassign a profile from a display. The profile stays registered.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` - If the profile was unassigned
+    /// - `Err(ProfileError::DisplayNotFound)` - If the display no longer exists
+    /// - `Err(ProfileError::ProfileNotAvailable)` - If no profile has the ID
+    /// - `Err(ProfileError)` - If the change is refused, for example because
+    ///   the profile is not assigned to the display
+    fn remove_profile(&self, display: &Display, profile_id: &str) -> Result<(), ProfileError>;
+
+    /// Import an ICC profile, assign it to a display and make it the default.
+    ///
+    /// Returns the ID of the imported profile. The default implementation calls
+    /// [`import_profile`](Self::import_profile),
+    /// [`add_profile`](Self::add_profile) and
+    /// [`make_profile_default`](Self::make_profile_default) in turn.
+    fn install_profile(&self, display: &Display, path: &Path) -> Result<String, ProfileError> {
+        let profile_id = self.import_profile(path)?;
+        self.add_profile(display, &profile_id)?;
+        self.make_profile_default(display, &profile_id)?;
+        Ok(profile_id)
+    }
 }
 
 /// Supported platforms for ICC profile retrieval
@@ -753,10 +1787,9 @@ pub fn create_provider() -> Result<Box<dyn DisplayProfileProvider>, ProfileError
 ///
 /// # fn example() -> Result<(), display_icc::ProfileError> {
 /// // Create configuration for maximum performance
-/// let config = ProfileConfig {
-///     linux_prefer_dbus: true,  // Use D-Bus on Linux (faster)
-///     fallback_enabled: false,  // Skip fallbacks for speed
-/// };
+/// let config = ProfileConfig::default()
+///     .with_linux_prefer_dbus(true) // Use D-Bus on Linux (faster)
+///     .with_fallback_enabled(false); // Skip fallbacks for speed
 ///
 /// let provider = create_provider_with_config(config)?;
 ///
@@ -772,10 +1805,9 @@ pub fn create_provider() -> Result<Box<dyn DisplayProfileProvider>, ProfileError
 ///
 /// # fn example() -> Result<(), display_icc::ProfileError> {
 /// // Create configuration for maximum reliability
-/// let config = ProfileConfig {
-///     linux_prefer_dbus: false, // Use colormgr command on Linux (more reliable)
-///     fallback_enabled: true,   // Try all available methods
-/// };
+/// let config = ProfileConfig::default()
+///     .with_linux_prefer_dbus(false) // Use colormgr command on Linux (more reliable)
+///     .with_fallback_enabled(true); // Try all available methods
 ///
 /// let provider = create_provider_with_config(config)?;
 /// let primary = provider.get_primary_display()?;
@@ -884,10 +1916,9 @@ pub fn get_primary_display_profile() -> Result<ProfileInfo, ProfileError> {
 ///
 /// # fn example() -> Result<(), display_icc::ProfileError> {
 /// // Configuration for maximum reliability
-/// let config = ProfileConfig {
-///     linux_prefer_dbus: false, // Use command-line tools on Linux
-///     fallback_enabled: true,   // Try all available methods
-/// };
+/// let config = ProfileConfig::default()
+///     .with_linux_prefer_dbus(false) // Use command-line tools on Linux
+///     .with_fallback_enabled(true); // Try all available methods
 ///
 /// let profile = get_primary_display_profile_with_config(config)?;
 /// println!("Primary display profile: {}", profile.name);
@@ -900,10 +1931,9 @@ pub fn get_primary_display_profile() -> Result<ProfileInfo, ProfileError> {
 ///
 /// # fn example() -> Result<(), display_icc::ProfileError> {
 /// // Configuration for maximum performance
-/// let config = ProfileConfig {
-///     linux_prefer_dbus: true,  // Use faster D-Bus API on Linux
-///     fallback_enabled: false,  // Skip fallbacks for speed
-/// };
+/// let config = ProfileConfig::default()
+///     .with_linux_prefer_dbus(true) // Use faster D-Bus API on Linux
+///     .with_fallback_enabled(false); // Skip fallbacks for speed
 ///
 /// let profile = get_primary_display_profile_with_config(config)?;
 /// println!("Primary display profile: {}", profile.name);
@@ -1065,6 +2095,12 @@ pub fn get_primary_display_profile_data_with_config(
 
 /// ICC profile header information extracted from profile data
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(description = "ICC profile header information extracted from profile data")
+)]
 pub struct IccHeader {
     /// Profile size in bytes (from header)
     pub profile_size: u32,
@@ -1264,42 +2300,145 @@ pub fn parse_icc_header(data: &[u8]) -> Result<IccHeader, ProfileError> {
     IccHeader::parse(data)
 }
 
+/// Find the ICC profiles stored under a set of directories.
+///
+/// Directories are scanned recursively in order. Only files with an `.icc` or
+/// `.icm` extension whose header carries the ICC `acsp` signature are returned,
+/// and a profile reachable through several paths (e.g. via symlinks) is
+/// reported once. Missing or unreadable directories are skipped.
+///
+/// This is the scan the Linux file system fallback performs over
+/// [`ProfileConfig::profile_search_paths`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use display_icc::{find_icc_profiles, ProfileConfig};
+///
+/// let config = ProfileConfig::default();
+/// for profile in find_icc_profiles(&config.profile_search_paths()) {
+///     println!("{}", profile.display());
+/// }
+/// ```
+pub fn find_icc_profiles(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    search_path::find_profiles(dirs)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_display_creation() {
-        let display = Display {
-            id: "test_id".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display = Display::new("test_id".to_string(), "Test Display".to_string(), true);
 
         assert_eq!(display.id, "test_id");
         assert_eq!(display.name, "Test Display");
         assert!(display.is_primary);
     }
 
+    #[test]
+    fn test_stable_id() {
+        use crate::edid::test_support::{edid, TestMonitor};
+
+        let dell = edid(&TestMonitor::default(), &[]);
+        let display = Display::new("xrandr-Dell", "Dell", false)
+            .with_connector("DP-1")
+            .with_edid(dell.clone());
+        assert_eq!(display.stable_id(), "edid:DEL:a0e8:8LX2N63");
+
+        // The serial descriptor text, made safe to embed
+        let spaced = edid(
+            &TestMonitor {
+                serial: "AB 12:3@4",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        let display = Display::new("1", "Dell", false).with_edid(spaced);
+        assert_eq!(display.stable_id(), "edid:DEL:a0e8:AB_12_3_4");
+
+        // Only a numeric serial
+        let numeric = edid(
+            &TestMonitor {
+                serial: "",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        let display = Display::new("1", "Dell", false).with_edid(numeric);
+        assert_eq!(display.stable_id(), "edid:DEL:a0e8:0x4c4d3253");
+
+        // No serial at all
+        let anonymous = edid(
+            &TestMonitor {
+                serial: "",
+                serial_number: 0,
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        let display = Display::new("1", "Dell", false).with_edid(anonymous.clone());
+        assert_eq!(display.stable_id(), "edid:DEL:a0e8");
+        let display = display.with_connector("HDMI-A-1");
+        assert_eq!(display.stable_id(), "edid:DEL:a0e8@HDMI-A-1");
+
+        // An unparseable EDID is as good as none
+        let display = Display::new("1", "Dell", false)
+            .with_connector("DP-2")
+            .with_edid(vec![0; 16]);
+        assert_eq!(display.stable_id(), "connector:DP-2");
+        assert_eq!(Display::new("7", "Dell", false).stable_id(), "id:7");
+    }
+
+    #[test]
+    fn test_resolve_stable_id() {
+        use crate::edid::test_support::{edid, TestMonitor};
+
+        let anonymous = edid(
+            &TestMonitor {
+                serial: "",
+                serial_number: 0,
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        let displays = vec![
+            Display::new("a", "Dell", true)
+                .with_connector("DP-1")
+                .with_edid(edid(&TestMonitor::default(), &[])),
+            Display::new("b", "Dell", false)
+                .with_connector("DP-3")
+                .with_edid(anonymous.clone()),
+            Display::new("c", "Projector", false).with_connector("HDMI-A-1"),
+        ];
+        let resolve = |id: &str| resolve_stable_id(displays.clone(), id).map(|d| d.id);
+
+        assert_eq!(resolve("edid:DEL:a0e8:8LX2N63").as_deref(), Some("a"));
+        assert_eq!(resolve("edid:DEL:a0e8@DP-3").as_deref(), Some("b"));
+        assert_eq!(resolve("connector:HDMI-A-1").as_deref(), Some("c"));
+        assert_eq!(resolve("edid:DEL:a0e8:OTHER"), None);
+
+        // The serial-less monitor moved from DP-2, and is the only one of its model
+        assert_eq!(resolve("edid:DEL:a0e8@DP-2").as_deref(), Some("b"));
+
+        // With two of them connected, there is no telling which one was meant
+        let mut twins = displays.clone();
+        twins.push(
+            Display::new("d", "Dell", false)
+                .with_connector("DP-4")
+                .with_edid(anonymous),
+        );
+        assert_eq!(resolve_stable_id(twins, "edid:DEL:a0e8@DP-2"), None);
+    }
+
     #[test]
     fn test_display_equality() {
-        let display1 = Display {
-            id: "test_id".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display1 = Display::new("test_id".to_string(), "Test Display".to_string(), true);
 
-        let display2 = Display {
-            id: "test_id".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display2 = Display::new("test_id".to_string(), "Test Display".to_string(), true);
 
-        let display3 = Display {
-            id: "different_id".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display3 = Display::new("different_id".to_string(), "Test Display".to_string(), true);
 
         assert_eq!(display1, display2);
         assert_ne!(display1, display3);
@@ -1345,12 +2484,34 @@ mod tests {
         let config = ProfileConfig {
             linux_prefer_dbus: false,
             fallback_enabled: false,
+            ..ProfileConfig::default()
         };
 
         assert!(!config.linux_prefer_dbus);
         assert!(!config.fallback_enabled);
     }
 
+    #[test]
+    fn test_profile_config_builder() {
+        let config = ProfileConfig::default()
+            .with_linux_prefer_dbus(false)
+            .with_fallback_enabled(false)
+            .with_extra_profile_dirs(vec![PathBuf::from("/opt/profiles")])
+            .with_primary_display("DP-1")
+            .with_timeout(Duration::from_millis(250))
+            .with_linux_profile_map("/etc/display_icc/profiles.conf");
+
+        assert!(!config.linux_prefer_dbus);
+        assert!(!config.fallback_enabled);
+        assert_eq!(config.extra_profile_dirs, [PathBuf::from("/opt/profiles")]);
+        assert_eq!(config.primary_display.as_deref(), Some("DP-1"));
+        assert_eq!(config.timeout, Duration::from_millis(250));
+        assert_eq!(
+            config.linux_profile_map,
+            Some(PathBuf::from("/etc/display_icc/profiles.conf"))
+        );
+    }
+
     #[test]
     fn test_profile_error_display() {
         let error = ProfileError::UnsupportedPlatform;
@@ -1546,16 +2707,34 @@ mod api_tests {
         assert!(profile.file_path.is_some());
     }
 
+    #[test]
+    fn test_find_display_by_stable_id() {
+        let mut provider = MockProfileProvider::with_test_data();
+        provider.add_display(
+            Display::new("xrandr-HDMI-A-1", "Projector", false).with_connector("HDMI-A-1"),
+        );
+
+        let display = provider.find_display("connector:HDMI-A-1").unwrap();
+        assert_eq!(display.id, "xrandr-HDMI-A-1");
+        let display = provider.find_display("id:secondary").unwrap();
+        assert_eq!(display.name, "Secondary Display");
+
+        match provider.find_display("connector:DP-9") {
+            Err(ProfileError::DisplayNotFound(id)) => assert_eq!(id, "connector:DP-9"),
+            other => panic!("expected DisplayNotFound, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_get_primary_display_profile_no_primary() {
         let mut provider = MockProfileProvider::new();
 
         // Add non-primary display
-        let display = Display {
-            id: "secondary".to_string(),
-            name: "Secondary Display".to_string(),
-            is_primary: false,
-        };
+        let display = Display::new(
+            "secondary".to_string(),
+            "Secondary Display".to_string(),
+            false,
+        );
         provider.add_display(display);
 
         let result = provider.get_primary_display();
@@ -1600,11 +2779,11 @@ mod api_tests {
         let mut provider = MockProfileProvider::new();
 
         // Add display with profile
-        let display1 = Display {
-            id: "with_profile".to_string(),
-            name: "Display with Profile".to_string(),
-            is_primary: true,
-        };
+        let display1 = Display::new(
+            "with_profile".to_string(),
+            "Display with Profile".to_string(),
+            true,
+        );
         let profile1 = ProfileInfo {
             name: "Test Profile".to_string(),
             description: None,
@@ -1615,11 +2794,11 @@ mod api_tests {
         provider.set_profile("with_profile", profile1);
 
         // Add display without profile
-        let display2 = Display {
-            id: "without_profile".to_string(),
-            name: "Display without Profile".to_string(),
-            is_primary: false,
-        };
+        let display2 = Display::new(
+            "without_profile".to_string(),
+            "Display without Profile".to_string(),
+            false,
+        );
         provider.add_display(display2);
 
         // Simulate get_all_display_profiles behavior
@@ -1658,6 +2837,7 @@ mod api_tests {
         let config = ProfileConfig {
             linux_prefer_dbus: false,
             fallback_enabled: false,
+            ..ProfileConfig::default()
         };
 
         // Test that custom configuration is preserved
@@ -1683,15 +2863,109 @@ mod api_tests {
         assert!(!data.is_empty());
     }
 
+    #[test]
+    fn test_display_metadata_accessors() {
+        let display = Display::new("test", "Test Display", true);
+        assert_eq!(display.vendor(), None);
+        assert_eq!(display.connector(), None);
+        assert_eq!(display.is_builtin(), None);
+        assert_eq!(display.edid(), None);
+
+        let display = display
+            .with_vendor("Dell Inc.")
+            .with_model("DELL U2415")
+            .with_serial("HT8XN64P0D2S")
+            .with_connector("DP-1")
+            .with_resolution(1920, 1200)
+            .with_position(1920, 0)
+            .with_scale(1.0)
+            .with_refresh_rate(59.95)
+            .with_builtin(false)
+            .with_edid(vec![0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+
+        assert_eq!(display.id, "test");
+        assert_eq!(display.vendor(), Some("Dell Inc."));
+        assert_eq!(display.model(), Some("DELL U2415"));
+        assert_eq!(display.serial(), Some("HT8XN64P0D2S"));
+        assert_eq!(display.connector(), Some("DP-1"));
+        assert_eq!(display.resolution(), Some((1920, 1200)));
+        assert_eq!(display.position(), Some((1920, 0)));
+        assert_eq!(display.scale(), Some(1.0));
+        assert_eq!(display.refresh_rate(), Some(59.95));
+        assert_eq!(display.is_builtin(), Some(false));
+        assert_eq!(display.edid().map(<[u8]>::len), Some(8));
+        assert_eq!(display.primary_source(), None);
+
+        // Scale and refresh rate keep `Display` usable as a set member
+        let set: std::collections::HashSet<_> = [display.clone(), display.clone().with_scale(1.0)]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 1);
+
+        let display = display.with_primary_source(PrimarySource::Colord);
+        assert!(display.is_primary);
+        assert_eq!(display.primary_source(), Some(PrimarySource::Colord));
+        assert_eq!(PrimarySource::Colord.to_string(), "colord");
+    }
+
+    #[test]
+    fn test_get_profiles_default_implementation() {
+        let mut provider = MockProfileProvider::with_test_data();
+        let primary = provider.get_primary_display().unwrap();
+
+        let profiles = provider.get_profiles(&primary).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert!(profiles[0].is_default);
+        assert_eq!(profiles[0].profile.name, "sRGB IEC61966-2.1");
+
+        // Displays without a profile have an empty list rather than an error
+        let display = Display::new("bare".to_string(), "Bare Display".to_string(), false);
+        provider.add_display(display.clone());
+        assert!(provider.get_profiles(&display).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_assigned_profile_data_default_implementation() {
+        let provider = MockProfileProvider::with_test_data();
+        let primary = provider.get_primary_display().unwrap();
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"from file").unwrap();
+
+        let mut assigned = AssignedProfile::new(provider.get_profile(&primary).unwrap(), false);
+        assigned.profile.file_path = Some(file.path().to_path_buf());
+        assert_eq!(
+            provider
+                .get_assigned_profile_data(&primary, &assigned)
+                .unwrap(),
+            b"from file"
+        );
+
+        // Without a file only the default profile's data can be retrieved
+        assigned.profile.file_path = None;
+        assert!(matches!(
+            provider.get_assigned_profile_data(&primary, &assigned),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+
+        assigned.is_default = true;
+        assert_eq!(
+            provider
+                .get_assigned_profile_data(&primary, &assigned)
+                .unwrap(),
+            provider.get_profile_data(&primary).unwrap()
+        );
+    }
+
     #[test]
     fn test_error_propagation() {
         let mut provider = MockProfileProvider::new();
 
-        let display = Display {
-            id: "error_display".to_string(),
-            name: "Error Display".to_string(),
-            is_primary: true,
-        };
+        let display = Display::new(
+            "error_display".to_string(),
+            "Error Display".to_string(),
+            true,
+        );
 
         provider.add_display(display.clone());
         provider.set_failure(
@@ -1713,11 +2987,7 @@ mod api_tests {
 
         // Add multiple displays with different configurations
         for i in 0..5 {
-            let display = Display {
-                id: format!("display_{}", i),
-                name: format!("Display {}", i),
-                is_primary: i == 0,
-            };
+            let display = Display::new(format!("display_{}", i), format!("Display {}", i), i == 0);
 
             let profile = ProfileInfo {
                 name: format!("Profile {}", i),
@@ -1760,11 +3030,7 @@ mod api_tests {
         assert!(primary_result.is_err());
 
         // Test with non-existent display
-        let fake_display = Display {
-            id: "fake".to_string(),
-            name: "Fake Display".to_string(),
-            is_primary: false,
-        };
+        let fake_display = Display::new("fake".to_string(), "Fake Display".to_string(), false);
 
         let profile_result = provider.get_profile(&fake_display);
         assert!(profile_result.is_err());
@@ -1783,6 +3049,7 @@ mod configuration_tests {
         let config1 = ProfileConfig {
             linux_prefer_dbus: true,
             fallback_enabled: false,
+            ..ProfileConfig::default()
         };
 
         let config2 = config1.clone();
@@ -1808,26 +3075,34 @@ mod configuration_tests {
             ProfileConfig {
                 linux_prefer_dbus: true,
                 fallback_enabled: true,
+                ..ProfileConfig::default()
             },
             ProfileConfig {
                 linux_prefer_dbus: true,
                 fallback_enabled: false,
+                ..ProfileConfig::default()
             },
             ProfileConfig {
                 linux_prefer_dbus: false,
                 fallback_enabled: true,
+                ..ProfileConfig::default()
             },
             ProfileConfig {
                 linux_prefer_dbus: false,
                 fallback_enabled: false,
+                ..ProfileConfig::default()
             },
         ];
 
-        // Verify all configurations are valid and can be created
-        for config in &configs {
-            assert!(config.linux_prefer_dbus || !config.linux_prefer_dbus); // Always true, but tests field access
-            assert!(config.fallback_enabled || !config.fallback_enabled);
-        }
+        // Verify all configurations are valid and keep their fields
+        let combinations: Vec<(bool, bool)> = configs
+            .iter()
+            .map(|config| (config.linux_prefer_dbus, config.fallback_enabled))
+            .collect();
+        assert_eq!(
+            combinations,
+            [(true, true), (true, false), (false, true), (false, false)]
+        );
     }
 }
 
@@ -1856,6 +3131,7 @@ mod error_handling_tests {
             ProfileError::ProfileNotAvailable("test".to_string()),
             ProfileError::SystemError("test".to_string()),
             ProfileError::ParseError("test".to_string()),
+            ProfileError::Timeout("test".to_string()),
         ];
 
         for error in &errors {
@@ -1864,6 +3140,16 @@ mod error_handling_tests {
         }
     }
 
+    #[test]
+    fn test_timed_out_io_error_becomes_timeout() {
+        let io_error = io::Error::new(io::ErrorKind::TimedOut, "colormgr did not finish");
+
+        match ProfileError::from(io_error) {
+            ProfileError::Timeout(msg) => assert!(msg.contains("colormgr")),
+            other => panic!("Expected Timeout variant, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_profile_error_equality() {
         // Test that errors can be compared (for testing purposes)
@@ -1890,3 +3176,178 @@ mod error_handling_tests {
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_display_json_shape() {
+        let display = Display::new(
+            "69733382".to_string(),
+            "Built-in Retina Display".to_string(),
+            true,
+        );
+
+        let json = serde_json::to_value(&display).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": "69733382",
+                "name": "Built-in Retina Display",
+                "is_primary": true,
+                "vendor": null,
+                "model": null,
+                "serial": null,
+                "connector": null,
+                "resolution": null,
+                "position": null,
+                "scale": null,
+                "refresh_rate": null,
+                "builtin": null,
+                "edid": null,
+                "primary_source": null
+            })
+        );
+
+        let round_trip: Display = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, display);
+
+        // Documents written before the metadata fields existed still deserialize
+        let legacy: Display = serde_json::from_value(serde_json::json!({
+            "id": "69733382",
+            "name": "Built-in Retina Display",
+            "is_primary": true
+        }))
+        .unwrap();
+        assert_eq!(legacy, display);
+    }
+
+    #[test]
+    fn test_display_metadata_json_shape() {
+        let display = Display::new("xrandr-DP-1", "Dell U2415", false)
+            .with_connector("DP-1")
+            .with_resolution(1920, 1200)
+            .with_position(-1920, 0)
+            .with_refresh_rate(59.95)
+            .with_edid(vec![0x00, 0xff, 0x10]);
+
+        let json = serde_json::to_value(&display).unwrap();
+        assert_eq!(json["connector"], "DP-1");
+        assert_eq!(json["resolution"], serde_json::json!([1920, 1200]));
+        assert_eq!(json["position"], serde_json::json!([-1920, 0]));
+        assert_eq!(json["refresh_rate"], 59.95);
+        assert!(json["scale"].is_null());
+        assert_eq!(json["edid"], "00ff10");
+        assert!(json["primary_source"].is_null());
+
+        let primary = display.clone().with_primary_source(PrimarySource::RandR);
+        assert_eq!(
+            serde_json::to_value(&primary).unwrap()["primary_source"],
+            "randr"
+        );
+
+        let round_trip: Display = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, display);
+
+        let invalid = serde_json::json!({
+            "id": "x",
+            "name": "x",
+            "is_primary": false,
+            "edid": "0g"
+        });
+        assert!(serde_json::from_value::<Display>(invalid).is_err());
+    }
+
+    #[test]
+    fn test_display_edid_rejects_non_hex_input() {
+        for edid in ["aéb", "é0", "+f", "abc"] {
+            let json = format!(
+                r#"{{"id":"a","name":"b","is_primary":true,"edid":"{}"}}"#,
+                edid
+            );
+            assert!(
+                serde_json::from_str::<Display>(&json).is_err(),
+                "{:?} should be rejected",
+                edid
+            );
+        }
+
+        let display: Display =
+            serde_json::from_str(r#"{"id":"a","name":"b","is_primary":true,"edid":"00FFa0"}"#)
+                .unwrap();
+        assert_eq!(display.edid, Some(vec![0x00, 0xff, 0xa0]));
+    }
+
+    #[test]
+    fn test_profile_info_json_shape() {
+        let profile = ProfileInfo {
+            name: "sRGB".to_string(),
+            description: None,
+            file_path: Some(PathBuf::from("/usr/share/color/icc/sRGB.icc")),
+            color_space: ColorSpace::RGB,
+        };
+
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "sRGB",
+                "description": null,
+                "file_path": "/usr/share/color/icc/sRGB.icc",
+                "color_space": "RGB"
+            })
+        );
+
+        let round_trip: ProfileInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, profile);
+    }
+
+    #[test]
+    fn test_color_space_matches_display_impl() {
+        for color_space in [ColorSpace::RGB, ColorSpace::Lab, ColorSpace::Unknown] {
+            let json = serde_json::to_value(color_space).unwrap();
+            assert_eq!(json, serde_json::Value::String(color_space.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_icc_header_round_trip() {
+        let mut data = vec![0u8; 128];
+        data[0..4].copy_from_slice(&1024u32.to_be_bytes());
+        data[8..12].copy_from_slice(&0x04300000u32.to_be_bytes());
+        data[12..16].copy_from_slice(b"mntr");
+        data[16..20].copy_from_slice(b"RGB ");
+        data[20..24].copy_from_slice(b"XYZ ");
+        data[44..48].copy_from_slice(&1u32.to_be_bytes());
+
+        let header = IccHeader::parse(&data).unwrap();
+        let json = serde_json::to_value(&header).unwrap();
+
+        assert_eq!(json["version"], serde_json::json!([4, 3]));
+        assert_eq!(json["flags"], serde_json::json!(1));
+        assert_eq!(json["device_class"], "mntr");
+
+        let round_trip: IccHeader = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, header);
+    }
+
+    #[test]
+    fn test_profile_config_partial_deserialize() {
+        let config: ProfileConfig = serde_json::from_str(r#"{"fallback_enabled": false}"#).unwrap();
+
+        assert!(config.linux_prefer_dbus);
+        assert!(!config.fallback_enabled);
+        assert_eq!(config.timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_profile_config_timeout_in_milliseconds() {
+        let config: ProfileConfig = serde_json::from_str(r#"{"timeout_ms": 1500}"#).unwrap();
+        assert_eq!(config.timeout, Duration::from_millis(1500));
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["timeout_ms"], 1500);
+        assert!(json.get("timeout").is_none());
+    }
+}
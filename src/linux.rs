@@ -1,97 +1,187 @@
 //! Linux-specific implementation using colormgr and D-Bus
 
+use crate::command::{CommandOutput, CommandRunner, ProcessRunner};
 use crate::{
-    ColorSpace, Display, DisplayProfileProvider, ProfileConfig, ProfileError, ProfileInfo,
+    icc, md5, profile_from_edid, search_path, AssignedProfile, ColorSpace, Display,
+    DisplayProfileManager, DisplayProfileProvider, Edid, PrimarySource, ProfileConfig,
+    ProfileError, ProfileInfo, ProfileWatcher,
 };
-use std::path::PathBuf;
-use std::process::Command;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+mod colormgr;
+
+use colormgr::{ColormgrDevice, ColormgrProfile};
+
+mod drm;
+
+use drm::DrmConnector;
+
+mod fallback;
+
+mod profile_map;
+
+use profile_map::ProfileMap;
 
 #[cfg(feature = "dbus-support")]
-use dbus::blocking::Connection;
-#[cfg(feature = "dbus-support")]
-use std::time::Duration;
-
-/// Represents a colormgr device (display)
-#[derive(Debug, Clone)]
-struct ColormgrDevice {
-    id: String,
-    kind: String,
-    model: String,
-    vendor: String,
-    serial: String,
-    profiles: Vec<String>,
-}
-
-/// Represents a colormgr profile
-#[derive(Debug, Clone)]
-struct ColormgrProfile {
-    id: String,
-    filename: Option<PathBuf>,
-    title: Option<String>,
-    kind: String,
-    colorspace: String,
-}
-
-/// D-Bus interface constants for colord daemon
-#[cfg(feature = "dbus-support")]
-const COLORD_SERVICE: &str = "org.freedesktop.ColorManager";
+mod colord;
+
 #[cfg(feature = "dbus-support")]
-const COLORD_PATH: &str = "/org/freedesktop/ColorManager";
+use colord::ColordClient;
+
 #[cfg(feature = "dbus-support")]
-const COLORD_INTERFACE: &str = "org.freedesktop.ColorManager";
+mod watch;
+
+#[cfg(feature = "tokio")]
+mod asynchronous;
+
+#[cfg(feature = "x11")]
+mod x11;
+
+#[cfg(feature = "x11")]
+use x11::X11Profile;
+
+#[cfg(feature = "wayland")]
+mod wayland;
+
+#[cfg(feature = "wayland")]
+use wayland::WaylandOutput;
+
+/// ID prefix of displays found by enumerating DRM connectors
+const DRM_ID_PREFIX: &str = "drm-";
+
+/// ID prefix of displays found through the X11 `_ICC_PROFILE` properties
+#[cfg(feature = "x11")]
+const X11_ID_PREFIX: &str = "x11-";
+
+/// ID prefix of displays found through the Wayland colour management protocol
+#[cfg(feature = "wayland")]
+const WAYLAND_ID_PREFIX: &str = "wayland-";
 
 /// Linux implementation of DisplayProfileProvider using colormgr and D-Bus
+///
+/// Usually obtained through [`create_provider`](crate::create_provider); use the
+/// type directly for Linux-only features such as reading EDIDs from another sysfs root.
 pub struct LinuxProfileProvider {
     config: ProfileConfig,
+    runner: Arc<dyn CommandRunner>,
+    /// Cached result of probing for colormgr
+    colormgr_available: OnceLock<bool>,
+    /// Where sysfs is mounted, for reading monitor EDIDs
+    sysfs_root: PathBuf,
+    /// colord D-Bus client, connected on first use; `None` if colord is unavailable
+    #[cfg(feature = "dbus-support")]
+    colord: OnceLock<Option<ColordClient>>,
+    /// Compositor socket to use instead of `$WAYLAND_DISPLAY`
+    #[cfg(feature = "wayland")]
+    wayland_socket: Option<PathBuf>,
+    /// Root window profiles to report instead of asking the X server
+    #[cfg(all(test, feature = "x11"))]
+    x11_stand_in: Option<Vec<X11Profile>>,
+}
+
+impl Default for LinuxProfileProvider {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LinuxProfileProvider {
     /// Create a new Linux profile provider with default configuration
     pub fn new() -> Self {
-        Self {
-            config: ProfileConfig::default(),
-        }
+        Self::with_config(ProfileConfig::default())
     }
 
     /// Create a new Linux profile provider with custom configuration
     pub fn with_config(config: ProfileConfig) -> Self {
-        Self { config }
+        let runner = ProcessRunner::new(config.timeout);
+        Self::with_shared_runner(config, Arc::new(runner))
     }
 
-    /// Check if colormgr command is available
-    fn is_colormgr_available(&self) -> bool {
-        Command::new("colormgr")
-            .arg("--version")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+    fn with_shared_runner(config: ProfileConfig, runner: Arc<dyn CommandRunner>) -> Self {
+        Self {
+            config,
+            runner,
+            colormgr_available: OnceLock::new(),
+            sysfs_root: PathBuf::from("/sys"),
+            #[cfg(feature = "dbus-support")]
+            colord: OnceLock::new(),
+            #[cfg(feature = "wayland")]
+            wayland_socket: None,
+            #[cfg(all(test, feature = "x11"))]
+            x11_stand_in: None,
+        }
     }
 
-    /// Execute colormgr command and return output
-    fn execute_colormgr(&self, args: &[&str]) -> Result<String, ProfileError> {
-        if !self.is_colormgr_available() {
-            return Err(ProfileError::SystemError(
-                "colormgr command not found. Please install colord package.".to_string(),
-            ));
-        }
+    /// Read monitor EDIDs from the sysfs mounted at `root` instead of `/sys`
+    ///
+    /// EDIDs identify displays beyond what colord reports, and tie colord
+    /// devices to the connectors they are plugged into.
+    pub fn with_sysfs_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.sysfs_root = root.into();
+        self
+    }
 
-        let output = Command::new("colormgr")
-            .args(args)
-            .output()
-            .map_err(|e| ProfileError::SystemError(format!("Failed to execute colormgr: {}", e)))?;
+    /// Create a provider that runs colormgr and other tools through `runner`
+    ///
+    /// [`with_config`](Self::with_config) uses a [`ProcessRunner`] with the
+    /// configured timeout. Another runner can sandbox or log the commands, or
+    /// answer them from recorded output.
+    pub fn with_runner(config: ProfileConfig, runner: impl CommandRunner + 'static) -> Self {
+        Self::with_shared_runner(config, Arc::new(runner))
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ProfileError::SystemError(format!(
-                "colormgr command failed: {}",
-                stderr
-            )));
-        }
+    /// Create a provider that talks to colord through `client`
+    #[cfg(all(test, feature = "dbus-support"))]
+    fn with_colord(config: ProfileConfig, client: ColordClient) -> Self {
+        let provider = Self::with_config(config);
+        let _ = provider.colord.set(Some(client));
+        provider
+    }
+
+    /// Create a provider that reads image descriptions from the compositor on `socket`
+    #[cfg(all(test, feature = "wayland"))]
+    fn with_wayland_socket(config: ProfileConfig, socket: &Path) -> Self {
+        let mut provider = Self::with_config(config);
+        provider.wayland_socket = Some(socket.to_path_buf());
+        provider
+    }
+
+    /// Report `profiles` as the X11 root window properties
+    #[cfg(all(test, feature = "x11"))]
+    fn with_x11_profiles(mut self, profiles: Vec<X11Profile>) -> Self {
+        self.x11_stand_in = Some(profiles);
+        self
+    }
 
-        let stdout = String::from_utf8(output.stdout)
-            .map_err(|e| ProfileError::ParseError(format!("Invalid UTF-8 output: {}", e)))?;
+    /// Check that the colormgr command is available, probing once per provider
+    fn check_colormgr(&self) -> Result<(), ProfileError> {
+        let available = match self.colormgr_available.get() {
+            Some(&available) => available,
+            None => self.record_colormgr_probe(self.runner.run("colormgr", &["--version"]))?,
+        };
+        colormgr_availability(available)
+    }
+
+    /// Remember whether a `colormgr --version` probe found colormgr
+    fn record_colormgr_probe(
+        &self,
+        probe: io::Result<CommandOutput>,
+    ) -> Result<bool, ProfileError> {
+        // A probe that timed out is not cached, so a recovered colord is noticed
+        let available = match probe {
+            Ok(output) => output.success,
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => return Err(e.into()),
+            Err(_) => false,
+        };
+        Ok(*self.colormgr_available.get_or_init(|| available))
+    }
 
-        Ok(stdout)
+    /// Execute colormgr command and return output
+    fn execute_colormgr(&self, args: &[&str]) -> Result<String, ProfileError> {
+        self.check_colormgr()?;
+        colormgr_stdout(self.runner.run("colormgr", args))
     }
 
     /// Get all devices from colormgr
@@ -100,72 +190,12 @@ impl LinuxProfileProvider {
         self.parse_colormgr_devices(&output)
     }
 
-    /// Parse colormgr get-devices output
+    /// Parse colormgr get-devices output, keeping only displays
     fn parse_colormgr_devices(&self, output: &str) -> Result<Vec<ColormgrDevice>, ProfileError> {
-        let mut devices = Vec::new();
-        let mut current_device: Option<ColormgrDevice> = None;
-
-        for line in output.lines() {
-            let line = line.trim();
-
-            if line.starts_with("Device ID:") {
-                // Save previous device if exists
-                if let Some(device) = current_device.take() {
-                    devices.push(device);
-                }
-
-                // Start new device
-                let id = line
-                    .strip_prefix("Device ID:")
-                    .unwrap_or("")
-                    .trim()
-                    .to_string();
-                current_device = Some(ColormgrDevice {
-                    id,
-                    kind: String::new(),
-                    model: String::new(),
-                    vendor: String::new(),
-                    serial: String::new(),
-                    profiles: Vec::new(),
-                });
-            } else if let Some(ref mut device) = current_device {
-                if line.starts_with("Kind:") {
-                    device.kind = line.strip_prefix("Kind:").unwrap_or("").trim().to_string();
-                } else if line.starts_with("Model:") {
-                    device.model = line.strip_prefix("Model:").unwrap_or("").trim().to_string();
-                } else if line.starts_with("Vendor:") {
-                    device.vendor = line
-                        .strip_prefix("Vendor:")
-                        .unwrap_or("")
-                        .trim()
-                        .to_string();
-                } else if line.starts_with("Serial:") {
-                    device.serial = line
-                        .strip_prefix("Serial:")
-                        .unwrap_or("")
-                        .trim()
-                        .to_string();
-                } else if line.starts_with("Profile ") && line.contains(":") {
-                    // Extract profile ID from "Profile 1: profile_id"
-                    if let Some(profile_id) = line.split(':').nth(1) {
-                        device.profiles.push(profile_id.trim().to_string());
-                    }
-                }
-            }
-        }
-
-        // Don't forget the last device
-        if let Some(device) = current_device {
-            devices.push(device);
-        }
-
-        // Filter to only display devices
-        let display_devices: Vec<ColormgrDevice> = devices
+        Ok(colormgr::parse_devices(output)?
             .into_iter()
-            .filter(|device| device.kind.to_lowercase().contains("display"))
-            .collect();
-
-        Ok(display_devices)
+            .filter(ColormgrDevice::is_display)
+            .collect())
     }
 
     /// Get profile information from colormgr
@@ -180,39 +210,7 @@ impl LinuxProfileProvider {
         output: &str,
         profile_id: &str,
     ) -> Result<ColormgrProfile, ProfileError> {
-        let mut profile = ColormgrProfile {
-            id: profile_id.to_string(),
-            filename: None,
-            title: None,
-            kind: String::new(),
-            colorspace: String::new(),
-        };
-
-        for line in output.lines() {
-            let line = line.trim();
-
-            if line.starts_with("Filename:") {
-                let filename_str = line.strip_prefix("Filename:").unwrap_or("").trim();
-                if !filename_str.is_empty() && filename_str != "(none)" {
-                    profile.filename = Some(PathBuf::from(filename_str));
-                }
-            } else if line.starts_with("Title:") {
-                let title = line.strip_prefix("Title:").unwrap_or("").trim();
-                if !title.is_empty() {
-                    profile.title = Some(title.to_string());
-                }
-            } else if line.starts_with("Kind:") {
-                profile.kind = line.strip_prefix("Kind:").unwrap_or("").trim().to_string();
-            } else if line.starts_with("Colorspace:") {
-                profile.colorspace = line
-                    .strip_prefix("Colorspace:")
-                    .unwrap_or("")
-                    .trim()
-                    .to_string();
-            }
-        }
-
-        Ok(profile)
+        colormgr::parse_profile(output, profile_id)
     }
 
     /// Convert colormgr colorspace to our ColorSpace enum
@@ -229,202 +227,447 @@ impl LinuxProfileProvider {
         std::fs::read(file_path).map_err(|e| ProfileError::IoError(e.to_string()))
     }
 
+    /// The profiles of colord display `display` among `devices` that `read`
+    /// still finds, in colord's relevance order with the first as default,
+    /// else one of the fallbacks if the device has none
+    fn colord_profiles(
+        &self,
+        display: &Display,
+        devices: &[ColormgrDevice],
+        read: impl Fn(&str) -> Result<ColormgrProfile, ProfileError>,
+    ) -> Result<Vec<AssignedProfile>, ProfileError> {
+        let device = devices
+            .iter()
+            .find(|d| d.id == display.id)
+            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))?;
+
+        if device.profiles.is_empty() {
+            if let Some(profile) = self.unassigned_fallback(display) {
+                return Ok(vec![profile.assigned()]);
+            }
+        }
+
+        let mut assigned = Vec::new();
+        for profile_id in &device.profiles {
+            match read(profile_id) {
+                Ok(profile) => {
+                    let is_default = assigned.is_empty();
+                    assigned.push(self.convert_assigned_profile(profile, is_default));
+                }
+                Err(e) if !profile_vanished(&e) => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Ok(assigned)
+    }
+
+    /// The profile of colord display `display` among `devices`: the first
+    /// of its profiles that `read` still finds, else one of the fallbacks if
+    /// the device has none
+    fn colord_profile(
+        &self,
+        display: &Display,
+        devices: &[ColormgrDevice],
+        read: impl Fn(&str) -> Result<ColormgrProfile, ProfileError>,
+    ) -> Result<Lookup, ProfileError> {
+        let device = devices
+            .iter()
+            .find(|d| d.id == display.id)
+            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))?;
+
+        if device.profiles.is_empty() {
+            return self
+                .unassigned_fallback(display)
+                .map(Lookup::Fallback)
+                .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()));
+        }
+
+        for profile_id in &device.profiles {
+            match read(profile_id) {
+                Ok(profile) => return Ok(Lookup::Colord(Box::new(profile))),
+                Err(e) if !profile_vanished(&e) => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Err(ProfileError::ProfileNotAvailable(display.id.clone()))
+    }
+
+    /// Convert a colord profile into an AssignedProfile
+    fn convert_assigned_profile(
+        &self,
+        colormgr_profile: ColormgrProfile,
+        is_default: bool,
+    ) -> AssignedProfile {
+        AssignedProfile {
+            profile: ProfileInfo {
+                name: colormgr_profile
+                    .title
+                    .unwrap_or_else(|| colormgr_profile.id.clone()),
+                description: None,
+                file_path: colormgr_profile.filename,
+                color_space: self.parse_colorspace(&colormgr_profile.colorspace),
+            },
+            id: Some(colormgr_profile.id),
+            is_default,
+            kind: if colormgr_profile.kind.is_empty() {
+                None
+            } else {
+                Some(colormgr_profile.kind)
+            },
+            metadata: colormgr_profile.metadata,
+        }
+    }
+
     /// Check if D-Bus API is available and preferred
     #[cfg(feature = "dbus-support")]
     fn should_use_dbus(&self) -> bool {
-        self.config.linux_prefer_dbus && self.is_dbus_available()
+        self.preferred_colord().is_some()
     }
 
-    #[cfg(not(feature = "dbus-support"))]
+    /// The colord client, if D-Bus is preferred and colord is reachable
+    #[cfg(feature = "dbus-support")]
+    fn preferred_colord(&self) -> Option<&ColordClient> {
+        self.config
+            .linux_prefer_dbus
+            .then(|| self.colord())
+            .flatten()
+    }
+
+    /// Without D-Bus support there is nothing to prefer; only the tests ask
+    #[cfg(all(test, not(feature = "dbus-support")))]
     fn should_use_dbus(&self) -> bool {
         false
     }
 
-    /// Check if D-Bus colord service is available
+    /// The colord client, connecting and probing the service once per provider
     #[cfg(feature = "dbus-support")]
-    fn is_dbus_available(&self) -> bool {
-        match Connection::new_system() {
-            Ok(conn) => {
-                // Try to create a proxy to the colord service
-                let proxy =
-                    conn.with_proxy(COLORD_SERVICE, COLORD_PATH, Duration::from_millis(1000));
+    fn colord(&self) -> Option<&ColordClient> {
+        if let Some(client) = self.colord.get() {
+            return client.as_ref();
+        }
 
-                // Try a simple method call to check if the service is available
-                let result: Result<(Vec<dbus::Path>,), dbus::Error> =
-                    proxy.method_call(COLORD_INTERFACE, "GetDevices", ());
+        let client = ColordClient::system(self.config.timeout)
+            .and_then(|client| client.ping().map(|_| client));
+        match client {
+            Ok(client) => self.colord.get_or_init(|| Some(client)).as_ref(),
+            // As with colormgr, a probe that timed out is not cached
+            Err(ProfileError::Timeout(_)) => None,
+            Err(_) => self.colord.get_or_init(|| None).as_ref(),
+        }
+    }
 
-                result.is_ok()
-            }
-            Err(_) => false,
+    /// A provider with this one's configuration, command runner and sysfs
+    /// root, for a polling watcher or a blocking task to own
+    fn detached(&self) -> Self {
+        #[allow(unused_mut)]
+        let mut provider = Self::with_shared_runner(self.config.clone(), Arc::clone(&self.runner))
+            .with_sysfs_root(self.sysfs_root.clone());
+        #[cfg(feature = "wayland")]
+        {
+            provider.wayland_socket = self.wayland_socket.clone();
         }
+        #[cfg(all(test, feature = "x11"))]
+        {
+            provider.x11_stand_in = self.x11_stand_in.clone();
+        }
+        provider
     }
 
-    /// Get devices using D-Bus API
+    /// Get display devices using D-Bus API
     #[cfg(feature = "dbus-support")]
     fn get_dbus_devices(&self) -> Result<Vec<ColormgrDevice>, ProfileError> {
-        let conn = Connection::new_system()
-            .map_err(|e| ProfileError::SystemError(format!("Failed to connect to D-Bus: {}", e)))?;
+        let client = self.colord().ok_or_else(|| {
+            ProfileError::SystemError("colord is not available on D-Bus".to_string())
+        })?;
 
-        let proxy = conn.with_proxy(COLORD_SERVICE, COLORD_PATH, Duration::from_millis(5000));
+        Ok(client
+            .get_devices()?
+            .into_iter()
+            .filter(ColormgrDevice::is_display)
+            .collect())
+    }
 
-        // Get all devices
-        let (device_paths,): (Vec<dbus::Path>,) = proxy
-            .method_call(COLORD_INTERFACE, "GetDevices", ())
-            .map_err(|e| ProfileError::SystemError(format!("D-Bus GetDevices failed: {}", e)))?;
+    /// Get profile information via D-Bus
+    #[cfg(feature = "dbus-support")]
+    fn get_dbus_profile(&self, profile_id: &str) -> Result<ColormgrProfile, ProfileError> {
+        let client = self.colord().ok_or_else(|| {
+            ProfileError::SystemError("colord is not available on D-Bus".to_string())
+        })?;
 
-        let mut devices = Vec::new();
+        client.find_profile_by_id(profile_id)
+    }
 
-        for device_path in device_paths {
-            if let Ok(device) = self.get_dbus_device_info(&conn, &device_path) {
-                // Filter to display devices only
-                if device.kind.to_lowercase().contains("display") {
-                    devices.push(device);
-                }
-            }
+    /// Register `path` with colord over D-Bus, reusing an existing registration
+    #[cfg(feature = "dbus-support")]
+    fn create_dbus_profile(
+        &self,
+        client: &ColordClient,
+        path: &Path,
+        data: &[u8],
+    ) -> Result<String, ProfileError> {
+        if let Some(profile_id) = client.find_profile_by_filename(path)? {
+            return Ok(profile_id);
         }
 
-        Ok(devices)
+        let profile_id = new_profile_id(path, data);
+        client.create_profile(&profile_id, path)?;
+        Ok(profile_id)
     }
 
-    /// Get device information via D-Bus
-    #[cfg(feature = "dbus-support")]
-    fn get_dbus_device_info(
-        &self,
-        conn: &Connection,
-        device_path: &dbus::Path,
-    ) -> Result<ColormgrDevice, ProfileError> {
-        let proxy = conn.with_proxy(COLORD_SERVICE, device_path, Duration::from_millis(2000));
-
-        // Get device properties
-        let device_id: String = proxy
-            .get("org.freedesktop.ColorManager.Device", "DeviceId")
-            .unwrap_or_default();
-
-        let kind: String = proxy
-            .get("org.freedesktop.ColorManager.Device", "Kind")
-            .unwrap_or_default();
-
-        let model: String = proxy
-            .get("org.freedesktop.ColorManager.Device", "Model")
-            .unwrap_or_default();
-
-        let vendor: String = proxy
-            .get("org.freedesktop.ColorManager.Device", "Vendor")
-            .unwrap_or_default();
-
-        let serial: String = proxy
-            .get("org.freedesktop.ColorManager.Device", "Serial")
-            .unwrap_or_default();
-
-        // Get profiles for this device
-        let (profile_paths,): (Vec<dbus::Path>,) = proxy
-            .method_call("org.freedesktop.ColorManager.Device", "GetProfiles", ())
-            .unwrap_or((Vec::new(),));
-
-        let mut profiles = Vec::new();
-        for profile_path in profile_paths {
-            if let Some(profile_id) = profile_path.as_cstr().to_str().ok() {
-                // Extract profile ID from path
-                if let Some(id) = profile_id.split('/').last() {
-                    profiles.push(id.to_string());
-                }
-            }
+    /// Read the profile ID from colormgr's dump of a single profile
+    fn colormgr_profile_id(&self, output: &str) -> Result<String, ProfileError> {
+        let profile = colormgr::parse_profile(output, "")?;
+        if profile.id.is_empty() {
+            return Err(ProfileError::ParseError(
+                "colormgr did not report a Profile ID".to_string(),
+            ));
+        }
+        Ok(profile.id)
+    }
+
+    /// Profiles set on the X11 root window of `$DISPLAY`, if any
+    #[cfg(feature = "x11")]
+    fn x11_profiles(&self) -> Vec<X11Profile> {
+        #[cfg(test)]
+        if let Some(profiles) = &self.x11_stand_in {
+            return profiles.clone();
         }
+        x11::read_profiles(None, self.config.timeout).unwrap_or_default()
+    }
 
-        Ok(ColormgrDevice {
-            id: device_id,
-            kind,
-            model,
-            vendor,
-            serial,
-            profiles,
+    /// The X11 profile for `display`, matched by ID or RandR output
+    #[cfg(feature = "x11")]
+    fn x11_profile(&self, display: &Display) -> Option<X11Profile> {
+        self.x11_profiles().into_iter().find(|profile| {
+            x11_display_id(profile) == display.id
+                || (profile.output.is_some() && profile.output.as_deref() == display.connector())
         })
     }
 
-    /// Get profile information via D-Bus
-    #[cfg(feature = "dbus-support")]
-    fn get_dbus_profile(&self, profile_id: &str) -> Result<ColormgrProfile, ProfileError> {
-        let conn = Connection::new_system()
-            .map_err(|e| ProfileError::SystemError(format!("Failed to connect to D-Bus: {}", e)))?;
-
-        // Find profile by ID
-        let proxy = conn.with_proxy(COLORD_SERVICE, COLORD_PATH, Duration::from_millis(5000));
-
-        let (profile_paths,): (Vec<dbus::Path>,) = proxy
-            .method_call(COLORD_INTERFACE, "GetProfiles", ())
-            .map_err(|e| ProfileError::SystemError(format!("D-Bus GetProfiles failed: {}", e)))?;
-
-        for profile_path in profile_paths {
-            let profile_proxy =
-                conn.with_proxy(COLORD_SERVICE, &profile_path, Duration::from_millis(2000));
-
-            let path_profile_id: String = profile_proxy
-                .get("org.freedesktop.ColorManager.Profile", "ProfileId")
-                .unwrap_or_default();
-
-            if path_profile_id == profile_id {
-                let filename: String = profile_proxy
-                    .get("org.freedesktop.ColorManager.Profile", "Filename")
-                    .unwrap_or_default();
-
-                let title: String = profile_proxy
-                    .get("org.freedesktop.ColorManager.Profile", "Title")
-                    .unwrap_or_default();
-
-                let kind: String = profile_proxy
-                    .get("org.freedesktop.ColorManager.Profile", "Kind")
-                    .unwrap_or_default();
-
-                let colorspace: String = profile_proxy
-                    .get("org.freedesktop.ColorManager.Profile", "Colorspace")
-                    .unwrap_or_default();
-
-                return Ok(ColormgrProfile {
-                    id: profile_id.to_string(),
-                    filename: if filename.is_empty() {
-                        None
-                    } else {
-                        Some(PathBuf::from(filename))
-                    },
-                    title: if title.is_empty() { None } else { Some(title) },
-                    kind,
-                    colorspace,
+    /// Outputs of the Wayland compositor, if it supports colour management
+    #[cfg(feature = "wayland")]
+    fn wayland_outputs(&self) -> Option<Vec<WaylandOutput>> {
+        wayland::read_outputs(self.wayland_socket.as_deref(), self.config.timeout)
+            .ok()
+            .filter(|outputs| !outputs.is_empty())
+    }
+
+    /// Displays of the Wayland compositor, if it supports colour management
+    #[cfg(feature = "wayland")]
+    fn compositor_displays(&self) -> Option<Vec<Display>> {
+        let outputs = self.wayland_outputs()?;
+        let connectors = self.drm_connectors();
+        Some(
+            wayland_displays(&outputs, self.config.primary_display.as_deref())
+                .into_iter()
+                .map(|display| identify_display(display, None, &connectors))
+                .collect(),
+        )
+    }
+
+    /// The Wayland output shown as `display`
+    #[cfg(feature = "wayland")]
+    fn wayland_output(&self, display: &Display) -> Result<WaylandOutput, ProfileError> {
+        self.wayland_outputs()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .find(|(index, output)| wayland_display_id(output, *index) == display.id)
+            .map(|(_, output)| output)
+            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))
+    }
+
+    /// Connectors with a monitor attached, from sysfs
+    fn drm_connectors(&self) -> Vec<DrmConnector> {
+        drm::read_connectors(&self.sysfs_root)
+    }
+
+    /// The connector shown as DRM display `display`
+    fn drm_connector(&self, display: &Display) -> Result<DrmConnector, ProfileError> {
+        self.drm_connectors()
+            .into_iter()
+            .find(|connector| drm_display_id(connector) == display.id)
+            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))
+    }
+
+    /// The profile for DRM display `display`: the one the mapping file
+    /// assigns, else the `edid-<md5>.icc` in the profile search paths, else
+    /// one on disk made for the monitor, its model or manufacturer, else
+    /// (with fallbacks enabled) one generated from the EDID
+    fn drm_profile(&self, display: &Display) -> Result<DrmProfile, ProfileError> {
+        let connector = self.drm_connector(display)?;
+        let edid_md5 = (!connector.edid.is_empty()).then(|| md5::hex(&connector.edid));
+        let search_paths = self.config.profile_search_paths();
+
+        let map_path = match &self.config.linux_profile_map {
+            Some(path) => Some(path.clone()),
+            None => profile_map::default_path(|name| std::env::var_os(name)),
+        };
+        if let Some(map_path) = map_path {
+            let map = ProfileMap::load(&map_path)?;
+            let keys: Vec<&str> = [Some(display.id.as_str()), edid_md5.as_deref()]
+                .into_iter()
+                .flatten()
+                .chain([connector.name.as_str()])
+                .collect();
+            if let Some(profile) = map.lookup(&keys) {
+                let path =
+                    profile_map::resolve_profile(profile, &search_paths).ok_or_else(|| {
+                        ProfileError::IoError(format!(
+                            "{}: profile {} for {} not found",
+                            map_path.display(),
+                            profile.display(),
+                            display.id
+                        ))
+                    })?;
+                return Ok(DrmProfile::File {
+                    path,
+                    kind: "mapped",
+                });
+            }
+        }
+
+        if let Some(md5) = &edid_md5 {
+            let file_name = format!("edid-{}.icc", md5);
+            if let Some(path) = search_paths
+                .iter()
+                .map(|dir| dir.join(&file_name))
+                .find(|path| path.is_file())
+            {
+                return Ok(DrmProfile::File {
+                    path,
+                    kind: "edid-store",
                 });
             }
         }
 
-        Err(ProfileError::ProfileNotAvailable(format!(
-            "Profile {} not found via D-Bus",
-            profile_id
-        )))
+        if let Some((candidate, reason)) = self.matching_profile(display) {
+            return Ok(DrmProfile::Matched { candidate, reason });
+        }
+
+        if self.config.fallback_enabled {
+            if let Some(profile) = self.edid_profile(display) {
+                return Ok(DrmProfile::Edid(profile));
+            }
+        }
+        Err(ProfileError::ProfileNotAvailable(display.id.clone()))
     }
 
-    /// Fallback to file system scanning when other methods fail
-    fn scan_filesystem_profiles(&self) -> Result<Vec<PathBuf>, ProfileError> {
-        let profile_dirs = [
-            "/usr/share/color/icc",
-            "/usr/local/share/color/icc",
-            "/home/.local/share/icc", // User profiles
-            "/var/lib/color/icc",
-        ];
+    /// The EDID of the monitor shown as `display`, taken from the display
+    /// itself or else from its DRM connector
+    fn monitor_edid(&self, display: &Display) -> Option<Vec<u8>> {
+        match display.edid() {
+            Some(edid) => Some(edid.to_vec()),
+            None => {
+                let connector = display.connector()?;
+                let drm = self
+                    .drm_connectors()
+                    .into_iter()
+                    .find(|drm| drm.name == connector)?;
+                Some(drm.edid)
+            }
+        }
+    }
 
-        let mut profiles = Vec::new();
-
-        for dir in &profile_dirs {
-            if let Ok(entries) = std::fs::read_dir(dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path
-                        .extension()
-                        .map_or(false, |ext| ext == "icc" || ext == "icm")
-                    {
-                        profiles.push(path);
+    /// A profile generated from the EDID of the monitor shown as `display`
+    fn edid_profile(&self, display: &Display) -> Option<EdidProfile> {
+        let edid = self.monitor_edid(display)?;
+        let data = profile_from_edid(&edid).ok()?;
+        Some(EdidProfile { edid, data })
+    }
+
+    /// The display profile on disk made for the monitor shown as `display`,
+    /// its model or its manufacturer, and why it was chosen
+    fn matching_profile(
+        &self,
+        display: &Display,
+    ) -> Option<(fallback::Candidate, fallback::Reason)> {
+        let mut display = display.clone();
+        if display.edid().is_none() {
+            if let Some(edid) = self.monitor_edid(&display) {
+                display = display.with_edid(edid);
+            }
+        }
+        let candidates = fallback::candidates(&self.scan_filesystem_profiles().ok()?);
+        fallback::choose(&candidates, &display)
+            .filter(|(_, reason)| reason.fits_monitor())
+            .map(|(candidate, reason)| (candidate.clone(), reason))
+    }
+
+    /// With fallbacks enabled, a profile and its data for a colord display
+    /// that has none: the X11 root window property still applies to the
+    /// output, failing that a profile on disk made for the monitor is used,
+    /// and failing that one is generated from the monitor's EDID
+    fn unassigned_fallback(&self, display: &Display) -> Option<UnassignedProfile> {
+        if !self.config.fallback_enabled {
+            return None;
+        }
+        #[cfg(feature = "x11")]
+        if let Some(profile) = self.x11_profile(display) {
+            return Some(UnassignedProfile::X11(profile));
+        }
+        if let Some((candidate, reason)) = self.matching_profile(display) {
+            if let Ok(data) = read_icc_file(&candidate.path) {
+                return Some(UnassignedProfile::Matched {
+                    candidate,
+                    reason,
+                    data,
+                });
+            }
+        }
+        self.edid_profile(display).map(UnassignedProfile::Edid)
+    }
+
+    /// The profile of a colord display: the first one colord has for it over
+    /// D-Bus or colormgr, else one of the fallbacks for a display without any
+    fn lookup_profile(&self, display: &Display) -> Result<Lookup, ProfileError> {
+        // Try D-Bus first if preferred and available; only a failed call
+        // falls through to colormgr
+        #[cfg(feature = "dbus-support")]
+        if self.should_use_dbus() {
+            match self.get_dbus_devices() {
+                Ok(devices) => {
+                    match self.colord_profile(display, &devices, |id| self.get_dbus_profile(id)) {
+                        Err(ProfileError::Timeout(_)) if self.config.fallback_enabled => {}
+                        result => return result,
                     }
                 }
+                Err(_) if !self.config.fallback_enabled => {
+                    return Err(ProfileError::SystemError(
+                        "D-Bus method failed and fallback is disabled".to_string(),
+                    ));
+                }
+                Err(_) => {}
             }
         }
 
-        Ok(profiles)
+        // Fallback to colormgr command
+        let colormgr_devices = self.get_colormgr_devices()?;
+        self.colord_profile(display, &colormgr_devices, |id| {
+            self.get_colormgr_profile(id)
+        })
+    }
+
+    /// Fallback to file system scanning when other methods fail
+    fn scan_filesystem_profiles(&self) -> Result<Vec<PathBuf>, ProfileError> {
+        Ok(search_path::find_profiles(
+            &self.config.profile_search_paths(),
+        ))
+    }
+
+    /// The display profile on disk that best fits `display`, and why
+    fn filesystem_profile(
+        &self,
+        display: &Display,
+    ) -> Result<(fallback::Candidate, fallback::Reason), ProfileError> {
+        let candidates = fallback::candidates(&self.scan_filesystem_profiles()?);
+        fallback::choose(&candidates, display)
+            .map(|(candidate, reason)| (candidate.clone(), reason))
+            .ok_or_else(|| {
+                ProfileError::ProfileNotAvailable(
+                    "No RGB display profiles found in the profile directories".to_string(),
+                )
+            })
     }
 
     /// Convert ColormgrDevice list to Display list
@@ -432,24 +675,18 @@ impl LinuxProfileProvider {
         &self,
         colormgr_devices: Vec<ColormgrDevice>,
     ) -> Result<Vec<Display>, ProfileError> {
-        let mut displays = Vec::new();
-        for (index, device) in colormgr_devices.iter().enumerate() {
-            let display_name = if !device.model.is_empty() {
-                if !device.vendor.is_empty() {
-                    format!("{} {}", device.vendor, device.model)
-                } else {
-                    device.model.clone()
-                }
-            } else {
-                format!("Display {}", index + 1)
-            };
-
-            displays.push(Display {
-                id: device.id.clone(),
-                name: display_name,
-                is_primary: index == 0, // First display is considered primary for now
-            });
-        }
+        let connectors = self.drm_connectors();
+        let outputs = self.randr_outputs();
+        let mut displays: Vec<Display> = colormgr_devices
+            .iter()
+            .enumerate()
+            .map(|(index, device)| {
+                let edid_md5 = device.metadata.get("OutputEdidMd5").map(String::as_str);
+                let display =
+                    identify_display(device_to_display(device, index), edid_md5, &connectors);
+                with_randr_layout(display, &outputs)
+            })
+            .collect();
 
         if displays.is_empty() {
             return Err(ProfileError::SystemError(
@@ -457,148 +694,997 @@ impl LinuxProfileProvider {
             ));
         }
 
+        let (index, source) = self.find_primary(&colormgr_devices, &displays, &outputs);
+        let primary = displays.remove(index).with_primary_source(source);
+        displays.insert(index, primary);
+
         Ok(displays)
     }
-}
 
-impl DisplayProfileProvider for LinuxProfileProvider {
-    fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
-        // Try D-Bus first if preferred and available
-        #[cfg(feature = "dbus-support")]
-        if self.should_use_dbus() {
-            if let Ok(devices) = self.get_dbus_devices() {
-                return self.convert_devices_to_displays(devices);
+    /// Displays found without colord: X11 root window profiles, DRM
+    /// connectors, or as a last resort a generic display for profiles on disk
+    fn fallback_displays(&self) -> Result<Vec<Display>, ProfileError> {
+        // Without colord, the X11 root window properties are what applications use
+        #[cfg(feature = "x11")]
+        {
+            let displays = x11_displays(&self.x11_profiles());
+            if !displays.is_empty() {
+                let connectors = self.drm_connectors();
+                let outputs = self.randr_outputs();
+                return Ok(displays
+                    .into_iter()
+                    .map(|display| {
+                        with_randr_layout(identify_display(display, None, &connectors), &outputs)
+                    })
+                    .collect());
             }
+        }
 
-            if !self.config.fallback_enabled {
-                return Err(ProfileError::SystemError(
-                    "D-Bus method failed and fallback is disabled".to_string(),
-                ));
+        // Without any display server support, the kernel still knows the monitors
+        let displays = drm_displays(
+            &self.drm_connectors(),
+            self.config.primary_display.as_deref(),
+        );
+        if !displays.is_empty() {
+            return Ok(displays);
+        }
+
+        // Final fallback: return a generic display if we can find any display profiles
+        let generic = Display::new(
+            "filesystem-fallback".to_string(),
+            "Generic Display".to_string(),
+            false,
+        )
+        .with_primary_source(PrimarySource::FirstDisplay);
+        match self.filesystem_profile(&generic) {
+            Ok(_) => Ok(vec![generic]),
+            Err(_) => Err(ProfileError::SystemError(
+                "No display devices found via any method".to_string(),
+            )),
+        }
+    }
+
+    /// Decide which display is primary, trying each source in order:
+    /// the configured override, colord metadata, the RandR primary output,
+    /// and finally the first display
+    fn find_primary(
+        &self,
+        colormgr_devices: &[ColormgrDevice],
+        displays: &[Display],
+        outputs: &[RandrOutput],
+    ) -> (usize, PrimarySource) {
+        if let Some(wanted) = &self.config.primary_display {
+            if let Some(index) = displays
+                .iter()
+                .position(|d| &d.id == wanted || d.connector() == Some(wanted.as_str()))
+            {
+                return (index, PrimarySource::Config);
             }
         }
 
-        // Fallback to colormgr command
-        match self.get_colormgr_devices() {
-            Ok(devices) => self.convert_devices_to_displays(devices),
-            Err(e) => {
-                if !self.config.fallback_enabled {
-                    return Err(e);
-                }
+        if let Some(index) = colormgr_devices.iter().position(is_colord_primary) {
+            return (index, PrimarySource::Colord);
+        }
 
-                // Final fallback: return a generic display if we can find any profiles
-                match self.scan_filesystem_profiles() {
-                    Ok(profiles) if !profiles.is_empty() => Ok(vec![Display {
-                        id: "filesystem-fallback".to_string(),
-                        name: "Generic Display".to_string(),
-                        is_primary: true,
-                    }]),
-                    _ => Err(ProfileError::SystemError(
-                        "No display devices found via any method".to_string(),
-                    )),
-                }
+        if let Some(output) = outputs.iter().find(|output| output.primary) {
+            if let Some(index) = displays
+                .iter()
+                .position(|d| d.connector() == Some(output.name.as_str()))
+            {
+                return (index, PrimarySource::RandR);
             }
         }
-    }
 
-    fn get_primary_display(&self) -> Result<Display, ProfileError> {
-        let displays = self.get_displays()?;
-        displays
-            .into_iter()
-            .find(|d| d.is_primary)
-            .ok_or_else(|| ProfileError::DisplayNotFound("No primary display found".to_string()))
+        (0, PrimarySource::FirstDisplay)
     }
 
-    fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
-        // Handle filesystem fallback case
-        if display.id == "filesystem-fallback" {
-            let profiles = self.scan_filesystem_profiles()?;
-            if let Some(profile_path) = profiles.first() {
-                return Ok(ProfileInfo {
-                    name: profile_path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("Unknown Profile")
-                        .to_string(),
-                    description: None,
-                    file_path: Some(profile_path.clone()),
-                    color_space: ColorSpace::Unknown,
-                });
-            }
+    /// The connected RandR outputs, on X11 sessions only: asked of the X
+    /// server with the `x11` feature, else listed by `xrandr --query`
+    fn randr_outputs(&self) -> Vec<RandrOutput> {
+        if std::env::var_os("DISPLAY").is_none() {
+            return Vec::new();
         }
 
-        // Try D-Bus first if preferred and available
-        #[cfg(feature = "dbus-support")]
-        if self.should_use_dbus() {
-            if let Ok(devices) = self.get_dbus_devices() {
-                if let Some(device) = devices.iter().find(|d| d.id == display.id) {
-                    if let Some(profile_id) = device.profiles.first() {
-                        if let Ok(profile) = self.get_dbus_profile(profile_id) {
-                            let profile_name = profile.title.unwrap_or_else(|| profile.id.clone());
-
-                            return Ok(ProfileInfo {
-                                name: profile_name,
-                                description: None,
-                                file_path: profile.filename,
-                                color_space: self.parse_colorspace(&profile.colorspace),
-                            });
-                        }
-                    }
+        #[cfg(feature = "x11")]
+        {
+            x11::read_outputs(None, self.config.timeout).unwrap_or_default()
+        }
+        #[cfg(not(feature = "x11"))]
+        {
+            match self.runner.run("xrandr", &["--query"]) {
+                Ok(output) if output.success => {
+                    parse_xrandr_outputs(&String::from_utf8_lossy(&output.stdout))
                 }
+                _ => Vec::new(),
             }
+        }
+    }
+}
 
-            if !self.config.fallback_enabled {
-                return Err(ProfileError::SystemError(
-                    "D-Bus method failed and fallback is disabled".to_string(),
-                ));
+/// A connected RandR output, from the X server or `xrandr --query`
+#[derive(Debug, Clone, PartialEq)]
+struct RandrOutput {
+    name: String,
+    primary: bool,
+    /// Size and position as `(width, height, x, y)`, if the output is enabled
+    geometry: Option<(u32, u32, i32, i32)>,
+    /// Refresh rate of the current mode, the one marked `*`
+    refresh_rate: Option<f64>,
+}
+
+/// Parse the connected outputs from `xrandr --query` output
+#[cfg_attr(feature = "x11", allow(dead_code))]
+fn parse_xrandr_outputs(output: &str) -> Vec<RandrOutput> {
+    let mut outputs: Vec<RandrOutput> = Vec::new();
+    // Whether the mode lines that follow belong to the last output pushed
+    let mut in_connected = false;
+
+    for line in output.lines() {
+        // Mode lines are indented under their output, e.g. "   2560x1440  59.95*+  143.91"
+        if line.starts_with(char::is_whitespace) {
+            let Some(current) = outputs.last_mut().filter(|_| in_connected) else {
+                continue;
+            };
+            if current.refresh_rate.is_none() {
+                current.refresh_rate = line
+                    .split_whitespace()
+                    .skip(1)
+                    .find(|rate| rate.contains('*'))
+                    .and_then(|rate| rate.trim_end_matches(['*', '+']).parse().ok());
             }
+            continue;
         }
 
-        // Fallback to colormgr command
-        let colormgr_devices = self.get_colormgr_devices()?;
+        // Output lines look like "DP-1 connected primary 2560x1440+0+0 ..."
+        let mut fields = line.split_whitespace().peekable();
+        let (Some(name), Some("connected")) = (fields.next(), fields.next()) else {
+            in_connected = false;
+            continue;
+        };
+        let primary = fields.next_if_eq(&"primary").is_some();
+        let geometry = fields.next().and_then(parse_xrandr_geometry);
+        outputs.push(RandrOutput {
+            name: name.to_string(),
+            primary,
+            geometry,
+            refresh_rate: None,
+        });
+        in_connected = true;
+    }
+
+    outputs
+}
+
+/// Parse an xrandr geometry such as `2560x1440+0+0` or `1920x1080+-1920+0`
+#[cfg_attr(feature = "x11", allow(dead_code))]
+fn parse_xrandr_geometry(geometry: &str) -> Option<(u32, u32, i32, i32)> {
+    let (width, rest) = geometry.split_once('x')?;
+    let mut parts = rest.splitn(3, '+');
+    let height = parts.next()?.parse().ok()?;
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    Some((width.parse().ok()?, height, x, y))
+}
+
+/// Fill in the geometry and refresh rate RandR reports for the display's output
+fn with_randr_layout(mut display: Display, outputs: &[RandrOutput]) -> Display {
+    let Some(output) = display
+        .connector()
+        .and_then(|connector| outputs.iter().find(|output| output.name == connector))
+    else {
+        return display;
+    };
+
+    if let Some((width, height, x, y)) = output.geometry {
+        display = display.with_resolution(width, height).with_position(x, y);
+    }
+    if let Some(refresh_rate) = output.refresh_rate {
+        display = display.with_refresh_rate(refresh_rate);
+    }
+    display
+}
+
+/// `Ok` if colormgr is available, else the error to report
+fn colormgr_availability(available: bool) -> Result<(), ProfileError> {
+    if available {
+        Ok(())
+    } else {
+        Err(ProfileError::SystemError(
+            "colormgr command not found. Please install colord package.".to_string(),
+        ))
+    }
+}
+
+/// The output of a colormgr run, or why it failed
+fn colormgr_stdout(result: io::Result<CommandOutput>) -> Result<String, ProfileError> {
+    let output = result.map_err(|e| match e.kind() {
+        io::ErrorKind::TimedOut => e.into(),
+        _ => ProfileError::SystemError(format!("Failed to execute colormgr: {}", e)),
+    })?;
+
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ProfileError::SystemError(format!(
+            "colormgr command failed: {}",
+            stderr
+        )));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|e| ProfileError::ParseError(format!("Invalid UTF-8 output: {}", e)))
+}
+
+/// Whether reading a profile colord lists failed because the profile was
+/// removed since the device was read, rather than because colord did not
+/// answer; as with devices, such a profile is skipped
+fn profile_vanished(error: &ProfileError) -> bool {
+    !matches!(error, ProfileError::Timeout(_))
+}
+
+/// Convert a colord display device; `index` numbers displays without a model name
+fn device_to_display(device: &ColormgrDevice, index: usize) -> Display {
+    let display_name = if !device.model.is_empty() {
+        if !device.vendor.is_empty() {
+            format!("{} {}", device.vendor, device.model)
+        } else {
+            device.model.clone()
+        }
+    } else {
+        format!("Display {}", index + 1)
+    };
+
+    let mut display =
+        Display::new(device.id.clone(), display_name, false).with_builtin(device.embedded);
+    if !device.vendor.is_empty() {
+        display = display.with_vendor(device.vendor.clone());
+    }
+    if !device.model.is_empty() {
+        display = display.with_model(device.model.clone());
+    }
+    if !device.serial.is_empty() {
+        display = display.with_serial(device.serial.clone());
+    }
+    // colord's xrandr and GNOME device plugins record the output name
+    if let Some(connector) = device.metadata.get("XRANDR_name") {
+        display = display.with_connector(connector.clone());
+    }
+    display
+}
+
+/// Where the profile of a DRM display comes from
+enum DrmProfile {
+    /// A profile file, assigned in the mapping file (`mapped`) or found by
+    /// its EDID checksum (`edid-store`)
+    File { path: PathBuf, kind: &'static str },
+    /// A profile on disk made for the monitor, its model or manufacturer
+    Matched {
+        candidate: fallback::Candidate,
+        reason: fallback::Reason,
+    },
+    /// Generated from the EDID
+    Edid(EdidProfile),
+}
+
+impl DrmProfile {
+    fn info(&self) -> Result<ProfileInfo, ProfileError> {
+        match self {
+            Self::File { path, .. } => Ok(icc::profile_info(&read_icc_file(path)?, Some(path))),
+            Self::Matched { candidate, reason } => Ok(candidate.clone().info(*reason)),
+            Self::Edid(profile) => Ok(profile.info()),
+        }
+    }
+
+    fn data(self) -> Result<Vec<u8>, ProfileError> {
+        match self {
+            Self::File { path, .. } => read_icc_file(&path),
+            Self::Matched { candidate, .. } => read_icc_file(&candidate.path),
+            Self::Edid(profile) => Ok(profile.data),
+        }
+    }
+
+    fn assigned(&self) -> Result<AssignedProfile, ProfileError> {
+        match self {
+            Self::File { kind, .. } => {
+                let mut profile = AssignedProfile::new(self.info()?, true);
+                profile.kind = Some(kind.to_string());
+                Ok(profile)
+            }
+            Self::Matched { candidate, reason } => Ok(candidate.clone().assigned(*reason)),
+            Self::Edid(profile) => Ok(profile.assigned()),
+        }
+    }
+}
+
+/// A profile generated from a monitor's EDID, offered when colord has none
+struct EdidProfile {
+    edid: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl EdidProfile {
+    /// Describe the profile, flagged as EDID-derived
+    fn info(&self) -> ProfileInfo {
+        ProfileInfo {
+            description: Some("EDID-derived profile, not measured".to_string()),
+            ..icc::profile_info(&self.data, None)
+        }
+    }
+
+    /// The profile as the display's only assigned profile, with the metadata
+    /// colord gives the EDID profiles it generates itself
+    fn assigned(&self) -> AssignedProfile {
+        let mut profile = AssignedProfile::new(self.info(), true);
+        profile.kind = Some("edid-derived".to_string());
+        profile
+            .metadata
+            .insert("DATA_source".to_string(), "edid".to_string());
+        profile
+            .metadata
+            .insert("EDID_md5".to_string(), md5::hex(&self.edid));
+        profile
+    }
+}
+
+/// Where the profile of a colord display comes from
+enum Lookup {
+    /// A profile colord has for the display
+    Colord(Box<ColormgrProfile>),
+    /// One of the fallbacks for a display colord has no profile for
+    Fallback(UnassignedProfile),
+}
+
+/// A profile for a colord display that has none, found by the fallbacks
+enum UnassignedProfile {
+    /// Set on the X11 root window for the display's output
+    #[cfg(feature = "x11")]
+    X11(X11Profile),
+    /// On disk, made for the monitor, its model or manufacturer
+    Matched {
+        candidate: fallback::Candidate,
+        reason: fallback::Reason,
+        data: Vec<u8>,
+    },
+    /// Generated from the monitor's EDID
+    Edid(EdidProfile),
+}
+
+impl UnassignedProfile {
+    fn info(&self) -> ProfileInfo {
+        match self {
+            #[cfg(feature = "x11")]
+            Self::X11(profile) => profile.info(),
+            Self::Matched {
+                candidate, reason, ..
+            } => candidate.clone().info(*reason),
+            Self::Edid(profile) => profile.info(),
+        }
+    }
+
+    fn data(self) -> Vec<u8> {
+        match self {
+            #[cfg(feature = "x11")]
+            Self::X11(profile) => profile.data,
+            Self::Matched { data, .. } => data,
+            Self::Edid(profile) => profile.data,
+        }
+    }
+
+    fn assigned(&self) -> AssignedProfile {
+        match self {
+            #[cfg(feature = "x11")]
+            Self::X11(profile) => AssignedProfile::new(profile.info(), true),
+            Self::Matched {
+                candidate, reason, ..
+            } => candidate.clone().assigned(*reason),
+            Self::Edid(profile) => profile.assigned(),
+        }
+    }
+}
 
-        // Find the device matching this display
-        let device = colormgr_devices
+/// Fill in what the EDID of the display's DRM connector tells about it.
+///
+/// The connector is the one whose EDID has colord's `edid_md5` checksum,
+/// else the one with the display's connector name, else the only one whose
+/// monitor name and serial match the display's model and serial.
+fn identify_display(
+    mut display: Display,
+    edid_md5: Option<&str>,
+    connectors: &[DrmConnector],
+) -> Display {
+    // Only connectors with a valid EDID have anything to tell
+    let connectors: Vec<(&DrmConnector, &Edid)> = connectors
+        .iter()
+        .filter_map(|connector| Some((connector, connector.info.as_ref()?)))
+        .collect();
+    let by_md5 = || {
+        let md5 = edid_md5?;
+        connectors
             .iter()
-            .find(|d| d.id == display.id)
-            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))?;
+            .find(|(connector, _)| md5::hex(&connector.edid).eq_ignore_ascii_case(md5))
+    };
+    let by_name = || {
+        let name = display.connector()?;
+        connectors
+            .iter()
+            .find(|(connector, _)| connector.name == name)
+    };
+    let by_identity = || {
+        let model = display.model()?;
+        let mut matches = connectors.iter().filter(|(_, info)| {
+            info.name.as_deref() == Some(model)
+                && (display.serial().is_none() || display.serial() == edid_serial(info).as_deref())
+        });
+        match (matches.next(), matches.next()) {
+            (Some(connector), None) => Some(connector),
+            _ => None,
+        }
+    };
+    let Some(&(connector, info)) = by_md5().or_else(by_name).or_else(by_identity) else {
+        return display;
+    };
+
+    if display.model().is_none() {
+        if let Some(name) = &info.name {
+            // Without a model the display only had a generic name
+            display.name = name.clone();
+            display = display.with_model(name.clone());
+        }
+    }
+    if display.vendor().is_none() {
+        display = display.with_vendor(info.manufacturer.clone());
+    }
+    if display.serial().is_none() {
+        if let Some(serial) = edid_serial(info) {
+            display = display.with_serial(serial);
+        }
+    }
+    if display.connector().is_none() {
+        display = display.with_connector(connector.name.clone());
+    }
+    if display.is_builtin().is_none() {
+        display = display.with_builtin(connector.is_builtin());
+    }
+    display.with_edid(connector.edid.clone())
+}
+
+/// The monitor's serial as colord reports it: the serial descriptor, else the
+/// numeric serial in hex
+fn edid_serial(info: &Edid) -> Option<String> {
+    info.serial
+        .clone()
+        .or_else(|| info.serial_number.map(|serial| format!("0x{:08x}", serial)))
+}
+
+/// ID of the display shown on a DRM connector: the connector name and, when
+/// the monitor sent an EDID, the start of its MD5, so that a different
+/// monitor on the same connector is a different display. Without an EDID the
+/// card is named too, as two cards can have connectors of the same name.
+fn drm_display_id(connector: &DrmConnector) -> String {
+    if connector.edid.is_empty() {
+        format!("{}card{}-{}", DRM_ID_PREFIX, connector.card, connector.name)
+    } else {
+        format!(
+            "{}{}-{}",
+            DRM_ID_PREFIX,
+            connector.name,
+            &md5::hex(&connector.edid)[..8]
+        )
+    }
+}
+
+/// Displays for the connected DRM connectors, primary being the configured
+/// display or else the first enabled one
+fn drm_displays(connectors: &[DrmConnector], primary_display: Option<&str>) -> Vec<Display> {
+    let mut displays: Vec<Display> = connectors
+        .iter()
+        .map(|connector| {
+            let mut display = Display::new(drm_display_id(connector), &connector.name, false)
+                .with_connector(connector.name.clone())
+                .with_builtin(connector.is_builtin());
+            // sysfs lists the preferred mode first, but not the current one
+            if let Some(&(width, height)) = connector.modes.first() {
+                display = display.with_resolution(width, height);
+            }
+            identify_display(display, None, connectors)
+        })
+        .collect();
+
+    let configured = primary_display.and_then(|wanted| {
+        displays
+            .iter()
+            .position(|d| d.id == wanted || d.connector() == Some(wanted))
+    });
+    let (index, source) = match configured {
+        Some(index) => (index, PrimarySource::Config),
+        None => (
+            connectors
+                .iter()
+                .position(|connector| connector.enabled)
+                .unwrap_or(0),
+            PrimarySource::FirstDisplay,
+        ),
+    };
+    if index < displays.len() {
+        let primary = displays.remove(index).with_primary_source(source);
+        displays.insert(index, primary);
+    }
+    displays
+}
 
-        // Get the first profile for this device
-        let profile_id = device
-            .profiles
-            .first()
-            .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()))?;
+/// ID of the display an X11 profile belongs to
+#[cfg(feature = "x11")]
+fn x11_display_id(profile: &X11Profile) -> String {
+    format!(
+        "{}{}",
+        X11_ID_PREFIX,
+        profile.output.as_deref().unwrap_or(&profile.atom)
+    )
+}
 
-        let colormgr_profile = self.get_colormgr_profile(profile_id)?;
+/// Displays for the X11 profiles, primary being the RandR primary or else the first
+#[cfg(feature = "x11")]
+fn x11_displays(profiles: &[X11Profile]) -> Vec<Display> {
+    let primary = profiles.iter().position(|profile| profile.primary);
+
+    profiles
+        .iter()
+        .enumerate()
+        .map(|(index, profile)| {
+            let name = profile
+                .output
+                .clone()
+                .unwrap_or_else(|| format!("Display {}", index + 1));
+            let mut display = Display::new(x11_display_id(profile), name, false);
+            if let Some(output) = &profile.output {
+                display = display.with_connector(output.clone());
+            }
+            match primary {
+                Some(primary) if primary == index => {
+                    display.with_primary_source(PrimarySource::RandR)
+                }
+                None if index == 0 => display.with_primary_source(PrimarySource::FirstDisplay),
+                _ => display,
+            }
+        })
+        .collect()
+}
 
-        let profile_name = colormgr_profile
-            .title
-            .unwrap_or_else(|| colormgr_profile.id.clone());
+/// ID of the display a Wayland output is shown as; `index` numbers outputs
+/// without a connector name
+#[cfg(feature = "wayland")]
+fn wayland_display_id(output: &WaylandOutput, index: usize) -> String {
+    match &output.name {
+        Some(name) => format!("{}{}", WAYLAND_ID_PREFIX, name),
+        None => format!("{}{}", WAYLAND_ID_PREFIX, index + 1),
+    }
+}
 
-        Ok(ProfileInfo {
-            name: profile_name,
-            description: None, // colormgr doesn't provide description
-            file_path: colormgr_profile.filename,
-            color_space: self.parse_colorspace(&colormgr_profile.colorspace),
+/// Displays for the Wayland outputs, primary being the configured display or else the first
+#[cfg(feature = "wayland")]
+fn wayland_displays(outputs: &[WaylandOutput], primary_display: Option<&str>) -> Vec<Display> {
+    let mut displays: Vec<Display> = outputs
+        .iter()
+        .enumerate()
+        .map(|(index, output)| {
+            let name = match (&output.make, &output.model) {
+                (Some(make), Some(model)) => format!("{} {}", make, model),
+                (None, Some(model)) => model.clone(),
+                _ => output
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("Display {}", index + 1)),
+            };
+            let mut display = Display::new(wayland_display_id(output, index), name, false);
+            if let Some(make) = &output.make {
+                display = display.with_vendor(make.clone());
+            }
+            if let Some(model) = &output.model {
+                display = display.with_model(model.clone());
+            }
+            if let Some(connector) = &output.name {
+                display = display.with_connector(connector.clone());
+            }
+            if let Some((width, height)) = output.resolution {
+                display = display.with_resolution(width, height);
+            }
+            if let Some((x, y)) = output.position {
+                display = display.with_position(x, y);
+            }
+            if let Some(scale) = output.scale {
+                display = display.with_scale(f64::from(scale));
+            }
+            if let Some(refresh_rate) = output.refresh_rate {
+                display = display.with_refresh_rate(refresh_rate);
+            }
+            display
         })
+        .collect();
+
+    // Wayland has no primary output, so only the configuration can name one
+    let configured = primary_display.and_then(|wanted| {
+        displays
+            .iter()
+            .position(|d| d.id == wanted || d.connector() == Some(wanted))
+    });
+    let (index, source) = match configured {
+        Some(index) => (index, PrimarySource::Config),
+        None => (0, PrimarySource::FirstDisplay),
+    };
+    if index < displays.len() {
+        let primary = displays.remove(index).with_primary_source(source);
+        displays.insert(index, primary);
+    }
+    displays
+}
+
+/// Read an ICC profile, checking for the `acsp` signature in its header
+fn read_icc_file(path: &Path) -> Result<Vec<u8>, ProfileError> {
+    let data = std::fs::read(path)
+        .map_err(|e| ProfileError::IoError(format!("{}: {}", path.display(), e)))?;
+    if data.get(36..40) != Some(b"acsp".as_slice()) {
+        return Err(ProfileError::ParseError(format!(
+            "{} is not an ICC profile",
+            path.display()
+        )));
+    }
+    Ok(data)
+}
+
+/// An ID for a profile colord does not know yet, in colord's `icc-<hex>` style.
+///
+/// Uses the MD5 profile ID from the ICC header when the profile has one, and
+/// otherwise the file name.
+fn new_profile_id(path: &Path, data: &[u8]) -> String {
+    match data.get(84..100) {
+        Some(digest) if digest.iter().any(|&b| b != 0) => {
+            let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("icc-{}", hex)
+        }
+        _ => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let name: String = stem
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            format!("icc-{}", name)
+        }
+    }
+}
+
+/// Copy `path` into the profile store `dir`, returning the copy's path.
+///
+/// A profile already in the store is left where it is.
+#[cfg_attr(not(feature = "dbus-support"), allow(dead_code))]
+fn copy_into_store(path: &Path, dir: &Path) -> Result<PathBuf, ProfileError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| ProfileError::IoError(format!("{} is not a file", path.display())))?;
+    let target = dir.join(file_name);
+
+    let same_file = match (path.canonicalize(), target.canonicalize()) {
+        (Ok(source), Ok(target)) => source == target,
+        _ => false,
+    };
+    if !same_file {
+        std::fs::create_dir_all(dir)?;
+        std::fs::copy(path, &target)?;
+    }
+    Ok(target)
+}
+
+/// `path` as a command-line argument for colormgr
+fn path_arg(path: &Path) -> Result<&str, ProfileError> {
+    path.to_str()
+        .ok_or_else(|| ProfileError::IoError(format!("{} is not valid UTF-8", path.display())))
+}
+
+/// Whether colord metadata marks `device` as the primary output
+fn is_colord_primary(device: &ColormgrDevice) -> bool {
+    // gnome-settings-daemon and KDE's colord-kde record the primary output here
+    device
+        .metadata
+        .get("OutputPriority")
+        .is_some_and(|priority| priority == "primary")
+}
+
+impl DisplayProfileProvider for LinuxProfileProvider {
+    fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
+        // On Wayland the compositor, not colord, decides how outputs are colour managed
+        #[cfg(feature = "wayland")]
+        if let Some(displays) = self.compositor_displays() {
+            return Ok(displays);
+        }
+
+        // Try D-Bus first if preferred and available
+        #[cfg(feature = "dbus-support")]
+        if self.should_use_dbus() {
+            match self.get_dbus_devices() {
+                Ok(devices) => return self.convert_devices_to_displays(devices),
+                Err(e @ ProfileError::Timeout(_)) if !self.config.fallback_enabled => {
+                    return Err(e)
+                }
+                Err(_) => {}
+            }
+
+            if !self.config.fallback_enabled {
+                return Err(ProfileError::SystemError(
+                    "D-Bus method failed and fallback is disabled".to_string(),
+                ));
+            }
+        }
+
+        // Fallback to colormgr command
+        match self.get_colormgr_devices() {
+            Ok(devices) => self.convert_devices_to_displays(devices),
+            Err(e) if !self.config.fallback_enabled => Err(e),
+            Err(_) => self.fallback_displays(),
+        }
+    }
+
+    fn get_primary_display(&self) -> Result<Display, ProfileError> {
+        let displays = self.get_displays()?;
+        displays
+            .into_iter()
+            .find(|d| d.is_primary)
+            .ok_or_else(|| ProfileError::DisplayNotFound("No primary display found".to_string()))
+    }
+
+    fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
+        // Handle filesystem fallback case
+        if display.id == "filesystem-fallback" {
+            let (candidate, reason) = self.filesystem_profile(display)?;
+            return Ok(candidate.info(reason));
+        }
+
+        if display.id.starts_with(DRM_ID_PREFIX) {
+            return self.drm_profile(display)?.info();
+        }
+
+        #[cfg(feature = "wayland")]
+        if display.id.starts_with(WAYLAND_ID_PREFIX) {
+            let output = self.wayland_output(display)?;
+            return output
+                .image
+                .map(|image| image.info(&display.name))
+                .map_err(ProfileError::ProfileNotAvailable);
+        }
+
+        #[cfg(feature = "x11")]
+        if display.id.starts_with(X11_ID_PREFIX) {
+            return self
+                .x11_profile(display)
+                .map(|profile| profile.info())
+                .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()));
+        }
+
+        match self.lookup_profile(display)? {
+            Lookup::Colord(profile) => Ok(self.convert_assigned_profile(*profile, true).profile),
+            Lookup::Fallback(profile) => Ok(profile.info()),
+        }
     }
 
     fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
-        let profile_info = self.get_profile(display)?;
+        if display.id.starts_with(DRM_ID_PREFIX) {
+            return self.drm_profile(display)?.data();
+        }
 
-        let file_path = profile_info.file_path.ok_or_else(|| {
-            ProfileError::ProfileNotAvailable(format!(
-                "No file path available for display {}",
-                display.id
-            ))
-        })?;
+        // Only descriptions created from an ICC profile have profile data
+        #[cfg(feature = "wayland")]
+        if display.id.starts_with(WAYLAND_ID_PREFIX) {
+            let image = self
+                .wayland_output(display)?
+                .image
+                .map_err(ProfileError::ProfileNotAvailable)?;
+            return image.icc.ok_or_else(|| {
+                ProfileError::ProfileNotAvailable(format!(
+                    "Display {} has a parametric image description",
+                    display.id
+                ))
+            });
+        }
+
+        // X11 profiles live in the root window property, not in a file
+        #[cfg(feature = "x11")]
+        if display.id.starts_with(X11_ID_PREFIX) {
+            return self
+                .x11_profile(display)
+                .map(|profile| profile.data)
+                .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()));
+        }
+
+        if display.id == "filesystem-fallback" {
+            let (candidate, _) = self.filesystem_profile(display)?;
+            return self.load_profile_data(&candidate.path);
+        }
+
+        match self.lookup_profile(display)? {
+            Lookup::Colord(profile) => match profile.filename {
+                Some(path) => self.load_profile_data(&path),
+                None => Err(ProfileError::ProfileNotAvailable(format!(
+                    "No file path available for display {}",
+                    display.id
+                ))),
+            },
+            // Nor do the fallbacks for colord displays without a profile
+            Lookup::Fallback(profile) => Ok(profile.data()),
+        }
+    }
+
+    fn get_profiles(&self, display: &Display) -> Result<Vec<AssignedProfile>, ProfileError> {
+        // The filesystem fallback only ever has the one profile get_profile picks
+        if display.id == "filesystem-fallback" {
+            let (candidate, reason) = self.filesystem_profile(display)?;
+            return Ok(vec![candidate.assigned(reason)]);
+        }
+
+        // Without colord, a DRM display has at most the one profile
+        if display.id.starts_with(DRM_ID_PREFIX) {
+            return match self.drm_profile(display) {
+                Ok(profile) => Ok(vec![profile.assigned()?]),
+                Err(ProfileError::ProfileNotAvailable(_)) => Ok(Vec::new()),
+                Err(e) => Err(e),
+            };
+        }
+
+        // A Wayland output has a single image description, its parameters kept as metadata
+        #[cfg(feature = "wayland")]
+        if display.id.starts_with(WAYLAND_ID_PREFIX) {
+            let image = self
+                .wayland_output(display)?
+                .image
+                .map_err(ProfileError::ProfileNotAvailable)?;
+            let mut profile = AssignedProfile::new(image.info(&display.name), true);
+            profile.kind = Some(
+                if image.icc.is_some() {
+                    "icc"
+                } else {
+                    "parametric"
+                }
+                .to_string(),
+            );
+            profile.metadata = image.metadata();
+            return Ok(vec![profile]);
+        }
+
+        // As does each X11 root window property
+        #[cfg(feature = "x11")]
+        if display.id.starts_with(X11_ID_PREFIX) {
+            return Ok(vec![AssignedProfile::new(self.get_profile(display)?, true)]);
+        }
+
+        // Try D-Bus first if preferred and available; only a failed call
+        // falls through to colormgr
+        #[cfg(feature = "dbus-support")]
+        if self.should_use_dbus() {
+            match self.get_dbus_devices() {
+                Ok(devices) => {
+                    match self.colord_profiles(display, &devices, |id| self.get_dbus_profile(id)) {
+                        Err(ProfileError::Timeout(_)) if self.config.fallback_enabled => {}
+                        result => return result,
+                    }
+                }
+                Err(_) if !self.config.fallback_enabled => {
+                    return Err(ProfileError::SystemError(
+                        "D-Bus method failed and fallback is disabled".to_string(),
+                    ));
+                }
+                Err(_) => {}
+            }
+        }
+
+        // Fallback to colormgr command
+        let colormgr_devices = self.get_colormgr_devices()?;
+        self.colord_profiles(display, &colormgr_devices, |id| {
+            self.get_colormgr_profile(id)
+        })
+    }
+
+    /// With `dbus-support`, listens to colord's signals over a dedicated
+    /// connection to the system bus, which the returned watcher keeps until it
+    /// is dropped. Events then report a display as primary only from the
+    /// configured override or colord metadata, as RandR is not consulted, and
+    /// `PrimaryChanged` follows colord's `OutputPriority` metadata.
+    ///
+    /// Without colord, or with D-Bus not preferred, polls a provider sharing
+    /// this one's configuration.
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        #[cfg(feature = "dbus-support")]
+        if self.preferred_colord().is_some() {
+            // The watcher closes its connection when dropped, so it gets its own
+            let watcher = ColordClient::system(self.config.timeout)
+                .and_then(|client| watch::watch(client, &self.config));
+            if let Ok(watcher) = watcher {
+                return Ok(watcher);
+            }
+        }
+        ProfileWatcher::polling(self.detached(), crate::watch::POLL_INTERVAL)
+    }
+}
+
+impl DisplayProfileManager for LinuxProfileProvider {
+    fn import_profile(&self, path: &Path) -> Result<String, ProfileError> {
+        read_icc_file(path)?;
+
+        #[cfg(feature = "dbus-support")]
+        if self.should_use_dbus() {
+            let store =
+                search_path::user_profile_dir(|name| std::env::var_os(name)).ok_or_else(|| {
+                    ProfileError::IoError(
+                        "Cannot locate the user profile store: HOME is not set".to_string(),
+                    )
+                })?;
+            return self.create_profile(&copy_into_store(path, &store)?);
+        }
+
+        let output = self.execute_colormgr(&["import-profile", path_arg(path)?])?;
+        self.colormgr_profile_id(&output)
+    }
+
+    fn create_profile(&self, path: &Path) -> Result<String, ProfileError> {
+        let data = read_icc_file(path)?;
+
+        #[cfg(feature = "dbus-support")]
+        if let Some(client) = self.preferred_colord() {
+            return self.create_dbus_profile(client, path, &data);
+        }
+
+        let filename = path_arg(path)?;
+        match self.execute_colormgr(&["find-profile-by-filename", filename]) {
+            Ok(output) => return self.colormgr_profile_id(&output),
+            // colormgr reports an unknown file as a failed command
+            Err(ProfileError::SystemError(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        let profile_id = new_profile_id(path, &data);
+        self.execute_colormgr(&["create-profile", &profile_id, "disk"])?;
+        self.execute_colormgr(&["profile-set-property", &profile_id, "Filename", filename])?;
+        Ok(profile_id)
+    }
+
+    fn add_profile(&self, display: &Display, profile_id: &str) -> Result<(), ProfileError> {
+        #[cfg(feature = "dbus-support")]
+        if let Some(client) = self.preferred_colord() {
+            return client.add_profile(&display.id, profile_id);
+        }
+
+        self.execute_colormgr(&["device-add-profile", &display.id, profile_id])
+            .map(drop)
+    }
+
+    fn make_profile_default(
+        &self,
+        display: &Display,
+        profile_id: &str,
+    ) -> Result<(), ProfileError> {
+        #[cfg(feature = "dbus-support")]
+        if let Some(client) = self.preferred_colord() {
+            return client.make_profile_default(&display.id, profile_id);
+        }
+
+        self.execute_colormgr(&["device-make-profile-default", &display.id, profile_id])
+            .map(drop)
+    }
+
+    fn remove_profile(&self, display: &Display, profile_id: &str) -> Result<(), ProfileError> {
+        #[cfg(feature = "dbus-support")]
+        if let Some(client) = self.preferred_colord() {
+            return client.remove_profile(&display.id, profile_id);
+        }
 
-        self.load_profile_data(&file_path)
+        self.execute_colormgr(&["device-remove-profile", &display.id, profile_id])
+            .map(drop)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::command::CommandOutput;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
 
     #[test]
     fn test_parse_colormgr_devices() {
@@ -655,26 +1741,246 @@ Profile 1:          icc-a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6
     }
 
     #[test]
-    fn test_parse_colormgr_devices_empty() {
-        let provider = LinuxProfileProvider::new();
-        let empty_output = "";
-
-        let devices = provider.parse_colormgr_devices(empty_output).unwrap();
-        assert_eq!(devices.len(), 0);
-    }
-
-    #[test]
-    fn test_parse_colormgr_devices_no_displays() {
+    fn test_parse_colormgr_devices_metadata() {
         let provider = LinuxProfileProvider::new();
         let sample_output = r#"
-Device ID:          usb-046d-c52b-event-mouse
-Kind:               mouse
-Model:              Logitech USB Receiver
-Vendor:             Logitech
+Device ID:          xrandr-eDP-1
+Kind:               display
+Embedded:           Yes
+Model:              0x06cb
+Vendor:             BOE
+Metadata:           XRANDR_name=eDP-1
+Metadata:           OutputEdidMd5=f1b0a8d4a2e94b2c7c0b7d5a3f6e1c2d
+Profile 1:          icc-f1b0a8d4a2e94b2c7c0b7d5a3f6e1c2d
         "#;
 
         let devices = provider.parse_colormgr_devices(sample_output).unwrap();
-        assert_eq!(devices.len(), 0); // Should filter out non-display devices
+        assert_eq!(devices.len(), 1);
+        assert!(devices[0].embedded);
+        assert_eq!(
+            devices[0].metadata.get("XRANDR_name"),
+            Some(&"eDP-1".to_string())
+        );
+
+        let displays = provider.convert_devices_to_displays(devices).unwrap();
+        let display = &displays[0];
+        assert_eq!(display.vendor(), Some("BOE"));
+        assert_eq!(display.model(), Some("0x06cb"));
+        assert_eq!(display.serial(), None);
+        assert_eq!(display.connector(), Some("eDP-1"));
+        assert_eq!(display.is_builtin(), Some(true));
+    }
+
+    fn primary_test_devices() -> Vec<ColormgrDevice> {
+        ["eDP-1", "DP-1", "HDMI-1"]
+            .iter()
+            .map(|connector| ColormgrDevice {
+                id: format!("xrandr-{}", connector),
+                kind: "display".to_string(),
+                model: String::new(),
+                vendor: String::new(),
+                serial: String::new(),
+                embedded: false,
+                profiles: Vec::new(),
+                metadata: BTreeMap::from([("XRANDR_name".to_string(), connector.to_string())]),
+                ..ColormgrDevice::default()
+            })
+            .collect()
+    }
+
+    fn primary_of(displays: &[Display]) -> (&str, Option<PrimarySource>) {
+        let primaries: Vec<&Display> = displays.iter().filter(|d| d.is_primary).collect();
+        assert_eq!(primaries.len(), 1, "exactly one display should be primary");
+        (primaries[0].id.as_str(), primaries[0].primary_source())
+    }
+
+    #[test]
+    fn test_primary_from_colord_metadata() {
+        let provider = LinuxProfileProvider::new();
+        let mut devices = primary_test_devices();
+        devices[1]
+            .metadata
+            .insert("OutputPriority".to_string(), "primary".to_string());
+        devices[2]
+            .metadata
+            .insert("OutputPriority".to_string(), "secondary".to_string());
+
+        let displays = provider.convert_devices_to_displays(devices).unwrap();
+        assert_eq!(
+            primary_of(&displays),
+            ("xrandr-DP-1", Some(PrimarySource::Colord))
+        );
+        assert_eq!(displays[0].primary_source(), None);
+    }
+
+    #[test]
+    fn test_primary_config_override() {
+        let mut devices = primary_test_devices();
+        devices[1]
+            .metadata
+            .insert("OutputPriority".to_string(), "primary".to_string());
+
+        // Matched by connector name
+        let provider = LinuxProfileProvider::with_config(ProfileConfig {
+            primary_display: Some("HDMI-1".to_string()),
+            ..ProfileConfig::default()
+        });
+        let displays = provider
+            .convert_devices_to_displays(devices.clone())
+            .unwrap();
+        assert_eq!(
+            primary_of(&displays),
+            ("xrandr-HDMI-1", Some(PrimarySource::Config))
+        );
+
+        // Matched by display ID
+        let provider = LinuxProfileProvider::with_config(ProfileConfig {
+            primary_display: Some("xrandr-eDP-1".to_string()),
+            ..ProfileConfig::default()
+        });
+        let displays = provider
+            .convert_devices_to_displays(devices.clone())
+            .unwrap();
+        assert_eq!(
+            primary_of(&displays),
+            ("xrandr-eDP-1", Some(PrimarySource::Config))
+        );
+
+        // An override that matches nothing falls through to colord
+        let provider = LinuxProfileProvider::with_config(ProfileConfig {
+            primary_display: Some("DP-9".to_string()),
+            ..ProfileConfig::default()
+        });
+        let displays = provider.convert_devices_to_displays(devices).unwrap();
+        assert_eq!(
+            primary_of(&displays),
+            ("xrandr-DP-1", Some(PrimarySource::Colord))
+        );
+    }
+
+    #[test]
+    fn test_identify_displays_from_drm() {
+        use crate::edid::test_support::{edid, TestMonitor};
+        use drm::test_support::add_connector;
+
+        let sysfs = TempDir::new().unwrap();
+        let dell = edid(&TestMonitor::default(), &[]);
+        let panel = edid(
+            &TestMonitor {
+                manufacturer: "BOE",
+                name: "NE135FBM-N41",
+                serial: "",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        add_connector(sysfs.path(), "card0-DP-1", &dell);
+        add_connector(sysfs.path(), "card0-eDP-1", &panel);
+
+        // The Dell is found by model and serial, the panel (whose output the X
+        // driver names differently) by colord's EDID checksum
+        let output = format!(
+            r#"
+Device ID:          xrandr-Dell Inc.-DELL U2720Q-8LX2N63
+Kind:               display
+Model:              DELL U2720Q
+Vendor:             Dell Inc.
+Serial:             8LX2N63
+
+Device ID:          xrandr-eDP
+Kind:               display
+Metadata:           XRANDR_name=eDP
+Metadata:           OutputEdidMd5={}
+"#,
+            md5::hex(&panel)
+        );
+        let provider = LinuxProfileProvider::new().with_sysfs_root(sysfs.path());
+        let devices = provider.parse_colormgr_devices(&output).unwrap();
+        let displays = provider.convert_devices_to_displays(devices).unwrap();
+
+        assert_eq!(displays[0].name, "Dell Inc. DELL U2720Q");
+        assert_eq!(displays[0].connector(), Some("DP-1"));
+        assert_eq!(displays[0].edid(), Some(&dell[..]));
+
+        assert_eq!(displays[1].name, "NE135FBM-N41");
+        assert_eq!(displays[1].vendor(), Some("BOE"));
+        assert_eq!(displays[1].serial(), Some("0x4c4d3253"));
+        assert_eq!(displays[1].connector(), Some("eDP"));
+        assert_eq!(displays[1].edid(), Some(&panel[..]));
+    }
+
+    #[test]
+    fn test_parse_xrandr_outputs() {
+        let sample_output = r#"
+Screen 0: minimum 320 x 200, current 4480 x 1440, maximum 16384 x 16384
+eDP-1 connected 1920x1080+2560+0 (normal left inverted right x axis y axis) 309mm x 174mm
+   1920x1080     60.02*+  59.93
+DP-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm
+   2560x1440     59.95 +  143.91*
+HDMI-1 disconnected (normal left inverted right x axis y axis)
+   1920x1080     60.00*
+DP-2 connected (normal left inverted right x axis y axis)
+   1920x1200     59.95 +
+        "#;
+
+        let outputs = parse_xrandr_outputs(sample_output);
+        assert_eq!(
+            outputs,
+            vec![
+                RandrOutput {
+                    name: "eDP-1".to_string(),
+                    primary: false,
+                    geometry: Some((1920, 1080, 2560, 0)),
+                    refresh_rate: Some(60.02),
+                },
+                RandrOutput {
+                    name: "DP-1".to_string(),
+                    primary: true,
+                    geometry: Some((2560, 1440, 0, 0)),
+                    refresh_rate: Some(143.91),
+                },
+                RandrOutput {
+                    name: "DP-2".to_string(),
+                    primary: false,
+                    geometry: None,
+                    refresh_rate: None,
+                },
+            ]
+        );
+        assert!(parse_xrandr_outputs("HDMI-1 disconnected primary").is_empty());
+        assert_eq!(
+            parse_xrandr_geometry("1920x1080+-1920+0"),
+            Some((1920, 1080, -1920, 0))
+        );
+
+        let display = Display::new("xrandr-DP-1", "Dell", false).with_connector("DP-1");
+        let display = with_randr_layout(display, &outputs);
+        assert_eq!(display.resolution(), Some((2560, 1440)));
+        assert_eq!(display.position(), Some((0, 0)));
+        assert_eq!(display.refresh_rate(), Some(143.91));
+    }
+
+    #[test]
+    fn test_parse_colormgr_devices_empty() {
+        let provider = LinuxProfileProvider::new();
+        let empty_output = "";
+
+        let devices = provider.parse_colormgr_devices(empty_output).unwrap();
+        assert_eq!(devices.len(), 0);
+    }
+
+    #[test]
+    fn test_parse_colormgr_devices_no_displays() {
+        let provider = LinuxProfileProvider::new();
+        let sample_output = r#"
+Device ID:          usb-046d-c52b-event-mouse
+Kind:               mouse
+Model:              Logitech USB Receiver
+Vendor:             Logitech
+        "#;
+
+        let devices = provider.parse_colormgr_devices(sample_output).unwrap();
+        assert_eq!(devices.len(), 0); // Should filter out non-display devices
     }
 
     #[test]
@@ -702,6 +2008,41 @@ Colorspace:         rgb
         assert_eq!(profile.colorspace, "rgb");
     }
 
+    #[test]
+    fn test_parse_colormgr_profile_metadata() {
+        let provider = LinuxProfileProvider::new();
+        let sample_output = r#"
+Profile ID:         icc-2c9c8b0c8e5c4e9b8f7a6d5c4b3a2918
+Filename:           /var/lib/colord/icc/edid-2c9c8b0c8e5c4e9b8f7a6d5c4b3a2918.icc
+Title:              LG ULTRAWIDE
+Kind:               display-device
+Colorspace:         rgb
+Metadata:           DATA_source=edid
+Metadata:           EDID_md5=2c9c8b0c8e5c4e9b8f7a6d5c4b3a2918
+        "#;
+
+        let profile = provider
+            .parse_colormgr_profile(sample_output, "test-id")
+            .unwrap();
+
+        assert_eq!(profile.metadata.len(), 2);
+        assert_eq!(
+            profile.metadata.get("DATA_source"),
+            Some(&"edid".to_string())
+        );
+
+        let assigned = provider.convert_assigned_profile(profile, true);
+        assert!(assigned.is_default);
+        assert_eq!(assigned.id, Some("test-id".to_string()));
+        assert_eq!(assigned.kind, Some("display-device".to_string()));
+        assert_eq!(assigned.profile.name, "LG ULTRAWIDE");
+        assert_eq!(assigned.profile.color_space, ColorSpace::RGB);
+        assert_eq!(
+            assigned.metadata.get("EDID_md5"),
+            Some(&"2c9c8b0c8e5c4e9b8f7a6d5c4b3a2918".to_string())
+        );
+    }
+
     #[test]
     fn test_parse_colormgr_profile_no_filename() {
         let provider = LinuxProfileProvider::new();
@@ -739,7 +2080,7 @@ Colorspace:         rgb
         let provider = LinuxProfileProvider::new();
         // This test will depend on whether colormgr is installed
         // We can't assert a specific result, but we can test that it doesn't panic
-        let _available = provider.is_colormgr_available();
+        let _available = provider.check_colormgr().is_ok();
     }
 
     #[test]
@@ -754,6 +2095,7 @@ Colorspace:         rgb
         let config = ProfileConfig {
             linux_prefer_dbus: false,
             fallback_enabled: true,
+            ..ProfileConfig::default()
         };
         let provider = LinuxProfileProvider::with_config(config);
         // Even with dbus feature, should respect config
@@ -772,11 +2114,13 @@ Colorspace:         rgb
         let config_with_fallback = ProfileConfig {
             linux_prefer_dbus: true,
             fallback_enabled: true,
+            ..ProfileConfig::default()
         };
 
         let config_without_fallback = ProfileConfig {
             linux_prefer_dbus: true,
             fallback_enabled: false,
+            ..ProfileConfig::default()
         };
 
         let provider_with = LinuxProfileProvider::with_config(config_with_fallback);
@@ -788,20 +2132,18 @@ Colorspace:         rgb
 
     #[test]
     fn test_filesystem_fallback_display() {
-        let provider = LinuxProfileProvider::new();
-
         // Test the filesystem fallback display creation logic
-        let profiles = vec![
+        let profiles = [
             PathBuf::from("/usr/share/color/icc/sRGB.icc"),
             PathBuf::from("/usr/share/color/icc/AdobeRGB.icc"),
         ];
 
         if !profiles.is_empty() {
-            let displays = vec![Display {
-                id: "filesystem-fallback".to_string(),
-                name: "Generic Display".to_string(),
-                is_primary: true,
-            }];
+            let displays = [Display::new(
+                "filesystem-fallback".to_string(),
+                "Generic Display".to_string(),
+                true,
+            )];
 
             assert_eq!(displays.len(), 1);
             assert_eq!(displays[0].id, "filesystem-fallback");
@@ -810,60 +2152,8 @@ Colorspace:         rgb
         }
     }
 
-    // Mock tests for the trait implementation
-    struct MockLinuxProvider {
-        devices: Vec<ColormgrDevice>,
-        should_fail: bool,
-    }
-
-    impl MockLinuxProvider {
-        fn new() -> Self {
-            let devices = vec![
-                ColormgrDevice {
-                    id: "display-1".to_string(),
-                    kind: "display".to_string(),
-                    model: "Test Monitor".to_string(),
-                    vendor: "Test Vendor".to_string(),
-                    serial: "12345".to_string(),
-                    profiles: vec!["profile-1".to_string()],
-                },
-                ColormgrDevice {
-                    id: "display-2".to_string(),
-                    kind: "display".to_string(),
-                    model: "Second Monitor".to_string(),
-                    vendor: "Another Vendor".to_string(),
-                    serial: "67890".to_string(),
-                    profiles: vec!["profile-2".to_string()],
-                },
-            ];
-
-            Self {
-                devices,
-                should_fail: false,
-            }
-        }
-
-        fn with_failure() -> Self {
-            Self {
-                devices: Vec::new(),
-                should_fail: true,
-            }
-        }
-
-        fn empty() -> Self {
-            Self {
-                devices: Vec::new(),
-                should_fail: false,
-            }
-        }
-    }
-
-    // We can't easily mock the actual colormgr commands in unit tests,
-    // but we can test the parsing logic and error handling
     #[test]
     fn test_display_name_generation() {
-        let provider = LinuxProfileProvider::new();
-
         // Test with vendor and model
         let device1 = ColormgrDevice {
             id: "test-1".to_string(),
@@ -871,7 +2161,9 @@ Colorspace:         rgb
             model: "Monitor".to_string(),
             vendor: "TestCorp".to_string(),
             serial: "123".to_string(),
+            embedded: false,
             profiles: vec!["profile-1".to_string()],
+            ..ColormgrDevice::default()
         };
 
         // Test with model only
@@ -881,7 +2173,9 @@ Colorspace:         rgb
             model: "Monitor".to_string(),
             vendor: "".to_string(),
             serial: "456".to_string(),
+            embedded: false,
             profiles: vec!["profile-2".to_string()],
+            ..ColormgrDevice::default()
         };
 
         // Test with no model
@@ -891,10 +2185,12 @@ Colorspace:         rgb
             model: "".to_string(),
             vendor: "".to_string(),
             serial: "789".to_string(),
+            embedded: false,
             profiles: vec!["profile-3".to_string()],
+            ..ColormgrDevice::default()
         };
 
-        let devices = vec![device1, device2, device3];
+        let devices = [device1, device2, device3];
 
         // Simulate the display name generation logic
         let mut displays = Vec::new();
@@ -909,11 +2205,7 @@ Colorspace:         rgb
                 format!("Display {}", index + 1)
             };
 
-            displays.push(Display {
-                id: device.id.clone(),
-                name: display_name,
-                is_primary: index == 0,
-            });
+            displays.push(Display::new(device.id.clone(), display_name, index == 0));
         }
 
         assert_eq!(displays[0].name, "TestCorp Monitor");
@@ -924,4 +2216,1152 @@ Colorspace:         rgb
         assert!(!displays[1].is_primary);
         assert!(!displays[2].is_primary);
     }
+
+    /// Provider that answers colormgr calls from a recorded transcript in
+    /// `tests/fixtures/colormgr`, with D-Bus and fallbacks disabled
+    fn fixture_provider(fixture: &str) -> LinuxProfileProvider {
+        LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                fallback_enabled: false,
+                ..ProfileConfig::default()
+            },
+            crate::command::FixtureRunner::new(&format!("colormgr/{}", fixture)),
+        )
+        .with_sysfs_root("/nonexistent")
+    }
+
+    #[test]
+    fn test_watch_polls_without_colord() {
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                fallback_enabled: false,
+                ..ProfileConfig::default()
+            },
+            crate::command::FixtureRunner::new("colormgr/ubuntu-22.04"),
+        )
+        .with_sysfs_root("/nonexistent");
+        // As if colord were not running
+        #[cfg(feature = "dbus-support")]
+        let _ = provider.colord.set(None);
+
+        let watcher = provider.watch().unwrap();
+        assert!(matches!(
+            watcher.poll(std::time::Duration::from_millis(50)),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_fixture_fedora_gnome_wayland() {
+        let provider = fixture_provider("fedora-40");
+
+        // The printer device is not a display
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 2);
+        assert_eq!(displays[0].name, "BOE 0x0a1d");
+        assert_eq!(displays[0].connector(), Some("eDP-1"));
+        assert_eq!(displays[0].is_builtin(), Some(true));
+
+        let primary = provider.get_primary_display().unwrap();
+        assert_eq!(primary.id, "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S");
+        assert_eq!(primary.serial(), Some("HT8XN64P0D2S"));
+        assert_eq!(primary.primary_source(), Some(PrimarySource::Colord));
+
+        // The calibrated profile outranks the EDID one
+        let profile = provider.get_profile(&primary).unwrap();
+        assert_eq!(
+            profile.name,
+            "DELL U2415 #1 2024-03-02 09-14 2.2 F-S XYZLUT+MTX"
+        );
+        assert_eq!(profile.color_space, ColorSpace::RGB);
+        assert!(profile.file_path.as_ref().is_some_and(|p| p.is_file()));
+
+        let data = provider.get_profile_data(&primary).unwrap();
+        assert_eq!(&data[36..40], b"acsp");
+        let colorimetry = crate::Colorimetry::from_icc(&data).unwrap();
+        assert!(colorimetry.has_vcgt);
+
+        let profiles = provider.get_profiles(&primary).unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert!(profiles[0].is_default && !profiles[1].is_default);
+        assert_eq!(
+            profiles[0].metadata.get("DATA_source"),
+            Some(&"calib".to_string())
+        );
+        assert_eq!(
+            profiles[1].metadata.get("DATA_source"),
+            Some(&"edid".to_string())
+        );
+        assert_eq!(profiles[1].kind, Some("display-device".to_string()));
+        for assigned in &profiles {
+            let data = provider
+                .get_assigned_profile_data(&primary, assigned)
+                .unwrap();
+            assert_eq!(&data[36..40], b"acsp");
+        }
+    }
+
+    #[test]
+    fn test_fixture_ubuntu_gnome_x11() {
+        let provider = fixture_provider("ubuntu-22.04");
+
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 1);
+
+        let display = &displays[0];
+        assert_eq!(display.name, "Goldstar Company Ltd LG ULTRAWIDE");
+        assert_eq!(display.connector(), Some("HDMI-1"));
+        assert_eq!(display.primary_source(), Some(PrimarySource::Colord));
+
+        let profile = provider.get_profile(display).unwrap();
+        assert_eq!(profile.name, "LG ULTRAWIDE");
+
+        let data = provider.get_profile_data(display).unwrap();
+        let header = crate::parse_icc_header(&data).unwrap();
+        assert_eq!(header.version.0, 2);
+        assert_eq!(header.device_class, "mntr");
+    }
+
+    #[test]
+    fn test_fixture_arch_kde() {
+        let provider = fixture_provider("arch-kde");
+
+        // colord-kde records no output priority, so the first display is picked
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 2);
+        assert_eq!(
+            displays[0].primary_source(),
+            Some(PrimarySource::FirstDisplay)
+        );
+
+        let profile = provider.get_profile(&displays[0]).unwrap();
+        assert_eq!(profile.name, "sRGB");
+        let data = provider.get_profile_data(&displays[0]).unwrap();
+        let colorimetry = crate::Colorimetry::from_icc(&data).unwrap();
+        let white_point = colorimetry.white_point.unwrap();
+        assert!((white_point.x - 0.3127).abs() < 0.001);
+
+        // The projector has no profile assigned
+        let projector = &displays[1];
+        assert_eq!(projector.name, "Display 2");
+        assert!(matches!(
+            provider.get_profile(projector),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+        assert!(provider.get_profiles(projector).unwrap().is_empty());
+
+        let unknown = Display::new("xrandr-DP-9", "Gone", false);
+        assert!(matches!(
+            provider.get_profile(&unknown),
+            Err(ProfileError::DisplayNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_fixture_edid_derived_profile() {
+        use crate::edid::test_support::{edid, TestMonitor};
+        use drm::test_support::add_connector;
+
+        let sysfs = TempDir::new().unwrap();
+        let projector = edid(
+            &TestMonitor {
+                manufacturer: "EPS",
+                name: "EPSON PJ",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        add_connector(sysfs.path(), "card0-HDMI-A-1", &projector);
+
+        let provider_with = |fallback_enabled| {
+            LinuxProfileProvider::with_runner(
+                ProfileConfig {
+                    linux_prefer_dbus: false,
+                    fallback_enabled,
+                    ..ProfileConfig::default()
+                },
+                crate::command::FixtureRunner::new("colormgr/arch-kde"),
+            )
+            .with_sysfs_root(sysfs.path())
+        };
+
+        // The projector colord has no profile for gets one from its EDID
+        let provider = provider_with(true);
+        let displays = provider.get_displays().unwrap();
+        let projector_display = &displays[1];
+        assert_eq!(projector_display.connector(), Some("HDMI-A-1"));
+
+        let profile = provider.get_profile(projector_display).unwrap();
+        assert_eq!(profile.name, "EPSON PJ (EDID)");
+        assert!(profile
+            .description
+            .as_deref()
+            .is_some_and(|d| d.contains("EDID-derived")));
+        assert_eq!(profile.file_path, None);
+
+        let data = provider.get_profile_data(projector_display).unwrap();
+        assert_eq!(data, profile_from_edid(&projector).unwrap());
+
+        let profiles = provider.get_profiles(projector_display).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].kind.as_deref(), Some("edid-derived"));
+        assert_eq!(
+            profiles[0].metadata.get("EDID_md5"),
+            Some(&md5::hex(&projector))
+        );
+
+        // The monitor with a profile keeps it
+        let profile = provider.get_profile(&displays[0]).unwrap();
+        assert_eq!(profile.name, "sRGB");
+
+        // Without fallbacks, nothing is made up
+        let provider = provider_with(false);
+        assert!(matches!(
+            provider.get_profile(projector_display),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+        assert!(provider.get_profiles(projector_display).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fixture_matching_profile_before_edid() {
+        use crate::edid::test_support::{edid, TestMonitor};
+        use crate::icc::test_support::{mluc, profile};
+        use drm::test_support::add_connector;
+
+        let sysfs = TempDir::new().unwrap();
+        let projector = edid(
+            &TestMonitor {
+                manufacturer: "EPS",
+                name: "EPSON PJ",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        add_connector(sysfs.path(), "card0-HDMI-A-1", &projector);
+        let profiles = TempDir::new().unwrap();
+        let epson = profiles.path().join("epson.icc");
+        std::fs::write(&epson, profile(&[(b"dmnd", mluc("EPS"))])).unwrap();
+
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                fallback_enabled: true,
+                extra_profile_dirs: vec![profiles.path().to_path_buf()],
+                ..ProfileConfig::default()
+            },
+            crate::command::FixtureRunner::new("colormgr/arch-kde"),
+        )
+        .with_sysfs_root(sysfs.path());
+
+        // A profile made for the projector's manufacturer beats one from its EDID
+        let displays = provider.get_displays().unwrap();
+        let projector_display = &displays[1];
+        let profiles = provider.get_profiles(projector_display).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].profile.file_path.as_ref(), Some(&epson));
+        assert_eq!(
+            profiles[0].metadata.get("reason").map(String::as_str),
+            Some("manufacturer")
+        );
+        assert_eq!(
+            provider.get_profile_data(projector_display).unwrap(),
+            std::fs::read(&epson).unwrap()
+        );
+    }
+
+    #[cfg(feature = "x11")]
+    #[test]
+    fn test_fixture_x11_profile_before_edid() {
+        use crate::edid::test_support::{edid, TestMonitor};
+        use crate::icc::test_support::{mluc, profile};
+        use drm::test_support::add_connector;
+
+        let sysfs = TempDir::new().unwrap();
+        add_connector(
+            sysfs.path(),
+            "card0-HDMI-A-1",
+            &edid(&TestMonitor::default(), &[]),
+        );
+        let data = profile(&[(b"desc", mluc("Applied by dispwin"))]);
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                ..ProfileConfig::default()
+            },
+            crate::command::FixtureRunner::new("colormgr/arch-kde"),
+        )
+        .with_sysfs_root(sysfs.path())
+        .with_x11_profiles(vec![X11Profile {
+            atom: "_ICC_PROFILE_1".to_string(),
+            output: Some("HDMI-A-1".to_string()),
+            primary: false,
+            data: data.clone(),
+        }]);
+
+        // The profile applied on the root window wins over one made from the EDID
+        let displays = provider.get_displays().unwrap();
+        let projector = &displays[1];
+        assert_eq!(
+            provider.get_profile(projector).unwrap().name,
+            "Applied by dispwin"
+        );
+        assert_eq!(provider.get_profile_data(projector).unwrap(), data);
+        assert_eq!(provider.get_profiles(projector).unwrap()[0].kind, None);
+    }
+
+    #[test]
+    fn test_drm_backend_without_colord() {
+        use crate::edid::test_support::{edid, TestMonitor};
+        use crate::icc::test_support::{mluc, profile};
+        use drm::test_support::add_full_connector;
+
+        let sysfs = TempDir::new().unwrap();
+        let dell = edid(&TestMonitor::default(), &[]);
+        let panel = edid(
+            &TestMonitor {
+                manufacturer: "BOE",
+                name: "NE135FBM-N41",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        add_full_connector(
+            sysfs.path(),
+            "card0-DP-1",
+            "connected",
+            true,
+            &["3840x2160", "2560x1440"],
+            &dell,
+        );
+        add_full_connector(sysfs.path(), "card0-DP-2", "disconnected", false, &[], &[]);
+        add_full_connector(sysfs.path(), "card0-HDMI-A-1", "connected", false, &[], &[]);
+        add_full_connector(
+            sysfs.path(),
+            "card0-eDP-1",
+            "connected",
+            true,
+            &["2880x1920"],
+            &panel,
+        );
+
+        // The Dell has a profile named after its EDID in the store, the panel
+        // one assigned in the mapping file
+        let profiles = TempDir::new().unwrap();
+        let dell_profile = profiles
+            .path()
+            .join(format!("edid-{}.icc", md5::hex(&dell)));
+        std::fs::write(
+            &dell_profile,
+            profile(&[(b"desc", mluc("Dell calibrated"))]),
+        )
+        .unwrap();
+        std::fs::write(profiles.path().join("panel.icc"), profile(&[])).unwrap();
+        let map = profiles.path().join("profiles.conf");
+        std::fs::write(&map, "# Laptop panel\neDP-1 = panel.icc\n").unwrap();
+
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                fallback_enabled: true,
+                extra_profile_dirs: vec![profiles.path().to_path_buf()],
+                linux_profile_map: Some(map.clone()),
+                ..ProfileConfig::default()
+            },
+            crate::command::FixtureRunner::new("colormgr/missing"),
+        )
+        .with_sysfs_root(sysfs.path());
+
+        let displays = provider.get_displays().unwrap();
+        let ids: Vec<&str> = displays.iter().map(|d| d.id.as_str()).collect();
+        let dell_id = format!("drm-DP-1-{}", &md5::hex(&dell)[..8]);
+        let panel_id = format!("drm-eDP-1-{}", &md5::hex(&panel)[..8]);
+        assert_eq!(
+            ids,
+            [dell_id.as_str(), "drm-card0-HDMI-A-1", panel_id.as_str()]
+        );
+
+        let (dell_display, projector, laptop) = (&displays[0], &displays[1], &displays[2]);
+        assert_eq!(dell_display.name, "DELL U2720Q");
+        assert_eq!(dell_display.vendor(), Some("DEL"));
+        assert_eq!(dell_display.resolution(), Some((3840, 2160)));
+        assert_eq!(dell_display.is_builtin(), Some(false));
+        assert_eq!(
+            dell_display.primary_source(),
+            Some(PrimarySource::FirstDisplay)
+        );
+        assert_eq!(projector.name, "HDMI-A-1");
+        assert_eq!(projector.edid(), None);
+        assert_eq!(laptop.is_builtin(), Some(true));
+        assert_eq!(laptop.edid(), Some(&panel[..]));
+
+        let profile_info = provider.get_profile(dell_display).unwrap();
+        assert_eq!(profile_info.name, "Dell calibrated");
+        assert_eq!(profile_info.file_path.as_ref(), Some(&dell_profile));
+        let assigned = provider.get_profiles(dell_display).unwrap();
+        assert_eq!(assigned[0].kind.as_deref(), Some("edid-store"));
+
+        let profile_info = provider.get_profile(laptop).unwrap();
+        assert_eq!(profile_info.name, "panel");
+        assert_eq!(profile_info.color_space, ColorSpace::RGB);
+        let data = provider.get_profile_data(laptop).unwrap();
+        assert_eq!(&data[36..40], b"acsp");
+        let assigned = provider.get_profiles(laptop).unwrap();
+        assert_eq!(assigned[0].kind.as_deref(), Some("mapped"));
+
+        // Nothing to go on for the projector without an EDID
+        assert!(matches!(
+            provider.get_profile(projector),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+        assert!(provider.get_profiles(projector).unwrap().is_empty());
+
+        // A mapping to a missing profile is reported rather than ignored
+        std::fs::write(&map, "HDMI-A-1 = gone.icc\n").unwrap();
+        assert!(matches!(
+            provider.get_profile(projector),
+            Err(ProfileError::IoError(msg)) if msg.contains("gone.icc")
+        ));
+
+        // Another monitor on the same connector is another display
+        let unplugged = Display::new(dell_id.clone(), "DELL U2720Q", false);
+        add_full_connector(sysfs.path(), "card0-DP-1", "connected", true, &[], &panel);
+        assert!(matches!(
+            provider.get_profile(&unplugged),
+            Err(ProfileError::DisplayNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_filesystem_fallback_chooses_display_profile() {
+        use crate::edid::test_support::{edid, TestMonitor};
+        use crate::icc::test_support::{mluc, profile};
+        use drm::test_support::add_full_connector;
+
+        // A printer profile sorts first, but is no use for a display
+        let profiles = TempDir::new().unwrap();
+        let mut printer = profile(&[(b"desc", mluc("Office printer"))]);
+        printer[12..20].copy_from_slice(b"prtrCMYK");
+        std::fs::write(profiles.path().join("a-printer.icc"), printer).unwrap();
+        std::fs::write(
+            profiles.path().join("b-wide.icc"),
+            profile(&[(b"desc", mluc("Wide gamut"))]),
+        )
+        .unwrap();
+        std::fs::write(
+            profiles.path().join("c-srgb.icc"),
+            profile(&[(b"desc", mluc("sRGB IEC61966-2.1"))]),
+        )
+        .unwrap();
+        std::fs::write(
+            profiles.path().join("d-dell.icc"),
+            profile(&[(b"dmnd", mluc("DEL")), (b"dmdd", mluc("DELL U2720Q"))]),
+        )
+        .unwrap();
+        let map = profiles.path().join("profiles.conf");
+        std::fs::write(&map, "").unwrap();
+
+        let sysfs = TempDir::new().unwrap();
+        let panel = edid(
+            &TestMonitor {
+                manufacturer: "BOE",
+                name: "NE135FBM-N41",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        add_full_connector(
+            sysfs.path(),
+            "card0-DP-1",
+            "connected",
+            true,
+            &["3840x2160"],
+            &edid(&TestMonitor::default(), &[]),
+        );
+        add_full_connector(
+            sysfs.path(),
+            "card0-eDP-1",
+            "connected",
+            true,
+            &["2880x1920"],
+            &panel,
+        );
+
+        let provider_on = |sysfs: &Path| {
+            LinuxProfileProvider::with_runner(
+                ProfileConfig {
+                    linux_prefer_dbus: false,
+                    fallback_enabled: true,
+                    extra_profile_dirs: vec![profiles.path().to_path_buf()],
+                    linux_profile_map: Some(map.clone()),
+                    ..ProfileConfig::default()
+                },
+                crate::command::FixtureRunner::new("colormgr/missing"),
+            )
+            .with_sysfs_root(sysfs)
+        };
+        let provider = provider_on(sysfs.path());
+
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 2);
+        let (dell, laptop) = (&displays[0], &displays[1]);
+        assert_eq!(dell.name, "DELL U2720Q");
+
+        // The Dell gets the profile made for its model
+        let assigned = provider.get_profiles(dell).unwrap();
+        assert_eq!(assigned.len(), 1);
+        assert!(assigned[0].is_default);
+        assert_eq!(
+            assigned[0].profile.file_path,
+            Some(profiles.path().join("d-dell.icc"))
+        );
+        assert_eq!(assigned[0].kind.as_deref(), Some("filesystem"));
+        assert_eq!(
+            assigned[0].metadata.get("reason").map(String::as_str),
+            Some("model")
+        );
+        assert_eq!(
+            provider.get_profile(dell).unwrap().description.as_deref(),
+            Some("File system fallback: made for this monitor model")
+        );
+        assert_eq!(
+            provider.get_profile_data(dell).unwrap(),
+            std::fs::read(profiles.path().join("d-dell.icc")).unwrap()
+        );
+
+        // No profile on disk was made for the panel, so its EDID beats sRGB
+        let assigned = provider.get_profiles(laptop).unwrap();
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].kind.as_deref(), Some("edid-derived"));
+
+        // Without any monitor in sysfs, a generic display stands in for them
+        let empty = TempDir::new().unwrap();
+        let provider = provider_on(empty.path());
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 1);
+        let generic = &displays[0];
+        assert_eq!(generic.id, "filesystem-fallback");
+
+        // Nothing is known about the generic display, so sRGB is the best fit
+        let info = provider.get_profile(generic).unwrap();
+        assert_eq!(info.name, "sRGB IEC61966-2.1");
+        assert_eq!(info.file_path, Some(profiles.path().join("c-srgb.icc")));
+        assert_eq!(info.color_space, ColorSpace::RGB);
+        assert_eq!(
+            info.description.as_deref(),
+            Some("File system fallback: standard sRGB, as no profile matches the monitor")
+        );
+        let assigned = provider.get_profiles(generic).unwrap();
+        assert_eq!(
+            assigned[0].metadata.get("reason").map(String::as_str),
+            Some("srgb")
+        );
+    }
+
+    #[test]
+    fn test_fixture_without_colormgr() {
+        let provider = fixture_provider("missing");
+
+        match provider.get_displays() {
+            Err(ProfileError::SystemError(msg)) => assert!(msg.contains("colormgr")),
+            other => panic!("expected a SystemError, got {:?}", other),
+        }
+    }
+
+    /// Replays a fixture while counting `colormgr --version` probes
+    struct ProbeCountingRunner {
+        inner: crate::command::FixtureRunner,
+        probes: Arc<AtomicUsize>,
+    }
+
+    impl CommandRunner for ProbeCountingRunner {
+        fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+            if args == ["--version"] {
+                self.probes.fetch_add(1, Ordering::SeqCst);
+            }
+            self.inner.run(program, args)
+        }
+    }
+
+    /// Fails every command as if it hung past the timeout
+    struct HungRunner;
+
+    impl CommandRunner for HungRunner {
+        fn run(&self, program: &str, _args: &[&str]) -> io::Result<CommandOutput> {
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{} did not finish within 5s", program),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_colormgr_probe_is_cached() {
+        let probes = Arc::new(AtomicUsize::new(0));
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                fallback_enabled: false,
+                ..ProfileConfig::default()
+            },
+            ProbeCountingRunner {
+                inner: crate::command::FixtureRunner::new("colormgr/fedora-40"),
+                probes: Arc::clone(&probes),
+            },
+        )
+        .with_sysfs_root("/nonexistent");
+
+        let displays = provider.get_displays().unwrap();
+        provider.get_displays().unwrap();
+        provider.get_profiles(&displays[0]).unwrap();
+
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_colormgr_timeout() {
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                fallback_enabled: false,
+                ..ProfileConfig::default()
+            },
+            HungRunner,
+        )
+        .with_sysfs_root("/nonexistent");
+
+        match provider.get_displays() {
+            Err(ProfileError::Timeout(msg)) => assert!(msg.contains("colormgr")),
+            other => panic!("expected a Timeout, got {:?}", other),
+        }
+        // A probe that timed out is retried on the next call
+        assert_eq!(provider.colormgr_available.get(), None);
+    }
+
+    /// Replays a fixture in which one profile was removed after the devices were read
+    struct VanishedProfileRunner {
+        inner: crate::command::FixtureRunner,
+        profile_id: &'static str,
+    }
+
+    impl CommandRunner for VanishedProfileRunner {
+        fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+            if args == ["get-profile", self.profile_id] {
+                return Ok(CommandOutput {
+                    success: false,
+                    stdout: Vec::new(),
+                    stderr: b"Failed to find profile".to_vec(),
+                });
+            }
+            self.inner.run(program, args)
+        }
+    }
+
+    #[test]
+    fn test_vanished_first_profile() {
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                fallback_enabled: false,
+                ..ProfileConfig::default()
+            },
+            VanishedProfileRunner {
+                inner: crate::command::FixtureRunner::new("colormgr/fedora-40"),
+                profile_id: "icc-fc2a4e3a27ac8dd28a66e1a57c224442",
+            },
+        )
+        .with_sysfs_root("/nonexistent");
+        let displays = provider.get_displays().unwrap();
+        let dell = displays.iter().find(|d| d.name.contains("DELL")).unwrap();
+
+        // The remaining profile becomes the default
+        let profiles = provider.get_profiles(dell).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert!(profiles[0].is_default);
+        assert_eq!(
+            profiles[0].metadata.get("DATA_source"),
+            Some(&"edid".to_string())
+        );
+
+        // And get_profile agrees
+        let profile = provider.get_profile(dell).unwrap();
+        assert_eq!(profile, profiles[0].profile);
+        assert_eq!(
+            provider.get_profile_data(dell).unwrap(),
+            std::fs::read(profile.file_path.unwrap()).unwrap()
+        );
+    }
+
+    /// Records colormgr command lines, answering the profile commands like colormgr
+    #[derive(Default)]
+    struct RecordingRunner {
+        commands: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl CommandRunner for RecordingRunner {
+        fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+            self.commands
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", program, args.join(" ")));
+
+            let (success, stdout) = match args {
+                ["import-profile", _] => (
+                    true,
+                    "Object Path:   /org/freedesktop/ColorManager/profiles/icc_0f1e\n\
+                     Filename:      /home/alice/.local/share/icc/calibrated.icc\n\
+                     Profile ID:    icc-0f1e\n",
+                ),
+                // colormgr fails on a file colord does not know
+                ["find-profile-by-filename", _] => (false, ""),
+                _ => (true, ""),
+            };
+            Ok(CommandOutput {
+                success,
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+
+    /// Write a minimal ICC profile with the given header profile ID
+    fn write_icc(path: &Path, profile_id: [u8; 16]) {
+        let mut data = vec![0u8; 128];
+        data[0..4].copy_from_slice(&128u32.to_be_bytes());
+        data[36..40].copy_from_slice(b"acsp");
+        data[84..100].copy_from_slice(&profile_id);
+        std::fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_read_icc_file_checks_signature() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.icc");
+        std::fs::write(&path, b"not a profile").unwrap();
+
+        assert!(matches!(
+            read_icc_file(&path),
+            Err(ProfileError::ParseError(_))
+        ));
+        assert!(matches!(
+            read_icc_file(&dir.path().join("missing.icc")),
+            Err(ProfileError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_new_profile_id() {
+        let mut data = vec![0u8; 128];
+        let path = Path::new("/tmp/DELL U2415 (calibrated).icc");
+        assert_eq!(new_profile_id(path, &data), "icc-DELL_U2415__calibrated_");
+
+        data[84] = 0xab;
+        data[99] = 0x01;
+        assert_eq!(
+            new_profile_id(path, &data),
+            "icc-ab000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_copy_into_store() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("calibrated.icc");
+        write_icc(&source, [0; 16]);
+        let store = dir.path().join("share/icc");
+
+        let copy = copy_into_store(&source, &store).unwrap();
+        assert_eq!(copy, store.join("calibrated.icc"));
+        assert!(search_path::is_icc_file(&copy));
+
+        // Importing a profile that is already in the store leaves it intact
+        assert_eq!(copy_into_store(&copy, &store).unwrap(), copy);
+        assert!(search_path::is_icc_file(&copy));
+    }
+
+    #[test]
+    fn test_manage_profiles_with_colormgr() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("calibrated.icc");
+        write_icc(&path, [0; 16]);
+        let filename = path.to_str().unwrap();
+
+        let runner = RecordingRunner::default();
+        let commands = Arc::clone(&runner.commands);
+        let provider = LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                ..ProfileConfig::default()
+            },
+            runner,
+        )
+        .with_sysfs_root("/nonexistent");
+        let display = Display::new("xrandr-dell".to_string(), "Dell".to_string(), true);
+
+        assert_eq!(provider.import_profile(&path).unwrap(), "icc-0f1e");
+        assert_eq!(provider.create_profile(&path).unwrap(), "icc-calibrated");
+        provider.add_profile(&display, "icc-0f1e").unwrap();
+        provider.make_profile_default(&display, "icc-0f1e").unwrap();
+        provider.remove_profile(&display, "icc-calibrated").unwrap();
+
+        assert_eq!(
+            *commands.lock().unwrap(),
+            [
+                "colormgr --version".to_string(),
+                format!("colormgr import-profile {}", filename),
+                format!("colormgr find-profile-by-filename {}", filename),
+                "colormgr create-profile icc-calibrated disk".to_string(),
+                format!(
+                    "colormgr profile-set-property icc-calibrated Filename {}",
+                    filename
+                ),
+                "colormgr device-add-profile xrandr-dell icc-0f1e".to_string(),
+                "colormgr device-make-profile-default xrandr-dell icc-0f1e".to_string(),
+                "colormgr device-remove-profile xrandr-dell icc-calibrated".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_non_icc_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("calibrated.icc");
+        std::fs::write(&path, b"not a profile").unwrap();
+
+        let runner = RecordingRunner::default();
+        let commands = Arc::clone(&runner.commands);
+        let provider = LinuxProfileProvider::with_runner(ProfileConfig::default(), runner)
+            .with_sysfs_root("/nonexistent");
+
+        assert!(matches!(
+            provider.import_profile(&path),
+            Err(ProfileError::ParseError(_))
+        ));
+        assert!(commands.lock().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "x11")]
+    #[test]
+    fn test_x11_displays() {
+        let x11_profile = |atom: &str, output: Option<&str>, primary| X11Profile {
+            atom: atom.to_string(),
+            output: output.map(str::to_string),
+            primary,
+            data: Vec::new(),
+        };
+
+        let displays = x11_displays(&[
+            x11_profile("_ICC_PROFILE", Some("eDP-1"), false),
+            x11_profile("_ICC_PROFILE_1", Some("DP-1"), true),
+        ]);
+        assert_eq!(displays[0].id, "x11-eDP-1");
+        assert_eq!(displays[1].connector(), Some("DP-1"));
+        assert_eq!(
+            primary_of(&displays),
+            ("x11-DP-1", Some(PrimarySource::RandR))
+        );
+
+        // Without RandR only the property name identifies the screen
+        let displays = x11_displays(&[x11_profile("_ICC_PROFILE", None, false)]);
+        assert_eq!(displays[0].id, "x11-_ICC_PROFILE");
+        assert_eq!(displays[0].name, "Display 1");
+        assert_eq!(
+            primary_of(&displays),
+            ("x11-_ICC_PROFILE", Some(PrimarySource::FirstDisplay))
+        );
+    }
+
+    #[cfg(feature = "dbus-support")]
+    #[test]
+    fn test_dbus_provider_against_stub_colord() {
+        use colord::stub::{StubColord, StubDevice, StubProfile, TestBus};
+
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let laptop = StubDevice {
+            id: "xrandr-BOE-0x0a1d".to_string(),
+            kind: "display".to_string(),
+            vendor: "BOE".to_string(),
+            embedded: true,
+            metadata: [("XRANDR_name".to_string(), "eDP-1".to_string())].into(),
+            ..StubDevice::default()
+        };
+        let external = StubDevice {
+            id: "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S".to_string(),
+            kind: "display".to_string(),
+            metadata: [("XRANDR_name", "DP-1"), ("OutputPriority", "primary")]
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .into(),
+            profiles: vec![StubProfile {
+                id: "icc-fc2a4e3a27ac8dd28a66e1a57c224442".to_string(),
+                filename: "/home/alice/.local/share/icc/calibrated.icc".to_string(),
+                title: "DELL U2415 calibrated".to_string(),
+                ..StubProfile::display()
+            }],
+            ..StubDevice::default()
+        };
+        let printer = StubDevice {
+            id: "cups-HP_LaserJet".to_string(),
+            kind: "printer".to_string(),
+            ..StubDevice::default()
+        };
+        let _colord = StubColord::serve(&bus, vec![laptop, external, printer]).unwrap();
+
+        let config = ProfileConfig {
+            fallback_enabled: false,
+            ..ProfileConfig::default()
+        };
+        let client = ColordClient::connect(bus.address(), config.timeout).unwrap();
+        let provider = LinuxProfileProvider::with_colord(config, client);
+
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 2);
+        assert_eq!(displays[0].is_builtin(), Some(true));
+        assert_eq!(
+            primary_of(&displays),
+            (
+                "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S",
+                Some(PrimarySource::Colord)
+            )
+        );
+
+        let profiles = provider.get_profiles(&displays[1]).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(
+            profiles[0].id.as_deref(),
+            Some("icc-fc2a4e3a27ac8dd28a66e1a57c224442")
+        );
+        assert_eq!(profiles[0].profile.name, "DELL U2415 calibrated");
+    }
+
+    #[cfg(feature = "dbus-support")]
+    #[test]
+    fn test_dbus_get_profile_matches_get_profiles() {
+        use colord::stub::{StubColord, StubDevice, StubProfile, TestBus};
+
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let laptop = StubDevice {
+            id: "xrandr-BOE-0x0a1d".to_string(),
+            kind: "display".to_string(),
+            ..StubDevice::default()
+        };
+        let external = StubDevice {
+            id: "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S".to_string(),
+            kind: "display".to_string(),
+            profiles: vec![
+                StubProfile {
+                    id: "icc-fc2a4e3a27ac8dd28a66e1a57c224442".to_string(),
+                    title: "DELL U2415 calibrated".to_string(),
+                    ..StubProfile::display()
+                },
+                StubProfile {
+                    id: "icc-4a7fd54764161c8d4199ac127c6ce85f".to_string(),
+                    title: "DELL U2415".to_string(),
+                    ..StubProfile::display()
+                },
+            ],
+            ..StubDevice::default()
+        };
+        let colord = StubColord::serve(&bus, vec![laptop, external]).unwrap();
+        colord
+            .unregister_profile("icc-fc2a4e3a27ac8dd28a66e1a57c224442")
+            .unwrap();
+
+        let config = ProfileConfig {
+            fallback_enabled: false,
+            ..ProfileConfig::default()
+        };
+        let client = ColordClient::connect(bus.address(), config.timeout).unwrap();
+        let provider = LinuxProfileProvider::with_colord(config, client);
+        let displays = provider.get_displays().unwrap();
+
+        // colord answered, so a display without profiles has none to give
+        assert!(matches!(
+            provider.get_profile(&displays[0]),
+            Err(ProfileError::ProfileNotAvailable(id)) if id == displays[0].id
+        ));
+        assert!(provider.get_profiles(&displays[0]).unwrap().is_empty());
+
+        // The calibrated profile is gone, so the next one is the default
+        let profiles = provider.get_profiles(&displays[1]).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert!(profiles[0].is_default);
+        assert_eq!(profiles[0].profile.name, "DELL U2415");
+        assert_eq!(
+            provider.get_profile(&displays[1]).unwrap(),
+            profiles[0].profile
+        );
+    }
+
+    #[cfg(all(feature = "dbus-support", feature = "x11"))]
+    #[test]
+    fn test_dbus_unassigned_display_uses_x11_profile() {
+        use crate::icc::test_support::{mluc, profile};
+        use colord::stub::{StubColord, StubDevice, TestBus};
+
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let external = StubDevice {
+            id: "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S".to_string(),
+            kind: "display".to_string(),
+            metadata: [("XRANDR_name".to_string(), "DP-1".to_string())].into(),
+            ..StubDevice::default()
+        };
+        let _colord = StubColord::serve(&bus, vec![external]).unwrap();
+
+        let data = profile(&[(b"desc", mluc("Applied by dispwin"))]);
+        let config = ProfileConfig::default();
+        let client = ColordClient::connect(bus.address(), config.timeout).unwrap();
+        let provider =
+            LinuxProfileProvider::with_colord(config, client).with_x11_profiles(vec![X11Profile {
+                atom: "_ICC_PROFILE".to_string(),
+                output: Some("DP-1".to_string()),
+                primary: true,
+                data: data.clone(),
+            }]);
+
+        let displays = provider.get_displays().unwrap();
+        let profile = provider.get_profile(&displays[0]).unwrap();
+        assert_eq!(profile.name, "Applied by dispwin");
+        assert_eq!(
+            profile.description.as_deref(),
+            Some("X11 _ICC_PROFILE root window property")
+        );
+        assert_eq!(provider.get_profile_data(&displays[0]).unwrap(), data);
+
+        let profiles = provider.get_profiles(&displays[0]).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].profile, profile);
+    }
+
+    #[cfg(feature = "dbus-support")]
+    #[test]
+    fn test_dbus_manager_against_stub_colord() {
+        use colord::stub::{StubColord, StubDevice, StubProfile, TestBus};
+
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let monitor = StubDevice {
+            id: "xrandr-dell".to_string(),
+            kind: "display".to_string(),
+            profiles: vec![StubProfile {
+                id: "icc-edid".to_string(),
+                filename: "/home/alice/.local/share/icc/edid.icc".to_string(),
+                ..StubProfile::display()
+            }],
+            ..StubDevice::default()
+        };
+        let _colord = StubColord::serve(&bus, vec![monitor]).unwrap();
+
+        let config = ProfileConfig {
+            fallback_enabled: false,
+            ..ProfileConfig::default()
+        };
+        let client = ColordClient::connect(bus.address(), config.timeout).unwrap();
+        let provider = LinuxProfileProvider::with_colord(config, client);
+        let display = provider.get_primary_display().unwrap();
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("calibrated.icc");
+        write_icc(&path, [0; 16]);
+
+        let profile_id = provider.create_profile(&path).unwrap();
+        assert_eq!(profile_id, "icc-calibrated");
+        // A file colord already knows keeps its profile
+        assert_eq!(provider.create_profile(&path).unwrap(), profile_id);
+
+        provider.add_profile(&display, &profile_id).unwrap();
+        provider
+            .make_profile_default(&display, &profile_id)
+            .unwrap();
+        let ids = |provider: &LinuxProfileProvider| -> Vec<String> {
+            provider
+                .get_profiles(&display)
+                .unwrap()
+                .into_iter()
+                .filter_map(|profile| profile.id)
+                .collect()
+        };
+        assert_eq!(ids(&provider), ["icc-calibrated", "icc-edid"]);
+
+        provider.remove_profile(&display, "icc-edid").unwrap();
+        assert_eq!(ids(&provider), ["icc-calibrated"]);
+
+        let unknown = Display::new("xrandr-gone".to_string(), "Gone".to_string(), false);
+        assert!(matches!(
+            provider.add_profile(&unknown, &profile_id),
+            Err(ProfileError::DisplayNotFound(_))
+        ));
+        assert!(matches!(
+            provider.make_profile_default(&display, "icc-missing"),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+        assert!(matches!(
+            provider.remove_profile(&display, "icc-edid"),
+            Err(ProfileError::SystemError(_))
+        ));
+    }
+
+    #[cfg(feature = "wayland")]
+    #[test]
+    fn test_wayland_provider_against_stand_in() {
+        use crate::icc::test_support::{mluc, profile};
+        use wayland::stub::{StandIn, StubImage, StubOutput};
+
+        let data = profile(&[(b"desc", mluc("Calibrated U2720Q"))]);
+        let compositor = StandIn::serve(vec![
+            StubOutput::new("eDP-1"),
+            StubOutput {
+                image: StubImage::Icc(data.clone()),
+                ..StubOutput::new("DP-1")
+            },
+        ]);
+        let provider = LinuxProfileProvider::with_wayland_socket(
+            ProfileConfig {
+                primary_display: Some("DP-1".to_string()),
+                ..ProfileConfig::default()
+            },
+            compositor.socket(),
+        );
+
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 2);
+        assert_eq!(displays[0].id, "wayland-eDP-1");
+        assert_eq!(displays[0].name, "Dell Inc. DELL U2720Q");
+        assert_eq!(displays[0].resolution(), Some((3840, 2160)));
+        assert_eq!(displays[0].position(), Some((0, 0)));
+        assert_eq!(displays[0].scale(), Some(2.0));
+        assert_eq!(displays[0].refresh_rate(), Some(60.0));
+        assert_eq!(
+            primary_of(&displays),
+            ("wayland-DP-1", Some(PrimarySource::Config))
+        );
+
+        // A parametric description has no profile data, only its parameters
+        let parametric = provider.get_profiles(&displays[0]).unwrap();
+        assert_eq!(parametric.len(), 1);
+        assert_eq!(parametric[0].profile.name, "sRGB gamma 2.2");
+        assert_eq!(parametric[0].kind.as_deref(), Some("parametric"));
+        assert_eq!(parametric[0].metadata["tf_named"], "gamma22");
+        assert!(matches!(
+            provider.get_profile_data(&displays[0]),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+
+        let icc = provider.get_profile(&displays[1]).unwrap();
+        assert_eq!(icc.name, "Calibrated U2720Q");
+        assert_eq!(provider.get_profile_data(&displays[1]).unwrap(), data);
+        assert_eq!(
+            provider.get_profiles(&displays[1]).unwrap()[0]
+                .kind
+                .as_deref(),
+            Some("icc")
+        );
+    }
 }
@@ -0,0 +1,518 @@
+//! [`AsyncDisplayProfileProvider`] for Linux.
+//!
+//! colord is queried over async D-Bus and `colormgr` runs as an async
+//! process. What the blocking provider does besides — asking the Wayland
+//! compositor, matching devices to DRM connectors, running xrandr, and the
+//! X11, EDID and file system fallbacks — runs on tokio's blocking thread pool
+//! in a [detached](LinuxProfileProvider::detached) copy of the provider.
+
+use super::colormgr::{ColormgrDevice, ColormgrProfile};
+#[cfg(feature = "dbus-support")]
+use super::ColordClient;
+#[cfg(feature = "wayland")]
+use super::WAYLAND_ID_PREFIX;
+#[cfg(feature = "x11")]
+use super::X11_ID_PREFIX;
+use super::{
+    colormgr_availability, colormgr_stdout, profile_vanished, LinuxProfileProvider, Lookup,
+    DRM_ID_PREFIX,
+};
+use crate::asynchronous::{blocking, AsyncDisplayProfileProvider, DisplayEvents};
+use crate::{Display, DisplayProfileProvider, ProfileError, ProfileInfo};
+
+impl LinuxProfileProvider {
+    /// Run `call` on a detached copy of this provider on the blocking thread pool
+    async fn run_detached<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&Self) -> Result<T, ProfileError> + Send + 'static,
+    ) -> Result<T, ProfileError> {
+        let provider = self.detached();
+        blocking(move || call(&provider)).await
+    }
+
+    /// Execute colormgr as an async process and return its output
+    async fn execute_colormgr_async(&self, args: &[&str]) -> Result<String, ProfileError> {
+        let available = match self.colormgr_available.get() {
+            Some(&available) => available,
+            None => {
+                let probe = self.runner.run_async("colormgr", &["--version"]).await;
+                self.record_colormgr_probe(probe)?
+            }
+        };
+        colormgr_availability(available)?;
+        colormgr_stdout(self.runner.run_async("colormgr", args).await)
+    }
+
+    /// Get all display devices from colormgr
+    async fn get_colormgr_devices_async(&self) -> Result<Vec<ColormgrDevice>, ProfileError> {
+        let output = self.execute_colormgr_async(&["get-devices"]).await?;
+        self.parse_colormgr_devices(&output)
+    }
+
+    /// Get profile information from colormgr
+    async fn get_colormgr_profile_async(
+        &self,
+        profile_id: &str,
+    ) -> Result<ColormgrProfile, ProfileError> {
+        let output = self
+            .execute_colormgr_async(&["get-profile", profile_id])
+            .await?;
+        self.parse_colormgr_profile(&output, profile_id)
+    }
+
+    /// The colord client if D-Bus is preferred and colord is reachable,
+    /// connecting and probing the service once per provider
+    #[cfg(feature = "dbus-support")]
+    async fn preferred_colord_async(&self) -> Option<&ColordClient> {
+        if !self.config.linux_prefer_dbus {
+            return None;
+        }
+        if let Some(client) = self.colord.get() {
+            return client.as_ref();
+        }
+
+        let client = match ColordClient::system_async(self.config.timeout).await {
+            Ok(client) => client.ping_async().await.map(|_| client),
+            Err(e) => Err(e),
+        };
+        match client {
+            Ok(client) => self.colord.get_or_init(|| Some(client)).as_ref(),
+            // As with colormgr, a probe that timed out is not cached
+            Err(ProfileError::Timeout(_)) => None,
+            Err(_) => self.colord.get_or_init(|| None).as_ref(),
+        }
+    }
+
+    /// Turn colord's devices into displays, which may run xrandr
+    async fn displays_from_devices(
+        &self,
+        devices: Vec<ColormgrDevice>,
+    ) -> Result<Vec<Display>, ProfileError> {
+        self.run_detached(move |provider| provider.convert_devices_to_displays(devices))
+            .await
+    }
+
+    /// The X11 or EDID-derived profile of a colord display that has none,
+    /// found on the blocking pool as the blocking provider would
+    async fn unassigned_fallback_async(
+        &self,
+        display: &Display,
+    ) -> Result<Option<Lookup>, ProfileError> {
+        if !self.config.fallback_enabled {
+            return Ok(None);
+        }
+        let display = display.clone();
+        self.run_detached(move |provider| {
+            Ok(provider.unassigned_fallback(&display).map(Lookup::Fallback))
+        })
+        .await
+    }
+
+    /// The profile of a colord display, with the same fallbacks as the
+    /// blocking provider; the fallbacks run on the blocking pool without
+    /// asking colord again
+    async fn lookup_profile_async(&self, display: &Display) -> Result<Lookup, ProfileError> {
+        // Only a failed D-Bus call falls through to colormgr
+        #[cfg(feature = "dbus-support")]
+        if let Some(client) = self.preferred_colord_async().await {
+            match client.get_devices_async().await {
+                Ok(devices) => {
+                    let devices: Vec<ColormgrDevice> =
+                        devices.into_iter().filter(|d| d.is_display()).collect();
+                    let source = ProfileSource::DBus(client.clone());
+                    match self.colord_profile_async(display, &devices, source).await {
+                        Err(ProfileError::Timeout(_)) if self.config.fallback_enabled => {}
+                        result => return result,
+                    }
+                }
+                Err(_) if !self.config.fallback_enabled => {
+                    return Err(ProfileError::SystemError(
+                        "D-Bus method failed and fallback is disabled".to_string(),
+                    ));
+                }
+                Err(_) => {}
+            }
+        }
+
+        let devices = self.get_colormgr_devices_async().await?;
+        self.colord_profile_async(display, &devices, ProfileSource::Colormgr)
+            .await
+    }
+
+    /// The profile of colord display `display` among `devices`: the first
+    /// of its profiles still found in `source`, else one of the fallbacks if
+    /// the device has none
+    async fn colord_profile_async(
+        &self,
+        display: &Display,
+        devices: &[ColormgrDevice],
+        source: ProfileSource,
+    ) -> Result<Lookup, ProfileError> {
+        let device = devices
+            .iter()
+            .find(|d| d.id == display.id)
+            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))?;
+
+        if device.profiles.is_empty() {
+            return self
+                .unassigned_fallback_async(display)
+                .await?
+                .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()));
+        }
+
+        for profile_id in &device.profiles {
+            let profile = match &source {
+                #[cfg(feature = "dbus-support")]
+                ProfileSource::DBus(client) => client.find_profile_by_id_async(profile_id).await,
+                ProfileSource::Colormgr => self.get_colormgr_profile_async(profile_id).await,
+            };
+            match profile {
+                Ok(profile) => return Ok(Lookup::Colord(Box::new(profile))),
+                Err(e) if !profile_vanished(&e) => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Err(ProfileError::ProfileNotAvailable(display.id.clone()))
+    }
+}
+
+/// Where the profiles colord lists for a device are read from
+enum ProfileSource {
+    #[cfg(feature = "dbus-support")]
+    DBus(ColordClient),
+    Colormgr,
+}
+
+/// Whether `display` was found by one of the fallbacks rather than through colord
+fn is_fallback_display(display: &Display) -> bool {
+    if display.id == "filesystem-fallback" || display.id.starts_with(DRM_ID_PREFIX) {
+        return true;
+    }
+    #[cfg(feature = "x11")]
+    if display.id.starts_with(X11_ID_PREFIX) {
+        return true;
+    }
+    #[cfg(feature = "wayland")]
+    if display.id.starts_with(WAYLAND_ID_PREFIX) {
+        return true;
+    }
+    false
+}
+
+impl AsyncDisplayProfileProvider for LinuxProfileProvider {
+    async fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
+        #[cfg(feature = "wayland")]
+        if let Some(displays) = self
+            .run_detached(|provider| Ok(provider.compositor_displays()))
+            .await?
+        {
+            return Ok(displays);
+        }
+
+        #[cfg(feature = "dbus-support")]
+        if let Some(client) = self.preferred_colord_async().await {
+            match client.get_devices_async().await {
+                Ok(devices) => {
+                    let devices = devices.into_iter().filter(ColormgrDevice::is_display);
+                    return self.displays_from_devices(devices.collect()).await;
+                }
+                Err(e @ ProfileError::Timeout(_)) if !self.config.fallback_enabled => {
+                    return Err(e)
+                }
+                Err(_) => {}
+            }
+
+            if !self.config.fallback_enabled {
+                return Err(ProfileError::SystemError(
+                    "D-Bus method failed and fallback is disabled".to_string(),
+                ));
+            }
+        }
+
+        match self.get_colormgr_devices_async().await {
+            Ok(devices) => self.displays_from_devices(devices).await,
+            Err(e) if !self.config.fallback_enabled => Err(e),
+            Err(_) => {
+                self.run_detached(|provider| provider.fallback_displays())
+                    .await
+            }
+        }
+    }
+
+    async fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
+        if is_fallback_display(display) {
+            let display = display.clone();
+            return self
+                .run_detached(move |provider| {
+                    DisplayProfileProvider::get_profile(provider, &display)
+                })
+                .await;
+        }
+
+        match self.lookup_profile_async(display).await? {
+            Lookup::Colord(profile) => Ok(self.convert_assigned_profile(*profile, true).profile),
+            Lookup::Fallback(profile) => Ok(profile.info()),
+        }
+    }
+
+    async fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
+        if is_fallback_display(display) {
+            let display = display.clone();
+            return self
+                .run_detached(move |provider| {
+                    DisplayProfileProvider::get_profile_data(provider, &display)
+                })
+                .await;
+        }
+
+        match self.lookup_profile_async(display).await? {
+            Lookup::Colord(profile) => match profile.filename {
+                Some(path) => tokio::fs::read(&path)
+                    .await
+                    .map_err(|e| ProfileError::IoError(e.to_string())),
+                None => Err(ProfileError::ProfileNotAvailable(format!(
+                    "No file path available for display {}",
+                    display.id
+                ))),
+            },
+            Lookup::Fallback(profile) => Ok(profile.data()),
+        }
+    }
+
+    async fn events(&self) -> Result<DisplayEvents, ProfileError> {
+        self.run_detached(|provider| provider.watch())
+            .await
+            .map(DisplayEvents::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asynchronous::test_support::block_on;
+    use crate::command::FixtureRunner;
+    use crate::ProfileConfig;
+
+    fn provider_with(fixture: &str, fallback_enabled: bool) -> LinuxProfileProvider {
+        LinuxProfileProvider::with_runner(
+            ProfileConfig {
+                linux_prefer_dbus: false,
+                fallback_enabled,
+                ..ProfileConfig::default()
+            },
+            FixtureRunner::new(fixture),
+        )
+        .with_sysfs_root("/nonexistent")
+    }
+
+    #[test]
+    fn test_matches_blocking_provider() {
+        for fixture in ["fedora-40", "ubuntu-22.04", "arch-kde"] {
+            let provider = provider_with(&format!("colormgr/{}", fixture), false);
+            let displays = DisplayProfileProvider::get_displays(&provider).unwrap();
+
+            block_on(async {
+                assert_eq!(
+                    AsyncDisplayProfileProvider::get_displays(&provider)
+                        .await
+                        .unwrap(),
+                    displays
+                );
+                for display in &displays {
+                    let profile = AsyncDisplayProfileProvider::get_profile(&provider, display);
+                    let data = AsyncDisplayProfileProvider::get_profile_data(&provider, display);
+                    assert_eq!(
+                        format!("{:?}", profile.await),
+                        format!(
+                            "{:?}",
+                            DisplayProfileProvider::get_profile(&provider, display)
+                        )
+                    );
+                    assert_eq!(
+                        format!("{:?}", data.await),
+                        format!(
+                            "{:?}",
+                            DisplayProfileProvider::get_profile_data(&provider, display)
+                        )
+                    );
+                }
+            });
+        }
+    }
+
+    #[cfg(feature = "dbus-support")]
+    #[test]
+    fn test_matches_blocking_provider_over_dbus() {
+        use super::super::colord::stub::{StubColord, StubDevice, StubProfile, TestBus};
+        use super::super::drm::test_support::add_connector;
+        use crate::edid::test_support::{edid, TestMonitor};
+
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let calibrated = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(
+            "tests/fixtures/colormgr/fedora-40/root/home/alice/.local/share/icc/\
+             DELL U2415 #1 2024-03-02 09-14 2.2 F-S XYZLUT+MTX.icc",
+        );
+        let laptop = StubDevice {
+            id: "xrandr-BOE-0x0a1d".to_string(),
+            kind: "display".to_string(),
+            vendor: "BOE".to_string(),
+            embedded: true,
+            metadata: [("XRANDR_name".to_string(), "eDP-1".to_string())].into(),
+            ..StubDevice::default()
+        };
+        let external = StubDevice {
+            id: "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S".to_string(),
+            kind: "display".to_string(),
+            metadata: [("XRANDR_name", "DP-1"), ("OutputPriority", "primary")]
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .into(),
+            profiles: vec![StubProfile {
+                id: "icc-fc2a4e3a27ac8dd28a66e1a57c224442".to_string(),
+                filename: calibrated.display().to_string(),
+                title: "DELL U2415 calibrated".to_string(),
+                ..StubProfile::display()
+            }],
+            ..StubDevice::default()
+        };
+        let _colord = StubColord::serve(&bus, vec![laptop, external]).unwrap();
+
+        // The panel colord has no profile for gets one from its EDID with fallbacks
+        let sysfs = tempfile::TempDir::new().unwrap();
+        let panel = edid(
+            &TestMonitor {
+                manufacturer: "BOE",
+                name: "NE135FBM-N41",
+                ..TestMonitor::default()
+            },
+            &[],
+        );
+        add_connector(sysfs.path(), "card0-eDP-1", &panel);
+
+        for fallback_enabled in [false, true] {
+            let config = ProfileConfig {
+                fallback_enabled,
+                ..ProfileConfig::default()
+            };
+            let client = ColordClient::connect(bus.address(), config.timeout).unwrap();
+            let provider =
+                LinuxProfileProvider::with_colord(config, client).with_sysfs_root(sysfs.path());
+            let displays = DisplayProfileProvider::get_displays(&provider).unwrap();
+            assert_eq!(displays.len(), 2);
+
+            block_on(async {
+                assert_eq!(
+                    AsyncDisplayProfileProvider::get_displays(&provider)
+                        .await
+                        .unwrap(),
+                    displays
+                );
+                for display in &displays {
+                    let profile = AsyncDisplayProfileProvider::get_profile(&provider, display);
+                    let data = AsyncDisplayProfileProvider::get_profile_data(&provider, display);
+                    assert_eq!(
+                        format!("{:?}", profile.await),
+                        format!(
+                            "{:?}",
+                            DisplayProfileProvider::get_profile(&provider, display)
+                        )
+                    );
+                    assert_eq!(
+                        format!("{:?}", data.await),
+                        format!(
+                            "{:?}",
+                            DisplayProfileProvider::get_profile_data(&provider, display)
+                        )
+                    );
+                }
+            });
+
+            let panel_profile = DisplayProfileProvider::get_profile(&provider, &displays[0]);
+            assert_eq!(panel_profile.is_ok(), fallback_enabled);
+            assert_eq!(
+                DisplayProfileProvider::get_profile(&provider, &displays[1])
+                    .unwrap()
+                    .name,
+                "DELL U2415 calibrated"
+            );
+        }
+    }
+
+    #[cfg(all(feature = "dbus-support", feature = "x11"))]
+    #[test]
+    fn test_unassigned_display_over_dbus_uses_x11_profile() {
+        use super::super::colord::stub::{StubColord, StubDevice, TestBus};
+        use super::super::X11Profile;
+        use crate::icc::test_support::{mluc, profile};
+
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let external = StubDevice {
+            id: "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S".to_string(),
+            kind: "display".to_string(),
+            metadata: [("XRANDR_name".to_string(), "DP-1".to_string())].into(),
+            ..StubDevice::default()
+        };
+        let _colord = StubColord::serve(&bus, vec![external]).unwrap();
+
+        let data = profile(&[(b"desc", mluc("Applied by dispwin"))]);
+        let config = ProfileConfig::default();
+        let client = ColordClient::connect(bus.address(), config.timeout).unwrap();
+        let provider =
+            LinuxProfileProvider::with_colord(config, client).with_x11_profiles(vec![X11Profile {
+                atom: "_ICC_PROFILE".to_string(),
+                output: Some("DP-1".to_string()),
+                primary: true,
+                data: data.clone(),
+            }]);
+
+        let displays = DisplayProfileProvider::get_displays(&provider).unwrap();
+        block_on(async {
+            let profile = AsyncDisplayProfileProvider::get_profile(&provider, &displays[0])
+                .await
+                .unwrap();
+            assert_eq!(profile.name, "Applied by dispwin");
+            assert_eq!(
+                AsyncDisplayProfileProvider::get_profile_data(&provider, &displays[0])
+                    .await
+                    .unwrap(),
+                data
+            );
+        });
+    }
+
+    #[test]
+    fn test_missing_colormgr() {
+        use super::super::drm::test_support::add_connector;
+        use crate::edid::test_support::{edid, TestMonitor};
+
+        let provider = provider_with("colormgr/missing", false);
+        assert!(matches!(
+            block_on(AsyncDisplayProfileProvider::get_displays(&provider)),
+            Err(ProfileError::SystemError(e)) if e.contains("colormgr command not found")
+        ));
+
+        // With fallbacks the monitors in sysfs are found on the blocking pool
+        let sysfs = tempfile::TempDir::new().unwrap();
+        let monitor = edid(&TestMonitor::default(), &[]);
+        add_connector(sysfs.path(), "card0-DP-1", &monitor);
+        let provider = provider_with("colormgr/missing", true).with_sysfs_root(sysfs.path());
+
+        let displays = block_on(AsyncDisplayProfileProvider::get_displays(&provider)).unwrap();
+        assert_eq!(
+            displays,
+            DisplayProfileProvider::get_displays(&provider).unwrap()
+        );
+        assert!(displays[0].id.starts_with(DRM_ID_PREFIX));
+        assert_eq!(
+            block_on(AsyncDisplayProfileProvider::get_profile_data(
+                &provider,
+                &displays[0]
+            ))
+            .unwrap(),
+            DisplayProfileProvider::get_profile_data(&provider, &displays[0]).unwrap()
+        );
+    }
+}
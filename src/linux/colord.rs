@@ -0,0 +1,529 @@
+//! Client for the colord daemon's D-Bus API.
+//!
+//! One [`ColordClient`] holds one bus connection for the lifetime of the
+//! provider. Objects are read with a single `GetAll` each, and profiles are
+//! looked up by ID with `FindProfileById` instead of scanning every profile.
+//! The lookups are async, for the async API, with blocking wrappers for the
+//! provider.
+
+use super::colormgr::{ColormgrDevice, ColormgrProfile};
+use crate::ProfileError;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use zbus::blocking::{connection, Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+pub(super) const COLORD_SERVICE: &str = "org.freedesktop.ColorManager";
+pub(super) const COLORD_PATH: &str = "/org/freedesktop/ColorManager";
+pub(super) const COLORD_INTERFACE: &str = "org.freedesktop.ColorManager";
+pub(super) const DEVICE_INTERFACE: &str = "org.freedesktop.ColorManager.Device";
+pub(super) const PROFILE_INTERFACE: &str = "org.freedesktop.ColorManager.Profile";
+
+/// colord's error for unknown devices and profiles
+const NOT_FOUND_ERROR: &str = "org.freedesktop.ColorManager.NotFound";
+
+/// A connection to colord
+#[derive(Clone)]
+pub(super) struct ColordClient {
+    connection: Connection,
+}
+
+impl ColordClient {
+    /// Connect to colord on the system bus
+    pub fn system(timeout: Duration) -> Result<Self, ProfileError> {
+        let builder = connection::Builder::system()
+            .map_err(|e| colord_error("Failed to connect to D-Bus", e))?;
+        Self::build(builder, timeout)
+    }
+
+    /// Connect to colord on the bus at `address`
+    #[cfg(test)]
+    pub fn connect(address: &str, timeout: Duration) -> Result<Self, ProfileError> {
+        let builder = connection::Builder::address(address)
+            .map_err(|e| colord_error("Failed to connect to D-Bus", e))?;
+        Self::build(builder, timeout)
+    }
+
+    /// Connect to colord on the system bus without blocking
+    #[cfg(feature = "tokio")]
+    pub async fn system_async(timeout: Duration) -> Result<Self, ProfileError> {
+        let connection = zbus::connection::Builder::system()
+            .map_err(|e| colord_error("Failed to connect to D-Bus", e))?
+            .method_timeout(timeout)
+            .build()
+            .await
+            .map_err(|e| colord_error("Failed to connect to D-Bus", e))?;
+
+        Ok(Self {
+            connection: connection.into(),
+        })
+    }
+
+    fn build(builder: connection::Builder<'_>, timeout: Duration) -> Result<Self, ProfileError> {
+        let connection = builder
+            .method_timeout(timeout)
+            .build()
+            .map_err(|e| colord_error("Failed to connect to D-Bus", e))?;
+
+        Ok(Self { connection })
+    }
+
+    /// Check that colord answers, starting it through bus activation if needed
+    pub fn ping(&self) -> Result<(), ProfileError> {
+        zbus::block_on(self.ping_async())
+    }
+
+    /// [`ping`](Self::ping) without blocking
+    pub async fn ping_async(&self) -> Result<(), ProfileError> {
+        self.async_proxy(COLORD_PATH, "org.freedesktop.DBus.Peer")
+            .await?
+            .call::<_, _, ()>("Ping", &())
+            .await
+            .map_err(|e| colord_error("D-Bus Ping", e))
+    }
+
+    /// Close the connection, ending any [`signals`](Self::signals) iterator
+    pub fn close(&self) {
+        let _ = self.connection.clone().close();
+    }
+
+    /// Subscribe to colord's device and profile change signals.
+    ///
+    /// The subscription is active once this returns, so a snapshot taken
+    /// afterwards misses no change. The iterator ends when the connection closes.
+    pub fn signals(
+        &self,
+    ) -> Result<impl Iterator<Item = ColordSignal> + Send + 'static, ProfileError> {
+        let messages = self
+            .proxy(COLORD_PATH, COLORD_INTERFACE)?
+            .receive_all_signals()
+            .map_err(|e| colord_error("D-Bus AddMatch", e))?;
+
+        Ok(messages.filter_map(|message| {
+            let header = message.header();
+            let path: OwnedObjectPath = message.body().deserialize().ok()?;
+            match header.member()?.as_str() {
+                "DeviceAdded" => Some(ColordSignal::DeviceAdded(path)),
+                "DeviceRemoved" => Some(ColordSignal::DeviceRemoved(path)),
+                "DeviceChanged" => Some(ColordSignal::DeviceChanged(path)),
+                "ProfileChanged" => Some(ColordSignal::ProfileChanged(path)),
+                _ => None,
+            }
+        }))
+    }
+
+    /// Get every device colord knows about, including non-displays
+    pub fn get_devices(&self) -> Result<Vec<ColormgrDevice>, ProfileError> {
+        zbus::block_on(self.get_devices_async())
+    }
+
+    /// [`get_devices`](Self::get_devices) without blocking
+    pub async fn get_devices_async(&self) -> Result<Vec<ColormgrDevice>, ProfileError> {
+        let paths: Vec<OwnedObjectPath> = self
+            .async_proxy(COLORD_PATH, COLORD_INTERFACE)
+            .await?
+            .call("GetDevices", &())
+            .await
+            .map_err(|e| colord_error("D-Bus GetDevices", e))?;
+
+        let mut devices = Vec::with_capacity(paths.len());
+        for path in paths {
+            match self.get_device_async(&path).await {
+                Ok(device) => devices.push(device),
+                Err(e @ ProfileError::Timeout(_)) => return Err(e),
+                // Devices can be removed between listing and reading them
+                Err(_) => {}
+            }
+        }
+
+        Ok(devices)
+    }
+
+    /// Read the device at `path`
+    pub fn get_device(&self, path: &OwnedObjectPath) -> Result<ColormgrDevice, ProfileError> {
+        zbus::block_on(self.get_device_async(path))
+    }
+
+    async fn get_device_async(
+        &self,
+        path: &OwnedObjectPath,
+    ) -> Result<ColormgrDevice, ProfileError> {
+        let mut properties = self.get_all(path, DEVICE_INTERFACE).await?;
+
+        // Device profiles are object paths; their IDs are a property of each profile
+        let mut profiles = Vec::new();
+        for profile_path in properties
+            .take::<Vec<OwnedObjectPath>>("Profiles")
+            .unwrap_or_default()
+        {
+            match self.profile_id_async(&profile_path).await {
+                Ok(profile_id) => profiles.push(profile_id),
+                Err(e @ ProfileError::Timeout(_)) => return Err(e),
+                // As with devices, a profile removed since the device was read is skipped
+                Err(_) => {}
+            }
+        }
+
+        Ok(ColormgrDevice {
+            object_path: path.to_string(),
+            id: properties.string("DeviceId"),
+            kind: properties.string("Kind"),
+            embedded: properties.take("Embedded").unwrap_or_default(),
+            model: properties.string("Model"),
+            vendor: properties.string("Vendor"),
+            serial: properties.string("Serial"),
+            profiles,
+            metadata: properties.metadata(),
+        })
+    }
+
+    /// Read the ID of the profile at `path`
+    pub fn profile_id(&self, path: &OwnedObjectPath) -> Result<String, ProfileError> {
+        zbus::block_on(self.profile_id_async(path))
+    }
+
+    async fn profile_id_async(&self, path: &OwnedObjectPath) -> Result<String, ProfileError> {
+        self.async_proxy(path.as_str(), PROFILE_INTERFACE)
+            .await?
+            .get_property("ProfileId")
+            .await
+            .map_err(|e| colord_error("D-Bus Get ProfileId", e))
+    }
+
+    /// Look up a profile by its colord ID
+    pub fn find_profile_by_id(&self, profile_id: &str) -> Result<ColormgrProfile, ProfileError> {
+        zbus::block_on(self.find_profile_by_id_async(profile_id))
+    }
+
+    /// [`find_profile_by_id`](Self::find_profile_by_id) without blocking
+    pub async fn find_profile_by_id_async(
+        &self,
+        profile_id: &str,
+    ) -> Result<ColormgrProfile, ProfileError> {
+        let path = self.find_profile_path_async(profile_id).await?;
+        self.get_profile(&path, profile_id).await
+    }
+
+    /// Look up the ID of the profile registered for `filename`, if any
+    pub fn find_profile_by_filename(
+        &self,
+        filename: &Path,
+    ) -> Result<Option<String>, ProfileError> {
+        let result: zbus::Result<OwnedObjectPath> = self
+            .proxy(COLORD_PATH, COLORD_INTERFACE)?
+            .call("FindProfileByFilename", &(filename.to_string_lossy(),));
+        match result {
+            Ok(path) => self.profile_id(&path).map(Some),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(colord_error("D-Bus FindProfileByFilename", e)),
+        }
+    }
+
+    /// Register `filename` as a persistent profile with the given ID
+    pub fn create_profile(&self, profile_id: &str, filename: &Path) -> Result<(), ProfileError> {
+        let properties = HashMap::from([("Filename", filename.to_string_lossy())]);
+        self.proxy(COLORD_PATH, COLORD_INTERFACE)?
+            .call::<_, _, OwnedObjectPath>("CreateProfile", &(profile_id, "disk", properties))
+            .map(drop)
+            .map_err(|e| colord_error("D-Bus CreateProfile", e))
+    }
+
+    /// Assign a profile to a device, after its existing profiles
+    pub fn add_profile(&self, device_id: &str, profile_id: &str) -> Result<(), ProfileError> {
+        let (device, profile) = self.device_and_profile(device_id, profile_id)?;
+        // A "hard" relation is remembered by colord, unlike the "soft" ones it makes itself
+        let proxy = self.proxy(device.as_str(), DEVICE_INTERFACE)?;
+        proxy
+            .call::<_, _, ()>("AddProfile", &("hard", profile))
+            .map_err(|e| colord_error("D-Bus AddProfile", e))
+    }
+
+    /// Make an assigned profile the device's default
+    pub fn make_profile_default(
+        &self,
+        device_id: &str,
+        profile_id: &str,
+    ) -> Result<(), ProfileError> {
+        let (device, profile) = self.device_and_profile(device_id, profile_id)?;
+        let proxy = self.proxy(device.as_str(), DEVICE_INTERFACE)?;
+        proxy
+            .call::<_, _, ()>("MakeProfileDefault", &(profile,))
+            .map_err(|e| colord_error("D-Bus MakeProfileDefault", e))
+    }
+
+    /// Unassign a profile from a device
+    pub fn remove_profile(&self, device_id: &str, profile_id: &str) -> Result<(), ProfileError> {
+        let (device, profile) = self.device_and_profile(device_id, profile_id)?;
+        let proxy = self.proxy(device.as_str(), DEVICE_INTERFACE)?;
+        proxy
+            .call::<_, _, ()>("RemoveProfile", &(profile,))
+            .map_err(|e| colord_error("D-Bus RemoveProfile", e))
+    }
+
+    /// Look up the object paths of a device and a profile by ID
+    fn device_and_profile(
+        &self,
+        device_id: &str,
+        profile_id: &str,
+    ) -> Result<(OwnedObjectPath, OwnedObjectPath), ProfileError> {
+        let device = self
+            .proxy(COLORD_PATH, COLORD_INTERFACE)?
+            .call("FindDeviceById", &(device_id,))
+            .map_err(|e| match e {
+                e if is_not_found(&e) => ProfileError::DisplayNotFound(format!(
+                    "Device {} not found via D-Bus",
+                    device_id
+                )),
+                e => colord_error("D-Bus FindDeviceById", e),
+            })?;
+        Ok((device, self.find_profile_path(profile_id)?))
+    }
+
+    fn find_profile_path(&self, profile_id: &str) -> Result<OwnedObjectPath, ProfileError> {
+        zbus::block_on(self.find_profile_path_async(profile_id))
+    }
+
+    async fn find_profile_path_async(
+        &self,
+        profile_id: &str,
+    ) -> Result<OwnedObjectPath, ProfileError> {
+        self.async_proxy(COLORD_PATH, COLORD_INTERFACE)
+            .await?
+            .call("FindProfileById", &(profile_id,))
+            .await
+            .map_err(|e| match e {
+                e if is_not_found(&e) => ProfileError::ProfileNotAvailable(format!(
+                    "Profile {} not found via D-Bus",
+                    profile_id
+                )),
+                e => colord_error("D-Bus FindProfileById", e),
+            })
+    }
+
+    async fn get_profile(
+        &self,
+        path: &OwnedObjectPath,
+        profile_id: &str,
+    ) -> Result<ColormgrProfile, ProfileError> {
+        let mut properties = self.get_all(path, PROFILE_INTERFACE).await?;
+        let filename = properties.string("Filename");
+        let title = properties.string("Title");
+
+        Ok(ColormgrProfile {
+            id: profile_id.to_string(),
+            filename: (!filename.is_empty()).then(|| PathBuf::from(filename)),
+            title: (!title.is_empty()).then_some(title),
+            kind: properties.string("Kind"),
+            colorspace: properties.string("Colorspace"),
+            metadata: properties.metadata(),
+        })
+    }
+
+    /// Read all properties of `interface` on the object at `path`
+    async fn get_all(
+        &self,
+        path: &OwnedObjectPath,
+        interface: &str,
+    ) -> Result<Properties, ProfileError> {
+        self.async_proxy(path.as_str(), "org.freedesktop.DBus.Properties")
+            .await?
+            .call("GetAll", &(interface,))
+            .await
+            .map(Properties)
+            .map_err(|e| colord_error("D-Bus GetAll", e))
+    }
+
+    fn proxy<'a>(&'a self, path: &'a str, interface: &'a str) -> Result<Proxy<'a>, ProfileError> {
+        Proxy::new(&self.connection, COLORD_SERVICE, path, interface)
+            .map_err(|e| colord_error("D-Bus proxy", e))
+    }
+
+    /// A proxy for use from async code, sharing the blocking connection
+    async fn async_proxy<'a>(
+        &'a self,
+        path: &'a str,
+        interface: &'a str,
+    ) -> Result<zbus::Proxy<'a>, ProfileError> {
+        zbus::Proxy::new(self.connection.inner(), COLORD_SERVICE, path, interface)
+            .await
+            .map_err(|e| colord_error("D-Bus proxy", e))
+    }
+}
+
+/// A change announced by colord's manager object
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum ColordSignal {
+    DeviceAdded(OwnedObjectPath),
+    DeviceRemoved(OwnedObjectPath),
+    /// Any device property changed, including its profile list
+    DeviceChanged(OwnedObjectPath),
+    ProfileChanged(OwnedObjectPath),
+}
+
+/// Properties of one colord object, as returned by `GetAll`
+struct Properties(HashMap<String, OwnedValue>);
+
+impl Properties {
+    /// Remove a property, or `None` if it is missing or has an unexpected type
+    fn take<T: TryFrom<OwnedValue>>(&mut self, name: &str) -> Option<T> {
+        self.0
+            .remove(name)
+            .and_then(|value| T::try_from(value).ok())
+    }
+
+    fn string(&mut self, name: &str) -> String {
+        self.take(name).unwrap_or_default()
+    }
+
+    fn metadata(&mut self) -> std::collections::BTreeMap<String, String> {
+        self.take::<HashMap<String, String>>("Metadata")
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Whether a call failed because colord has no such object
+fn is_not_found(error: &zbus::Error) -> bool {
+    matches!(error, zbus::Error::MethodError(name, ..) if name.as_str() == NOT_FOUND_ERROR)
+}
+
+/// Whether a call failed because no reply arrived in time
+pub(super) fn is_timeout(error: &zbus::Error) -> bool {
+    match error {
+        zbus::Error::InputOutput(e) => e.kind() == io::ErrorKind::TimedOut,
+        zbus::Error::MethodError(name, ..) => matches!(
+            name.as_str(),
+            "org.freedesktop.DBus.Error.NoReply"
+                | "org.freedesktop.DBus.Error.Timeout"
+                | "org.freedesktop.DBus.Error.TimedOut"
+        ),
+        _ => false,
+    }
+}
+
+/// Map a D-Bus error, keeping timeouts apart from other failures
+pub(super) fn colord_error(context: &str, error: zbus::Error) -> ProfileError {
+    if is_timeout(&error) {
+        ProfileError::Timeout(format!("{}: {}", context, error))
+    } else {
+        ProfileError::SystemError(format!("{} failed: {}", context, error))
+    }
+}
+
+#[cfg(test)]
+pub(super) mod stub;
+
+#[cfg(test)]
+mod tests {
+    use super::stub::{StubColord, StubDevice, StubProfile, TestBus};
+    use super::*;
+
+    fn test_devices() -> Vec<StubDevice> {
+        vec![
+            StubDevice {
+                id: "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S".to_string(),
+                kind: "display".to_string(),
+                model: "DELL U2415".to_string(),
+                vendor: "Dell Inc.".to_string(),
+                serial: "HT8XN64P0D2S".to_string(),
+                metadata: [("XRANDR_name", "DP-1"), ("OutputPriority", "primary")]
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .into(),
+                profiles: vec![StubProfile {
+                    id: "icc-4a7fd54764161c8d4199ac127c6ce85f".to_string(),
+                    filename: "/usr/share/color/icc/dell.icc".to_string(),
+                    title: "Dell Inc. DELL U2415".to_string(),
+                    metadata: [("DATA_source".to_string(), "edid".to_string())].into(),
+                    ..StubProfile::display()
+                }],
+                ..StubDevice::default()
+            },
+            StubDevice {
+                id: "cups-HP_LaserJet".to_string(),
+                kind: "printer".to_string(),
+                ..StubDevice::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_get_devices_reads_properties() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let _colord = StubColord::serve(&bus, test_devices()).unwrap();
+        let client = ColordClient::connect(bus.address(), Duration::from_secs(5)).unwrap();
+
+        client.ping().unwrap();
+        let devices = client.get_devices().unwrap();
+        assert_eq!(devices.len(), 2);
+
+        let dell = &devices[0];
+        assert_eq!(dell.id, "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S");
+        assert!(dell.is_display());
+        assert_eq!(dell.vendor, "Dell Inc.");
+        assert_eq!(dell.metadata["XRANDR_name"], "DP-1");
+        // IDs come from the ProfileId property, not the object path
+        assert_eq!(dell.profiles, ["icc-4a7fd54764161c8d4199ac127c6ce85f"]);
+        assert!(!devices[1].is_display());
+    }
+
+    #[test]
+    fn test_get_devices_skips_vanished_profiles() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let colord = StubColord::serve(&bus, test_devices()).unwrap();
+        colord
+            .unregister_profile("icc-4a7fd54764161c8d4199ac127c6ce85f")
+            .unwrap();
+        let client = ColordClient::connect(bus.address(), Duration::from_secs(5)).unwrap();
+
+        // The display is still listed, without the profile
+        let devices = client.get_devices().unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].id, "xrandr-Dell Inc.-DELL U2415-HT8XN64P0D2S");
+        assert!(devices[0].profiles.is_empty());
+    }
+
+    #[test]
+    fn test_find_profile_by_id() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let _colord = StubColord::serve(&bus, test_devices()).unwrap();
+        let client = ColordClient::connect(bus.address(), Duration::from_secs(5)).unwrap();
+
+        let profile = client
+            .find_profile_by_id("icc-4a7fd54764161c8d4199ac127c6ce85f")
+            .unwrap();
+        assert_eq!(
+            profile.filename,
+            Some(PathBuf::from("/usr/share/color/icc/dell.icc"))
+        );
+        assert_eq!(profile.title.as_deref(), Some("Dell Inc. DELL U2415"));
+        assert_eq!(profile.kind, "display-device");
+        assert_eq!(profile.metadata["DATA_source"], "edid");
+
+        match client.find_profile_by_id("icc-missing") {
+            Err(ProfileError::ProfileNotAvailable(msg)) => assert!(msg.contains("icc-missing")),
+            other => panic!("expected ProfileNotAvailable, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_service_is_not_a_timeout() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let client = ColordClient::connect(bus.address(), Duration::from_secs(5)).unwrap();
+
+        match client.ping() {
+            Err(ProfileError::SystemError(msg)) => assert!(msg.contains("Ping")),
+            other => panic!("expected a SystemError, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! A stand-in colord service on a private bus, for tests.
+//!
+//! [`TestBus`] runs its own `dbus-daemon`, and [`StubColord`] claims colord's
+//! bus name on it and serves the manager, device and profile objects with the
+//! same object path layout as colord: path segments are the IDs with
+//! punctuation replaced by `_`, so they differ from the IDs themselves.
+
+use super::{COLORD_INTERFACE, COLORD_PATH, COLORD_SERVICE};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use tempfile::TempDir;
+use zbus::blocking::object_server::InterfaceRef;
+use zbus::blocking::{connection, Connection};
+use zbus::names::BusName;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{interface, ObjectServer};
+
+const BUS_CONFIG: &str = r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:dir=@DIR@</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#;
+
+/// A private `dbus-daemon`, stopped on drop
+pub(crate) struct TestBus {
+    daemon: Child,
+    address: String,
+    _dir: TempDir,
+}
+
+impl TestBus {
+    /// Start a bus, or `None` (with a note on stderr) when `dbus-daemon` is not installed
+    pub fn start() -> Option<Self> {
+        let dir = TempDir::new().ok()?;
+        let config = dir.path().join("bus.conf");
+        std::fs::write(
+            &config,
+            BUS_CONFIG.replace("@DIR@", &dir.path().display().to_string()),
+        )
+        .ok()?;
+
+        let spawned = Command::new("dbus-daemon")
+            .arg(format!("--config-file={}", config.display()))
+            .args(["--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut daemon = match spawned {
+            Ok(daemon) => daemon,
+            Err(e) => {
+                eprintln!("skipping: cannot start dbus-daemon: {}", e);
+                return None;
+            }
+        };
+
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+            _dir: dir,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+}
+
+impl Drop for TestBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+/// A device served by [`StubColord`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StubDevice {
+    pub id: String,
+    pub kind: String,
+    pub model: String,
+    pub vendor: String,
+    pub serial: String,
+    pub embedded: bool,
+    pub metadata: HashMap<String, String>,
+    pub profiles: Vec<StubProfile>,
+}
+
+/// A profile served by [`StubColord`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StubProfile {
+    pub id: String,
+    pub filename: String,
+    pub title: String,
+    pub kind: String,
+    pub colorspace: String,
+    pub metadata: HashMap<String, String>,
+}
+
+impl StubProfile {
+    /// An RGB display profile, to fill in with `..StubProfile::display()`
+    pub fn display() -> Self {
+        Self {
+            kind: "display-device".to_string(),
+            colorspace: "rgb".to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// colord's object path for `id` under `kind`
+fn object_path(kind: &str, id: &str) -> OwnedObjectPath {
+    let segment: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    OwnedObjectPath::try_from(format!("{}/{}/{}", COLORD_PATH, kind, segment))
+        .expect("sanitized object path is valid")
+}
+
+/// The colord service, served until dropped
+///
+/// Besides the initial devices it can connect and disconnect devices and change
+/// profiles at run time, announcing each change with colord's signals.
+pub(crate) struct StubColord {
+    connection: Connection,
+}
+
+impl StubColord {
+    /// Claim colord's name on `bus` and serve `devices` and their profiles
+    pub fn serve(bus: &TestBus, devices: Vec<StubDevice>) -> zbus::Result<Self> {
+        let connection = connection::Builder::address(bus.address())?
+            .name(COLORD_SERVICE)?
+            .serve_at(COLORD_PATH, Manager::default())?
+            .build()?;
+
+        let stub = Self { connection };
+        for device in devices {
+            stub.register_device(device)?;
+        }
+        Ok(stub)
+    }
+
+    /// Connect a device, emitting `DeviceAdded`
+    pub fn add_device(&self, device: StubDevice) -> zbus::Result<()> {
+        let path = self.register_device(device)?;
+        self.emit("DeviceAdded", &path)
+    }
+
+    /// Disconnect a device, emitting `DeviceRemoved`
+    pub fn remove_device(&self, id: &str) -> zbus::Result<()> {
+        let path = object_path("devices", id);
+        self.connection.object_server().remove::<Device, _>(&path)?;
+        self.manager()?.get_mut().devices.retain(|p| p != &path);
+        self.emit("DeviceRemoved", &path)
+    }
+
+    /// Add `profile` to a device, emitting `DeviceChanged`
+    pub fn assign_profile(&self, device_id: &str, profile: StubProfile) -> zbus::Result<()> {
+        let profile_path = self.register_profile(profile)?;
+        let path = object_path("devices", device_id);
+        self.connection
+            .object_server()
+            .interface::<_, Device>(&path)?
+            .get_mut()
+            .1
+            .push(profile_path);
+        self.emit("DeviceChanged", &path)
+    }
+
+    /// Set a device metadata entry, emitting `DeviceChanged`
+    pub fn set_metadata(&self, device_id: &str, key: &str, value: &str) -> zbus::Result<()> {
+        let path = object_path("devices", device_id);
+        self.connection
+            .object_server()
+            .interface::<_, Device>(&path)?
+            .get_mut()
+            .0
+            .metadata
+            .insert(key.to_string(), value.to_string());
+        self.emit("DeviceChanged", &path)
+    }
+
+    /// Emit `ProfileChanged`, as colord does when a profile's properties change
+    pub fn touch_profile(&self, id: &str) -> zbus::Result<()> {
+        self.emit("ProfileChanged", &object_path("profiles", id))
+    }
+
+    /// Remove a profile's object but not the devices' references to it, as
+    /// when colord deletes a profile while a client is reading a device
+    pub fn unregister_profile(&self, id: &str) -> zbus::Result<()> {
+        let path = object_path("profiles", id);
+        self.connection
+            .object_server()
+            .remove::<Profile, _>(&path)?;
+        Ok(())
+    }
+
+    fn register_device(&self, device: StubDevice) -> zbus::Result<OwnedObjectPath> {
+        let profile_paths = device
+            .profiles
+            .iter()
+            .map(|profile| self.register_profile(profile.clone()))
+            .collect::<zbus::Result<Vec<_>>>()?;
+
+        let path = object_path("devices", &device.id);
+        self.connection
+            .object_server()
+            .at(path.clone(), Device(device, profile_paths))?;
+        self.manager()?.get_mut().devices.push(path.clone());
+        Ok(path)
+    }
+
+    fn register_profile(&self, profile: StubProfile) -> zbus::Result<OwnedObjectPath> {
+        let path = object_path("profiles", &profile.id);
+        self.manager()?.get_mut().index_profile(&profile, &path);
+        self.connection
+            .object_server()
+            .at(path.clone(), Profile(profile))?;
+        Ok(path)
+    }
+
+    fn manager(&self) -> zbus::Result<InterfaceRef<Manager>> {
+        self.connection.object_server().interface(COLORD_PATH)
+    }
+
+    fn emit(&self, signal: &str, path: &OwnedObjectPath) -> zbus::Result<()> {
+        self.connection.emit_signal(
+            None::<BusName<'_>>,
+            COLORD_PATH,
+            COLORD_INTERFACE,
+            signal,
+            &(path,),
+        )
+    }
+}
+
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "org.freedesktop.ColorManager")]
+enum ColordError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    NotFound(String),
+    AlreadyExists(String),
+}
+
+#[derive(Default)]
+struct Manager {
+    devices: Vec<OwnedObjectPath>,
+    profiles: HashMap<String, OwnedObjectPath>,
+    filenames: HashMap<String, OwnedObjectPath>,
+}
+
+impl Manager {
+    fn index_profile(&mut self, profile: &StubProfile, path: &OwnedObjectPath) {
+        self.profiles.insert(profile.id.clone(), path.clone());
+        if !profile.filename.is_empty() {
+            self.filenames
+                .insert(profile.filename.clone(), path.clone());
+        }
+    }
+}
+
+#[interface(name = "org.freedesktop.ColorManager")]
+impl Manager {
+    fn get_devices(&self) -> Vec<OwnedObjectPath> {
+        self.devices.clone()
+    }
+
+    fn find_device_by_id(&self, id: &str) -> Result<OwnedObjectPath, ColordError> {
+        let path = object_path("devices", id);
+        if self.devices.contains(&path) {
+            Ok(path)
+        } else {
+            Err(ColordError::NotFound(format!(
+                "device id '{}' does not exist",
+                id
+            )))
+        }
+    }
+
+    fn find_profile_by_filename(&self, filename: &str) -> Result<OwnedObjectPath, ColordError> {
+        self.filenames.get(filename).cloned().ok_or_else(|| {
+            ColordError::NotFound(format!("profile filename '{}' does not exist", filename))
+        })
+    }
+
+    async fn create_profile(
+        &mut self,
+        id: String,
+        _scope: &str,
+        properties: HashMap<String, String>,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<OwnedObjectPath, ColordError> {
+        if self.profiles.contains_key(&id) {
+            return Err(ColordError::AlreadyExists(format!(
+                "profile id '{}' already exists",
+                id
+            )));
+        }
+
+        let profile = StubProfile {
+            id,
+            filename: properties.get("Filename").cloned().unwrap_or_default(),
+            ..StubProfile::display()
+        };
+        let path = object_path("profiles", &profile.id);
+        self.index_profile(&profile, &path);
+        server.at(path.clone(), Profile(profile)).await?;
+        Ok(path)
+    }
+
+    fn find_profile_by_id(&self, id: &str) -> Result<OwnedObjectPath, ColordError> {
+        self.profiles
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ColordError::NotFound(format!("profile id '{}' does not exist", id)))
+    }
+}
+
+struct Device(StubDevice, Vec<OwnedObjectPath>);
+
+impl Device {
+    /// Announce a change to the device, as colord does after each method call
+    async fn changed(&self, connection: &zbus::Connection) -> Result<(), ColordError> {
+        let path = object_path("devices", &self.0.id);
+        connection
+            .emit_signal(
+                None::<BusName<'_>>,
+                COLORD_PATH,
+                COLORD_INTERFACE,
+                "DeviceChanged",
+                &(path,),
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn position(&self, profile: &OwnedObjectPath) -> Result<usize, ColordError> {
+        self.1.iter().position(|p| p == profile).ok_or_else(|| {
+            ColordError::NotFound(format!("profile '{}' is not assigned", profile.as_str()))
+        })
+    }
+}
+
+#[interface(name = "org.freedesktop.ColorManager.Device")]
+impl Device {
+    async fn add_profile(
+        &mut self,
+        _relation: &str,
+        profile: OwnedObjectPath,
+        #[zbus(connection)] connection: &zbus::Connection,
+    ) -> Result<(), ColordError> {
+        if self.1.contains(&profile) {
+            return Err(ColordError::AlreadyExists(format!(
+                "profile '{}' has already been added",
+                profile.as_str()
+            )));
+        }
+        self.1.push(profile);
+        self.changed(connection).await
+    }
+
+    async fn make_profile_default(
+        &mut self,
+        profile: OwnedObjectPath,
+        #[zbus(connection)] connection: &zbus::Connection,
+    ) -> Result<(), ColordError> {
+        let index = self.position(&profile)?;
+        let profile = self.1.remove(index);
+        self.1.insert(0, profile);
+        self.changed(connection).await
+    }
+
+    async fn remove_profile(
+        &mut self,
+        profile: OwnedObjectPath,
+        #[zbus(connection)] connection: &zbus::Connection,
+    ) -> Result<(), ColordError> {
+        let index = self.position(&profile)?;
+        self.1.remove(index);
+        self.changed(connection).await
+    }
+
+    #[zbus(property)]
+    fn device_id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[zbus(property)]
+    fn kind(&self) -> String {
+        self.0.kind.clone()
+    }
+
+    #[zbus(property)]
+    fn model(&self) -> String {
+        self.0.model.clone()
+    }
+
+    #[zbus(property)]
+    fn vendor(&self) -> String {
+        self.0.vendor.clone()
+    }
+
+    #[zbus(property)]
+    fn serial(&self) -> String {
+        self.0.serial.clone()
+    }
+
+    #[zbus(property)]
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn embedded(&self) -> bool {
+        self.0.embedded
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, String> {
+        self.0.metadata.clone()
+    }
+
+    #[zbus(property)]
+    fn profiles(&self) -> Vec<OwnedObjectPath> {
+        self.1.clone()
+    }
+}
+
+struct Profile(StubProfile);
+
+#[interface(name = "org.freedesktop.ColorManager.Profile")]
+impl Profile {
+    #[zbus(property)]
+    fn profile_id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[zbus(property)]
+    fn filename(&self) -> String {
+        self.0.filename.clone()
+    }
+
+    #[zbus(property)]
+    fn title(&self) -> String {
+        self.0.title.clone()
+    }
+
+    #[zbus(property)]
+    fn kind(&self) -> String {
+        self.0.kind.clone()
+    }
+
+    #[zbus(property)]
+    fn colorspace(&self) -> String {
+        self.0.colorspace.clone()
+    }
+
+    #[zbus(property)]
+    fn has_vcgt(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn is_system_wide(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, String> {
+        self.0.metadata.clone()
+    }
+}
@@ -0,0 +1,427 @@
+//! Parser for `colormgr` output.
+//!
+//! `colormgr` prints one `Label: value` field per line with the values padded to
+//! a common column. Values may themselves contain colons and may continue on
+//! indented lines; metadata is printed either as one `Metadata:` field per entry
+//! or as a block of `key=value` lines under a single label. The labels are only
+//! stable untranslated, so the command runner forces the C locale.
+
+use crate::ProfileError;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// A device record from `colormgr get-devices`
+#[derive(Debug, Clone, Default)]
+pub(super) struct ColormgrDevice {
+    pub object_path: String,
+    pub id: String,
+    pub kind: String,
+    pub embedded: bool,
+    pub model: String,
+    pub vendor: String,
+    pub serial: String,
+    pub profiles: Vec<String>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl ColormgrDevice {
+    /// Whether colord tracks this device as a display (rather than a printer, scanner, ...)
+    pub fn is_display(&self) -> bool {
+        self.kind.eq_ignore_ascii_case("display")
+    }
+}
+
+/// A profile record from `colormgr get-profile`
+#[derive(Debug, Clone, Default)]
+pub(super) struct ColormgrProfile {
+    pub id: String,
+    pub filename: Option<PathBuf>,
+    pub title: Option<String>,
+    pub kind: String,
+    pub colorspace: String,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// One logical line of `colormgr` output
+enum Entry<'a> {
+    /// A `Label: value` field with any continuation lines
+    Field(Field<'a>),
+    /// A line that is not a field, such as a status message
+    Text { line: usize, text: &'a str },
+}
+
+struct Field<'a> {
+    line: usize,
+    label: &'a str,
+    /// Raw value lines with their line numbers, the first one from the label line
+    values: Vec<(usize, &'a str)>,
+}
+
+impl Field<'_> {
+    /// The value with continuation lines joined by newlines
+    fn value(&self) -> String {
+        self.values
+            .iter()
+            .map(|(_, value)| value.trim())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> ProfileError {
+        parse_error(self.line, message)
+    }
+}
+
+fn parse_error(line: usize, message: impl std::fmt::Display) -> ProfileError {
+    ProfileError::ParseError(format!("colormgr output line {}: {}", line, message))
+}
+
+/// Split output into fields.
+///
+/// An indented line directly below a field continues that field's value; blank
+/// lines end a field. Labels run up to the first colon, so values keep theirs.
+fn split_entries(output: &str) -> Result<Vec<Entry<'_>>, ProfileError> {
+    let mut entries = Vec::new();
+    let mut in_field = false;
+
+    for (index, raw) in output.lines().enumerate() {
+        let line = index + 1;
+
+        if raw.trim().is_empty() {
+            in_field = false;
+            continue;
+        }
+
+        if in_field && raw.starts_with(char::is_whitespace) {
+            if let Some(Entry::Field(field)) = entries.last_mut() {
+                field.values.push((line, raw));
+                continue;
+            }
+        }
+
+        let text = raw.trim();
+        match text.split_once(':') {
+            Some((label, value)) => {
+                let label = label.trim();
+                if label.is_empty() {
+                    return Err(parse_error(line, format!("missing label in {:?}", text)));
+                }
+                entries.push(Entry::Field(Field {
+                    line,
+                    label,
+                    values: vec![(line, value)],
+                }));
+                in_field = true;
+            }
+            None => {
+                entries.push(Entry::Text { line, text });
+                in_field = false;
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Parse a `Yes`/`No` field
+fn parse_bool(field: &Field<'_>) -> Result<bool, ProfileError> {
+    let value = field.value();
+    if value.eq_ignore_ascii_case("yes") {
+        Ok(true)
+    } else if value.eq_ignore_ascii_case("no") {
+        Ok(false)
+    } else {
+        Err(field.error(format!(
+            "expected Yes or No for {}, found {:?}",
+            field.label, value
+        )))
+    }
+}
+
+/// Add the `key=value` entries of a `Metadata` field to `metadata`
+fn parse_metadata(
+    field: &Field<'_>,
+    metadata: &mut BTreeMap<String, String>,
+) -> Result<(), ProfileError> {
+    for &(line, entry) in &field.values {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        match entry.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                metadata.insert(key.trim().to_string(), value.trim().to_string());
+            }
+            _ => {
+                return Err(parse_error(
+                    line,
+                    format!("metadata entry {:?} is not key=value", entry),
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `label` is a numbered device profile such as `Profile 1`
+fn is_profile_label(label: &str) -> bool {
+    label
+        .strip_prefix("Profile ")
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Parse `colormgr get-devices` output into every device it lists.
+///
+/// A record starts at its `Object Path`, or at a `Device ID` when the current
+/// record already has one. Unknown labels are skipped so newer `colormgr`
+/// versions still parse.
+pub(super) fn parse_devices(output: &str) -> Result<Vec<ColormgrDevice>, ProfileError> {
+    let mut devices = Vec::new();
+    let mut current: Option<(usize, ColormgrDevice)> = None;
+
+    for entry in split_entries(output)? {
+        let field = match entry {
+            // Status messages such as "There are no devices" carry no fields
+            Entry::Text { .. } if current.is_none() => continue,
+            Entry::Text { line, text } => {
+                return Err(parse_error(
+                    line,
+                    format!("expected `Label: value`, found {:?}", text),
+                ))
+            }
+            Entry::Field(field) => field,
+        };
+
+        let starts_device = field.label == "Object Path"
+            || (field.label == "Device ID"
+                && current.as_ref().is_none_or(|(_, d)| !d.id.is_empty()));
+        if starts_device {
+            if let Some(record) = current.take() {
+                devices.push(finish_device(record)?);
+            }
+            current = Some((field.line, ColormgrDevice::default()));
+        }
+
+        let Some((_, device)) = current.as_mut() else {
+            return Err(field.error(format!("{} outside a device record", field.label)));
+        };
+
+        match field.label {
+            "Object Path" => device.object_path = field.value(),
+            "Device ID" => device.id = field.value(),
+            "Type" | "Kind" => device.kind = field.value(),
+            "Embedded" => device.embedded = parse_bool(&field)?,
+            "Model" => device.model = field.value(),
+            "Vendor" => device.vendor = field.value(),
+            "Serial" => device.serial = field.value(),
+            "Metadata" => parse_metadata(&field, &mut device.metadata)?,
+            label if is_profile_label(label) => {
+                let profile_id = field.value();
+                if profile_id.is_empty() {
+                    return Err(field.error(format!("{} has no profile ID", label)));
+                }
+                device.profiles.push(profile_id);
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(record) = current {
+        devices.push(finish_device(record)?);
+    }
+
+    Ok(devices)
+}
+
+fn finish_device((line, device): (usize, ColormgrDevice)) -> Result<ColormgrDevice, ProfileError> {
+    if device.id.is_empty() {
+        return Err(parse_error(line, "device record has no Device ID"));
+    }
+    Ok(device)
+}
+
+/// Parse `colormgr get-profile` output for the profile `profile_id`.
+///
+/// The same format is printed by `import-profile` and `find-profile-by-filename`,
+/// whose callers do not know the ID yet: with an empty `profile_id` it is read
+/// from the output's `Profile ID` line.
+pub(super) fn parse_profile(
+    output: &str,
+    profile_id: &str,
+) -> Result<ColormgrProfile, ProfileError> {
+    let mut profile = ColormgrProfile {
+        id: profile_id.to_string(),
+        ..ColormgrProfile::default()
+    };
+
+    for entry in split_entries(output)? {
+        let field = match entry {
+            Entry::Text { line, text } => {
+                return Err(parse_error(
+                    line,
+                    format!("expected `Label: value`, found {:?}", text),
+                ))
+            }
+            Entry::Field(field) => field,
+        };
+
+        match field.label {
+            "Profile ID" if profile.id.is_empty() => profile.id = field.value(),
+            "Filename" => {
+                let filename = field.value();
+                if !filename.is_empty() && filename != "(none)" {
+                    profile.filename = Some(PathBuf::from(filename));
+                }
+            }
+            "Title" => {
+                let title = field.value();
+                if !title.is_empty() {
+                    profile.title = Some(title);
+                }
+            }
+            "Type" | "Kind" => profile.kind = field.value(),
+            "Colorspace" => profile.colorspace = field.value(),
+            "Metadata" => parse_metadata(&field, &mut profile.metadata)?,
+            _ => {}
+        }
+    }
+
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error_message(result: Result<impl std::fmt::Debug, ProfileError>) -> String {
+        match result.unwrap_err() {
+            ProfileError::ParseError(message) => message,
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_values_keep_colons_and_continuations() {
+        let output = "\
+Object Path:   /org/freedesktop/ColorManager/devices/xrandr_Acme
+Type:          display
+Enabled:       No
+Model:         Acme: Pro Art 27
+Vendor:        Acme
+               Display Division
+Device ID:     xrandr-Acme:27
+Format:        ColorSpace..
+Profile 1:     icc-1
+Profile 2:     icc-2
+Metadata:      XRANDR_name=DP-3
+Metadata:      URL=https://example.com/a=b
+";
+        let devices = parse_devices(output).unwrap();
+        assert_eq!(devices.len(), 1);
+
+        let device = &devices[0];
+        assert_eq!(
+            device.object_path,
+            "/org/freedesktop/ColorManager/devices/xrandr_Acme"
+        );
+        assert_eq!(device.id, "xrandr-Acme:27");
+        assert_eq!(device.model, "Acme: Pro Art 27");
+        assert_eq!(device.vendor, "Acme\nDisplay Division");
+        assert_eq!(device.profiles, ["icc-1", "icc-2"]);
+        assert_eq!(device.metadata["URL"], "https://example.com/a=b");
+        assert!(device.is_display());
+    }
+
+    #[test]
+    fn test_metadata_block() {
+        let output = "\
+Device ID:     xrandr-eDP-1
+Type:          display
+Metadata:
+               XRANDR_name=eDP-1
+               OutputPriority=primary
+Serial:        1234
+";
+        let devices = parse_devices(output).unwrap();
+        assert_eq!(devices[0].metadata.len(), 2);
+        assert_eq!(devices[0].metadata["OutputPriority"], "primary");
+        assert_eq!(devices[0].serial, "1234");
+    }
+
+    #[test]
+    fn test_status_message_without_devices() {
+        assert!(parse_devices("There are no devices\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_profile_skips_qualifier_and_flags() {
+        let output = "\
+Object Path:   /org/freedesktop/ColorManager/profiles/icc_1
+Format:        ColorSpace.Paper.Resolution
+Qualifier:     RGB.Plain.300dpi
+Title:         Printer: plain paper
+Gamma Table:   Yes
+System Wide:   No
+Filename:      /usr/share/color/icc/printer.icc
+";
+        let profile = parse_profile(output, "icc-1").unwrap();
+        assert_eq!(profile.id, "icc-1");
+        assert_eq!(profile.title.as_deref(), Some("Printer: plain paper"));
+        assert_eq!(
+            profile.filename,
+            Some(PathBuf::from("/usr/share/color/icc/printer.icc"))
+        );
+    }
+
+    #[test]
+    fn test_malformed_input_reports_line() {
+        let missing_colon = "Device ID:     a\nType:          display\nthis is not a field\n";
+        assert_eq!(
+            parse_error_message(parse_devices(missing_colon)),
+            "colormgr output line 3: expected `Label: value`, found \"this is not a field\""
+        );
+
+        let bad_bool = "\nDevice ID:     a\nEmbedded:      Ja\n";
+        assert_eq!(
+            parse_error_message(parse_devices(bad_bool)),
+            "colormgr output line 3: expected Yes or No for Embedded, found \"Ja\""
+        );
+
+        let bad_metadata = "Device ID:     a\nMetadata:\n  XRANDR_name=DP-1\n  broken\n";
+        assert_eq!(
+            parse_error_message(parse_devices(bad_metadata)),
+            "colormgr output line 4: metadata entry \"broken\" is not key=value"
+        );
+
+        let no_id = "Object Path:   /a\nType:          display\n\nObject Path:   /b\n";
+        assert_eq!(
+            parse_error_message(parse_devices(no_id)),
+            "colormgr output line 1: device record has no Device ID"
+        );
+
+        let no_label = "Title:         x\n: value\n";
+        assert_eq!(
+            parse_error_message(parse_profile(no_label, "icc-1")),
+            "colormgr output line 2: missing label in \": value\""
+        );
+    }
+
+    #[test]
+    fn test_localized_output_is_rejected_precisely() {
+        // Translated labels are not recognised, so no record ever starts
+        let german =
+            "Objektpfad:    /org/freedesktop/ColorManager/devices/x\nGeräte-ID:     xrandr-x\n";
+        assert_eq!(
+            parse_error_message(parse_devices(german)),
+            "colormgr output line 1: Objektpfad outside a device record"
+        );
+
+        let german = "Object Path:   /x\nGeräte-ID:     xrandr-x\n";
+        assert_eq!(
+            parse_error_message(parse_devices(german)),
+            "colormgr output line 1: device record has no Device ID"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Monitor EDIDs from the DRM connectors in sysfs.
+//!
+//! The kernel exposes every connector as `/sys/class/drm/card<N>-<connector>`,
+//! with the EDID of the attached monitor in its `edid` file (empty when
+//! nothing is connected). Connector names follow the kernel's naming, such as
+//! `eDP-1`, `DP-2` or `HDMI-A-1`, which Wayland compositors and the X
+//! modesetting driver use as output names too.
+//!
+//! Next to `edid`, each connector directory has `status` (`connected`,
+//! `disconnected` or `unknown`), `enabled` (whether a CRTC drives it) and
+//! `modes`, the modes the monitor supports with the preferred one first.
+
+use crate::Edid;
+use std::path::Path;
+
+/// A connector with a monitor attached
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct DrmConnector {
+    /// Index of the card the connector belongs to, `N` in `card<N>`
+    pub card: u32,
+    /// Connector name without the card prefix, e.g. `DP-1`
+    pub name: String,
+    /// Whether the connector is lit, i.e. part of the desktop
+    pub enabled: bool,
+    /// Supported modes as `(width, height)`, preferred first, without repeats
+    pub modes: Vec<(u32, u32)>,
+    /// Raw EDID as read from sysfs; empty if the monitor sent none
+    pub edid: Vec<u8>,
+    /// The decoded EDID, if there is a valid one
+    pub info: Option<Edid>,
+}
+
+impl DrmConnector {
+    /// Whether the connector is for a built-in panel
+    pub fn is_builtin(&self) -> bool {
+        ["eDP", "LVDS", "DSI"]
+            .iter()
+            .any(|kind| self.name.starts_with(kind))
+    }
+}
+
+/// Read the connectors with a monitor attached under `sysfs_root` (normally
+/// `/sys`), in card and connector order.
+///
+/// A connector counts as connected if its `status` says so. Drivers that
+/// cannot detect connection report `unknown`; those count when they have a
+/// valid EDID, as do connectors without a `status` file.
+pub(super) fn read_connectors(sysfs_root: &Path) -> Vec<DrmConnector> {
+    let Ok(entries) = std::fs::read_dir(sysfs_root.join("class/drm")) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| parse_entry(name).is_some())
+        .collect();
+    // By card number, so that card10 comes after card2
+    names.sort_by_cached_key(|name| {
+        parse_entry(name).map(|(card, connector)| (card, connector.to_string()))
+    });
+
+    names
+        .into_iter()
+        .filter_map(|entry| {
+            let (card, name) = parse_entry(&entry)?;
+            let dir = sysfs_root.join("class/drm").join(&entry);
+            let read = |file: &str| std::fs::read_to_string(dir.join(file)).ok();
+
+            let edid = std::fs::read(dir.join("edid")).unwrap_or_default();
+            let info = Edid::parse(&edid).ok();
+            match read("status").as_deref().map(str::trim) {
+                Some("connected") => {}
+                Some("disconnected") => return None,
+                _ if info.is_some() => {}
+                _ => return None,
+            }
+
+            Some(DrmConnector {
+                card,
+                name: name.to_string(),
+                enabled: read("enabled").is_none_or(|enabled| enabled.trim() == "enabled"),
+                modes: read("modes")
+                    .map(|modes| parse_modes(&modes))
+                    .unwrap_or_default(),
+                edid,
+                info,
+            })
+        })
+        .collect()
+}
+
+/// Parse the `modes` file, one `<width>x<height>` per line with an `i` suffix
+/// for interlaced modes
+fn parse_modes(modes: &str) -> Vec<(u32, u32)> {
+    let mut parsed: Vec<(u32, u32)> = Vec::new();
+    for line in modes.lines() {
+        let Some((width, height)) = line.trim().split_once('x') else {
+            continue;
+        };
+        let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+        if let (Ok(width), Ok(height)) = (width.parse(), height.parse()) {
+            if !parsed.contains(&(width, height)) {
+                parsed.push((width, height));
+            }
+        }
+    }
+    parsed
+}
+
+/// The card number and connector of a `card<N>-<connector>` entry; other
+/// entries, such as `card0` itself or `renderD128`, have none
+fn parse_entry(entry: &str) -> Option<(u32, &str)> {
+    let rest = entry.strip_prefix("card")?;
+    let (card, connector) = rest.split_once('-')?;
+    if !card.bytes().all(|b| b.is_ascii_digit()) || connector.is_empty() {
+        return None;
+    }
+    Some((card.parse().ok()?, connector))
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    //! A fake sysfs tree for tests
+
+    use std::path::Path;
+
+    /// Add connector `entry` (e.g. `card0-DP-1`) with `edid` under `root`
+    pub(crate) fn add_connector(root: &Path, entry: &str, edid: &[u8]) {
+        let dir = root.join("class/drm").join(entry);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("edid"), edid).unwrap();
+    }
+
+    /// Add connector `entry` with all the files the kernel provides
+    pub(crate) fn add_full_connector(
+        root: &Path,
+        entry: &str,
+        status: &str,
+        enabled: bool,
+        modes: &[&str],
+        edid: &[u8],
+    ) {
+        add_connector(root, entry, edid);
+        let dir = root.join("class/drm").join(entry);
+        std::fs::write(dir.join("status"), format!("{}\n", status)).unwrap();
+        let enabled = if enabled { "enabled\n" } else { "disabled\n" };
+        std::fs::write(dir.join("enabled"), enabled).unwrap();
+        let modes: String = modes.iter().map(|mode| format!("{}\n", mode)).collect();
+        std::fs::write(dir.join("modes"), modes).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{add_connector, add_full_connector};
+    use super::*;
+    use crate::edid::test_support::{edid, TestMonitor};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_entry() {
+        assert_eq!(parse_entry("card0-DP-1"), Some((0, "DP-1")));
+        assert_eq!(parse_entry("card1-HDMI-A-2"), Some((1, "HDMI-A-2")));
+        assert_eq!(parse_entry("card0"), None);
+        assert_eq!(parse_entry("renderD128"), None);
+        assert_eq!(parse_entry("card-DP-1"), None);
+        assert_eq!(parse_entry("card+1-DP-1"), None);
+    }
+
+    #[test]
+    fn test_read_connectors_in_card_order() {
+        let root = TempDir::new().unwrap();
+        let monitor = edid(&TestMonitor::default(), &[]);
+        for entry in ["card10-DP-1", "card2-DP-1", "card2-DP-10", "card2-DP-2"] {
+            add_connector(root.path(), entry, &monitor);
+        }
+
+        let connectors: Vec<(u32, String)> = read_connectors(root.path())
+            .into_iter()
+            .map(|connector| (connector.card, connector.name))
+            .collect();
+        assert_eq!(
+            connectors,
+            [
+                (2, "DP-1".to_string()),
+                (2, "DP-10".to_string()),
+                (2, "DP-2".to_string()),
+                (10, "DP-1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_connectors() {
+        let root = TempDir::new().unwrap();
+        let external = edid(&TestMonitor::default(), &[]);
+        add_connector(root.path(), "card1-DP-2", &external);
+        add_connector(
+            root.path(),
+            "card1-eDP-1",
+            &edid(
+                &TestMonitor {
+                    manufacturer: "BOE",
+                    name: "NE135FBM-N41",
+                    ..TestMonitor::default()
+                },
+                &[],
+            ),
+        );
+        // Disconnected connectors have an empty EDID, and the card itself none
+        add_connector(root.path(), "card1-HDMI-A-1", &[]);
+        std::fs::create_dir_all(root.path().join("class/drm/card1")).unwrap();
+
+        let connectors = read_connectors(root.path());
+        assert_eq!(connectors.len(), 2);
+        assert_eq!(connectors[0].name, "DP-2");
+        assert_eq!(connectors[0].edid, external);
+        let info = connectors[0].info.as_ref().unwrap();
+        assert_eq!(info.name.as_deref(), Some("DELL U2720Q"));
+        assert!(connectors[0].enabled);
+        assert_eq!(connectors[1].name, "eDP-1");
+        assert_eq!(connectors[1].info.as_ref().unwrap().manufacturer, "BOE");
+        assert!(connectors[1].is_builtin() && !connectors[0].is_builtin());
+    }
+
+    #[test]
+    fn test_read_connector_state() {
+        let root = TempDir::new().unwrap();
+        let monitor = edid(&TestMonitor::default(), &[]);
+        add_full_connector(
+            root.path(),
+            "card0-DP-1",
+            "connected",
+            true,
+            &[
+                "3840x2160",
+                "2560x1440",
+                "1920x1080",
+                "1920x1080",
+                "1920x1080i",
+            ],
+            &monitor,
+        );
+        // Connected but switched off, and without an EDID
+        add_full_connector(root.path(), "card0-HDMI-A-1", "connected", false, &[], &[]);
+        // Unplugged, though the EDID of the last monitor lingers
+        add_full_connector(
+            root.path(),
+            "card0-DP-2",
+            "disconnected",
+            false,
+            &[],
+            &monitor,
+        );
+        // Undetectable, so only counted with an EDID
+        add_full_connector(root.path(), "card0-Virtual-1", "unknown", true, &[], &[]);
+
+        let connectors = read_connectors(root.path());
+        let names: Vec<&str> = connectors.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["DP-1", "HDMI-A-1"]);
+        assert_eq!(
+            connectors[0].modes,
+            [(3840, 2160), (2560, 1440), (1920, 1080)]
+        );
+        assert!(connectors[0].enabled && !connectors[1].enabled);
+        assert_eq!(connectors[1].info, None);
+        assert!(connectors[1].modes.is_empty());
+    }
+
+    #[test]
+    fn test_read_connectors_without_drm() {
+        let root = TempDir::new().unwrap();
+        assert_eq!(read_connectors(root.path()), []);
+    }
+}
@@ -0,0 +1,304 @@
+//! Choosing a profile for the file system fallback.
+//!
+//! Without colord, or for a display colord has no profile for, there is no
+//! record of which profile belongs to the display, and the profile
+//! directories hold printer, camera and abstract profiles as well. Only
+//! `mntr` class RGB profiles are considered, ranked by how well they fit the
+//! display: made for this very monitor (its EDID checksum in colord's `meta`
+//! tag or the `edid-<md5>.icc` file name), for its model (`dmdd`), for its
+//! manufacturer (`dmnd`), a standard sRGB profile, and last whatever display
+//! profile comes first in search order.
+
+use crate::{icc, md5, AssignedProfile, ColorSpace, Display, Edid, ProfileInfo};
+use std::path::{Path, PathBuf};
+
+/// Why a profile was chosen, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Reason {
+    /// Made for the monitor with this EDID
+    Edid,
+    /// Made for this monitor model
+    Model,
+    /// Made for monitors from this manufacturer
+    Manufacturer,
+    /// A standard sRGB profile
+    Srgb,
+    /// The first display profile found
+    First,
+}
+
+impl Reason {
+    /// Whether the profile was made for the monitor, its model or its
+    /// manufacturer, rather than being a stand-in
+    pub fn fits_monitor(self) -> bool {
+        self <= Self::Manufacturer
+    }
+
+    /// Short code for profile metadata
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Edid => "edid",
+            Self::Model => "model",
+            Self::Manufacturer => "manufacturer",
+            Self::Srgb => "srgb",
+            Self::First => "first",
+        }
+    }
+
+    /// Explanation for the profile description
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Edid => "made for this monitor, matched by its EDID",
+            Self::Model => "made for this monitor model",
+            Self::Manufacturer => "made for this monitor's manufacturer",
+            Self::Srgb => "standard sRGB, as no profile matches the monitor",
+            Self::First => "first display profile found, as none matches and there is no sRGB",
+        }
+    }
+}
+
+/// A display profile found on disk
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Candidate {
+    pub path: PathBuf,
+    /// Text of the `desc` tag
+    pub description: Option<String>,
+    /// Text of the `dmnd` tag
+    manufacturer: Option<String>,
+    /// Text of the `dmdd` tag
+    model: Option<String>,
+    /// EDID checksum the profile was made for
+    edid_md5: Option<String>,
+}
+
+impl Candidate {
+    /// Read `path`, keeping it only if it is an RGB display profile
+    pub fn read(path: &Path) -> Option<Self> {
+        let data = std::fs::read(path).ok()?;
+        if data.get(12..20)? != b"mntrRGB " || data.get(36..40)? != b"acsp" {
+            return None;
+        }
+
+        let tags = icc::TagTable::parse(&data).ok();
+        let text = |signature: &[u8; 4]| tags.as_ref()?.get(signature).and_then(icc::read_text);
+        let meta_md5 = tags
+            .as_ref()
+            .and_then(|tags| tags.get(b"meta"))
+            .and_then(icc::read_dict)
+            .and_then(|entries| {
+                entries
+                    .into_iter()
+                    .find(|(name, _)| name == "EDID_md5")
+                    .map(|(_, value)| value)
+            });
+        let file_md5 = path
+            .file_stem()
+            .and_then(|stem| stem.to_str()?.strip_prefix("edid-"))
+            .map(str::to_string);
+
+        Some(Self {
+            path: path.to_path_buf(),
+            description: text(b"desc"),
+            manufacturer: text(b"dmnd"),
+            model: text(b"dmdd"),
+            edid_md5: meta_md5.or(file_md5),
+        })
+    }
+
+    /// Profile information, explaining why the profile was chosen
+    pub fn info(self, reason: Reason) -> ProfileInfo {
+        let name = self.description.unwrap_or_else(|| {
+            self.path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown Profile")
+                .to_string()
+        });
+        ProfileInfo {
+            name,
+            description: Some(format!("File system fallback: {}", reason.describe())),
+            file_path: Some(self.path),
+            color_space: ColorSpace::RGB,
+        }
+    }
+
+    /// The profile as the display's only assigned profile, with the reason
+    /// it was chosen as metadata
+    pub fn assigned(self, reason: Reason) -> AssignedProfile {
+        let mut profile = AssignedProfile::new(self.info(reason), true);
+        profile.kind = Some("filesystem".to_string());
+        profile
+            .metadata
+            .insert("reason".to_string(), reason.code().to_string());
+        profile
+    }
+
+    /// How well the profile fits a monitor
+    fn rank(&self, monitor: &Monitor) -> Reason {
+        let matches = |tag: &Option<String>, wanted: &[&str]| {
+            tag.as_deref()
+                .is_some_and(|tag| wanted.iter().any(|w| tag.eq_ignore_ascii_case(w)))
+        };
+
+        if self.edid_md5.is_some()
+            && self.edid_md5.as_deref().map(str::to_ascii_lowercase) == monitor.edid_md5
+        {
+            Reason::Edid
+        } else if matches(&self.model, &monitor.models) {
+            Reason::Model
+        } else if matches(&self.manufacturer, &monitor.manufacturers) {
+            Reason::Manufacturer
+        } else if self.is_srgb() {
+            Reason::Srgb
+        } else {
+            Reason::First
+        }
+    }
+
+    /// Whether this is one of the standard sRGB profiles, such as
+    /// `sRGB IEC61966-2.1` or colord's `sRGB.icc`
+    fn is_srgb(&self) -> bool {
+        let stem = self.path.file_stem().and_then(|stem| stem.to_str());
+        [self.description.as_deref(), stem]
+            .into_iter()
+            .flatten()
+            .any(|name| name.to_ascii_lowercase().starts_with("srgb"))
+    }
+}
+
+/// What is known about the monitor a profile is wanted for
+struct Monitor<'a> {
+    edid_md5: Option<String>,
+    manufacturers: Vec<&'a str>,
+    models: Vec<&'a str>,
+}
+
+/// The RGB display profiles among `paths`, in the same order
+pub(super) fn candidates(paths: &[PathBuf]) -> Vec<Candidate> {
+    paths
+        .iter()
+        .filter_map(|path| Candidate::read(path))
+        .collect()
+}
+
+/// The best profile for `display` among `candidates`, with the reason it was
+/// chosen; earlier candidates win ties
+pub(super) fn choose<'a>(
+    candidates: &'a [Candidate],
+    display: &Display,
+) -> Option<(&'a Candidate, Reason)> {
+    let edid = display.edid().and_then(|data| Edid::parse(data).ok());
+    let mut monitor = Monitor {
+        edid_md5: display.edid().map(md5::hex),
+        manufacturers: display.vendor().into_iter().collect(),
+        models: display.model().into_iter().collect(),
+    };
+    if let Some(edid) = &edid {
+        monitor.manufacturers.push(&edid.manufacturer);
+        monitor.models.extend(edid.name.as_deref());
+    }
+
+    candidates
+        .iter()
+        .map(|candidate| (candidate, candidate.rank(&monitor)))
+        .min_by_key(|&(_, reason)| reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edid::test_support::{edid, TestMonitor};
+    use crate::icc::test_support::{dict, mluc, profile};
+    use tempfile::TempDir;
+
+    /// Write a profile with `tags` to `dir`, returning its path
+    fn write(dir: &Path, name: &str, tags: &[(&[u8; 4], Vec<u8>)]) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, profile(tags)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_candidates_are_rgb_display_profiles() {
+        let dir = TempDir::new().unwrap();
+        let display = write(dir.path(), "display.icc", &[(b"desc", mluc("Display"))]);
+        let printer = dir.path().join("printer.icc");
+        let mut data = profile(&[]);
+        data[12..20].copy_from_slice(b"prtrCMYK");
+        std::fs::write(&printer, data).unwrap();
+        let broken = dir.path().join("broken.icc");
+        std::fs::write(&broken, b"not a profile").unwrap();
+
+        let found = candidates(&[printer, broken, display.clone()]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, display);
+        assert_eq!(found[0].description.as_deref(), Some("Display"));
+    }
+
+    #[test]
+    fn test_choose_by_rank() {
+        let dir = TempDir::new().unwrap();
+        let dell = edid(&TestMonitor::default(), &[]);
+        let display =
+            Display::new("filesystem-fallback", "Generic Display", true).with_edid(dell.clone());
+
+        let adobe = write(dir.path(), "AdobeRGB.icc", &[(b"desc", mluc("Adobe RGB"))]);
+        let srgb = write(
+            dir.path(),
+            "sRGB.icc",
+            &[(b"desc", mluc("sRGB IEC61966-2.1"))],
+        );
+        let vendor = write(dir.path(), "dell.icc", &[(b"dmnd", mluc("del"))]);
+        let model = write(
+            dir.path(),
+            "u2720q.icc",
+            &[(b"dmnd", mluc("DEL")), (b"dmdd", mluc("DELL U2720Q"))],
+        );
+        let by_meta = write(
+            dir.path(),
+            "calibrated.icc",
+            &[(b"meta", dict(&[("EDID_md5", &md5::hex(&dell))]))],
+        );
+        let by_name = write(dir.path(), &format!("edid-{}.icc", md5::hex(&dell)), &[]);
+
+        let mut paths = vec![
+            adobe.clone(),
+            srgb.clone(),
+            vendor.clone(),
+            model.clone(),
+            by_meta.clone(),
+            by_name,
+        ];
+        let chosen = |paths: &[PathBuf], display: &Display| {
+            let found = candidates(paths);
+            choose(&found, display).map(|(candidate, reason)| (candidate.path.clone(), reason))
+        };
+
+        // The first profile made for this monitor wins, then lesser matches in turn
+        assert_eq!(chosen(&paths, &display), Some((by_meta, Reason::Edid)));
+        paths.truncate(4);
+        assert_eq!(chosen(&paths, &display), Some((model, Reason::Model)));
+        paths.truncate(3);
+        assert_eq!(
+            chosen(&paths, &display),
+            Some((vendor.clone(), Reason::Manufacturer))
+        );
+        paths.truncate(2);
+        assert_eq!(chosen(&paths, &display), Some((srgb.clone(), Reason::Srgb)));
+        paths.truncate(1);
+        assert_eq!(chosen(&paths, &display), Some((adobe, Reason::First)));
+        assert_eq!(chosen(&[], &display), None);
+
+        // Without an EDID, the display's own vendor and model are used
+        let generic = Display::new("filesystem-fallback", "Generic Display", true);
+        assert_eq!(
+            chosen(&[srgb.clone(), vendor.clone()], &generic),
+            Some((srgb, Reason::Srgb))
+        );
+        let named = generic.with_vendor("DEL");
+        assert_eq!(
+            chosen(std::slice::from_ref(&vendor), &named),
+            Some((vendor, Reason::Manufacturer))
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! User-maintained display to profile assignments.
+//!
+//! Without colord nothing records which profile belongs to which monitor, so
+//! displays found through DRM take theirs from a mapping file, by default
+//! `$XDG_CONFIG_HOME/display_icc/profiles.conf` (`~/.config/display_icc/profiles.conf`):
+//!
+//! ```text
+//! # <display> = <profile>
+//! DP-1 = dell-u2720q.icc
+//! drm-eDP-1-0f3c9a21 = /opt/calibration/panel.icc
+//! 3c9f5d0e8e4b2a7160f1d3b7c5a9e2f4 = projector.icc
+//! ```
+//!
+//! A display is named by its display ID, the MD5 of its EDID or its connector,
+//! and entries are matched in that order of precedence. Relative profile paths
+//! are looked up in the profile search directories.
+
+use crate::{search_path, ProfileError};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Parsed mapping file entries, in file order
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct ProfileMap {
+    entries: Vec<(String, PathBuf)>,
+}
+
+impl ProfileMap {
+    /// Read the mapping file at `path`; a missing file is an empty map
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text)
+                .map_err(|e| ProfileError::ParseError(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(ProfileError::IoError(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    /// Parse `<display> = <profile>` lines, skipping blank lines and `#` comments
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('=') {
+                Some((display, profile)) if !display.trim().is_empty() => {
+                    let profile = profile.trim();
+                    if profile.is_empty() {
+                        return Err(format!("line {}: no profile given", number + 1));
+                    }
+                    entries.push((display.trim().to_string(), PathBuf::from(profile)));
+                }
+                _ => {
+                    return Err(format!(
+                        "line {}: expected `<display> = <profile>`",
+                        number + 1
+                    ))
+                }
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    /// The profile for the first of `keys` (most specific first) with an
+    /// entry, ignoring case so that EDID checksums match in either
+    pub fn lookup(&self, keys: &[&str]) -> Option<&Path> {
+        keys.iter().find_map(|key| {
+            self.entries
+                .iter()
+                .find(|(display, _)| display.eq_ignore_ascii_case(key))
+                .map(|(_, profile)| profile.as_path())
+        })
+    }
+}
+
+/// Resolve a profile path from the mapping file: absolute paths as given,
+/// relative ones in the first of `search_paths` that has them
+pub(super) fn resolve_profile(profile: &Path, search_paths: &[PathBuf]) -> Option<PathBuf> {
+    if profile.is_absolute() {
+        return Some(profile.to_path_buf());
+    }
+    search_paths
+        .iter()
+        .map(|dir| dir.join(profile))
+        .find(|path| path.is_file())
+}
+
+/// The default mapping file, `$XDG_CONFIG_HOME/display_icc/profiles.conf`
+pub(super) fn default_path(env: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    search_path::xdg_home(env, "XDG_CONFIG_HOME", ".config")
+        .map(|config| config.join("display_icc/profiles.conf"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_and_lookup() {
+        let map = ProfileMap::parse(
+            "# Assigned by hand\n\
+             \n\
+             DP-1 = dell.icc\n\
+             drm-DP-1-0f3c9a21 = /opt/calibrated.icc\n  \
+             3C9F5D0E8E4B2A7160F1D3B7C5A9E2F4=projector.icc\n",
+        )
+        .unwrap();
+
+        // The display ID outranks the connector
+        assert_eq!(
+            map.lookup(&["drm-DP-1-0f3c9a21", "aaaa", "DP-1"]),
+            Some(Path::new("/opt/calibrated.icc"))
+        );
+        assert_eq!(
+            map.lookup(&["drm-DP-1-12345678", "aaaa", "DP-1"]),
+            Some(Path::new("dell.icc"))
+        );
+        assert_eq!(
+            map.lookup(&["drm-card0-HDMI-A-1", "3c9f5d0e8e4b2a7160f1d3b7c5a9e2f4"]),
+            Some(Path::new("projector.icc"))
+        );
+        assert_eq!(map.lookup(&["HDMI-A-2"]), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            ProfileMap::parse("DP-1 dell.icc"),
+            Err("line 1: expected `<display> = <profile>`".to_string())
+        );
+        assert_eq!(
+            ProfileMap::parse("# ok\nDP-1 =\n"),
+            Err("line 2: no profile given".to_string())
+        );
+    }
+
+    #[test]
+    fn test_load_and_resolve() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            ProfileMap::load(&dir.path().join("missing.conf")).unwrap(),
+            ProfileMap::default()
+        );
+
+        let store = dir.path().join("icc");
+        std::fs::create_dir(&store).unwrap();
+        std::fs::write(store.join("dell.icc"), b"").unwrap();
+        let search_paths = [dir.path().join("empty"), store.clone()];
+        assert_eq!(
+            resolve_profile(Path::new("dell.icc"), &search_paths),
+            Some(store.join("dell.icc"))
+        );
+        assert_eq!(resolve_profile(Path::new("gone.icc"), &search_paths), None);
+        assert_eq!(
+            resolve_profile(Path::new("/opt/panel.icc"), &search_paths),
+            Some(PathBuf::from("/opt/panel.icc"))
+        );
+    }
+
+    #[test]
+    fn test_default_path() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert_eq!(
+            default_path(env(&[("HOME", "/home/alice")])),
+            Some(PathBuf::from(
+                "/home/alice/.config/display_icc/profiles.conf"
+            ))
+        );
+        assert_eq!(
+            default_path(env(&[("HOME", "/home/alice"), ("XDG_CONFIG_HOME", "/cfg")])),
+            Some(PathBuf::from("/cfg/display_icc/profiles.conf"))
+        );
+        assert_eq!(default_path(env(&[("XDG_CONFIG_HOME", "relative")])), None);
+    }
+}
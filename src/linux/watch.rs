@@ -0,0 +1,365 @@
+//! Display and profile change notifications from colord's D-Bus signals.
+//!
+//! colord announces changes on its manager object: `DeviceAdded` and
+//! `DeviceRemoved` for hotplug, `DeviceChanged` when a device's properties
+//! (including its profile list) change, and `ProfileChanged` when a profile
+//! is modified. A thread turns these into [`DisplayEvent`]s, keeping its own
+//! view of the displays so that removals can report the display that went away.
+
+use super::colord::{ColordClient, ColordSignal};
+use super::colormgr::ColormgrDevice;
+use super::{device_to_display, is_colord_primary};
+use crate::{Display, DisplayEvent, PrimarySource, ProfileConfig, ProfileError, ProfileWatcher};
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+
+/// Start watching colord over `client`, which the watcher takes over
+pub(super) fn watch(
+    client: ColordClient,
+    config: &ProfileConfig,
+) -> Result<ProfileWatcher, ProfileError> {
+    // Subscribe before the snapshot so that no change falls in between
+    let signals = client.signals()?;
+
+    let mut state = WatchState {
+        client: client.clone(),
+        primary_display: config.primary_display.clone(),
+        displays: BTreeMap::new(),
+    };
+    for device in client.get_devices()? {
+        state.track(device);
+    }
+
+    let (sender, events) = mpsc::channel();
+    let thread = thread::Builder::new()
+        .name("display_icc colord watch".to_string())
+        .spawn(move || {
+            for signal in signals {
+                for event in state.handle(signal) {
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        })?;
+
+    Ok(ProfileWatcher::new(
+        events,
+        Stop {
+            client,
+            thread: Some(thread),
+        },
+    ))
+}
+
+/// Stops the watch thread when the watcher is dropped
+struct Stop {
+    client: ColordClient,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Stop {
+    fn drop(&mut self) {
+        // Closing the connection ends the signal stream and with it the thread
+        self.client.close();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A display as last seen, with the IDs of its profiles
+struct Tracked {
+    display: Display,
+    profiles: Vec<String>,
+    /// Position among the displays when first tracked, which numbers the
+    /// display if it has no model name
+    index: usize,
+}
+
+struct WatchState {
+    client: ColordClient,
+    primary_display: Option<String>,
+    /// Displays by colord object path
+    displays: BTreeMap<String, Tracked>,
+}
+
+impl WatchState {
+    /// Start tracking `device` if it is a display, returning the display
+    fn track(&mut self, device: ColormgrDevice) -> Option<Display> {
+        if !device.is_display() {
+            return None;
+        }
+
+        let index = self.displays.len();
+        let display = self.to_display(&device, index);
+        self.displays.insert(
+            device.object_path.clone(),
+            Tracked {
+                display: display.clone(),
+                profiles: device.profiles,
+                index,
+            },
+        );
+        Some(display)
+    }
+
+    /// Turn `signal` into events, ending with `PrimaryChanged` when it moved
+    /// the primary display
+    fn handle(&mut self, signal: ColordSignal) -> Vec<DisplayEvent> {
+        let primary = self.primary().map(|display| display.id.clone());
+        let mut events = self.changes(signal);
+        if let Some(display) = self.primary() {
+            if primary.as_ref() != Some(&display.id) {
+                events.push(DisplayEvent::PrimaryChanged(display.clone()));
+            }
+        }
+        events
+    }
+
+    /// The display marked as primary, if any
+    fn primary(&self) -> Option<&Display> {
+        self.displays
+            .values()
+            .map(|tracked| &tracked.display)
+            .find(|display| display.is_primary)
+    }
+
+    fn changes(&mut self, signal: ColordSignal) -> Vec<DisplayEvent> {
+        match signal {
+            ColordSignal::DeviceAdded(path) => {
+                if self.displays.contains_key(path.as_str()) {
+                    return Vec::new();
+                }
+                self.client
+                    .get_device(&path)
+                    .ok()
+                    .and_then(|device| self.track(device))
+                    .map(DisplayEvent::Added)
+                    .into_iter()
+                    .collect()
+            }
+            ColordSignal::DeviceRemoved(path) => self
+                .displays
+                .remove(path.as_str())
+                .map(|tracked| DisplayEvent::Removed(tracked.display))
+                .into_iter()
+                .collect(),
+            ColordSignal::DeviceChanged(path) => {
+                let Some(tracked) = self.displays.get(path.as_str()) else {
+                    return Vec::new();
+                };
+                let Ok(device) = self.client.get_device(&path) else {
+                    return Vec::new();
+                };
+                // Metadata such as the output priority may change on its own
+                let profiles_changed = device.profiles != tracked.profiles;
+                let index = tracked.index;
+
+                let display = self.to_display(&device, index);
+                self.displays.insert(
+                    device.object_path.clone(),
+                    Tracked {
+                        display: display.clone(),
+                        profiles: device.profiles,
+                        index,
+                    },
+                );
+                if profiles_changed {
+                    vec![DisplayEvent::ProfileChanged(display)]
+                } else {
+                    Vec::new()
+                }
+            }
+            ColordSignal::ProfileChanged(path) => {
+                let Ok(profile_id) = self.client.profile_id(&path) else {
+                    return Vec::new();
+                };
+                self.displays
+                    .values()
+                    .filter(|tracked| tracked.profiles.contains(&profile_id))
+                    .map(|tracked| DisplayEvent::ProfileChanged(tracked.display.clone()))
+                    .collect()
+            }
+        }
+    }
+
+    /// Convert `device`, marking it primary from the configured override or,
+    /// without one, colord metadata
+    fn to_display(&self, device: &ColormgrDevice, index: usize) -> Display {
+        let display = device_to_display(device, index);
+        let source = match &self.primary_display {
+            Some(wanted) => (&display.id == wanted || display.connector() == Some(wanted))
+                .then_some(PrimarySource::Config),
+            None => is_colord_primary(device).then_some(PrimarySource::Colord),
+        };
+        match source {
+            Some(source) => display.with_primary_source(source),
+            None => display,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::colord::stub::{StubColord, StubDevice, StubProfile, TestBus};
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn monitor(id: &str, model: &str) -> StubDevice {
+        StubDevice {
+            id: id.to_string(),
+            kind: "display".to_string(),
+            model: model.to_string(),
+            vendor: "Dell".to_string(),
+            metadata: HashMap::from([("XRANDR_name".to_string(), "DP-1".to_string())]),
+            ..StubDevice::default()
+        }
+    }
+
+    fn profile(id: &str) -> StubProfile {
+        StubProfile {
+            id: id.to_string(),
+            filename: format!("/usr/share/color/icc/{}.icc", id),
+            title: id.to_string(),
+            ..StubProfile::display()
+        }
+    }
+
+    fn watch_stub(bus: &TestBus, config: &ProfileConfig) -> ProfileWatcher {
+        let client = ColordClient::connect(bus.address(), TIMEOUT).unwrap();
+        watch(client, config).unwrap()
+    }
+
+    #[test]
+    fn test_watch_hotplug() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let stub = StubColord::serve(&bus, vec![monitor("xrandr-dell-1", "U2720Q")]).unwrap();
+        let watcher = watch_stub(&bus, &ProfileConfig::default());
+
+        let mut webcam = monitor("webcam", "C920");
+        webcam.kind = "webcam".to_string();
+        stub.add_device(webcam).unwrap();
+        stub.add_device(monitor("xrandr-dell-2", "P2419H")).unwrap();
+
+        match watcher.recv_timeout(TIMEOUT) {
+            Some(DisplayEvent::Added(display)) => {
+                assert_eq!(display.id, "xrandr-dell-2");
+                assert_eq!(display.name, "Dell P2419H");
+                assert_eq!(display.connector(), Some("DP-1"));
+            }
+            other => panic!("expected Added, got {:?}", other),
+        }
+
+        stub.remove_device("xrandr-dell-1").unwrap();
+        match watcher.recv_timeout(TIMEOUT) {
+            Some(DisplayEvent::Removed(display)) => {
+                assert_eq!(display.id, "xrandr-dell-1");
+                assert_eq!(display.name, "Dell U2720Q");
+            }
+            other => panic!("expected Removed, got {:?}", other),
+        }
+        assert_eq!(watcher.try_recv(), None);
+    }
+
+    #[test]
+    fn test_watch_profile_changes() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let mut laptop = monitor("xrandr-laptop", "Panel");
+        laptop.profiles = vec![profile("icc-laptop")];
+        let stub = StubColord::serve(&bus, vec![laptop, monitor("xrandr-dell", "U2720Q")]).unwrap();
+        let watcher = watch_stub(
+            &bus,
+            &ProfileConfig {
+                primary_display: Some("xrandr-dell".to_string()),
+                ..ProfileConfig::default()
+            },
+        );
+
+        stub.assign_profile("xrandr-dell", profile("icc-dell"))
+            .unwrap();
+        match watcher.recv_timeout(TIMEOUT) {
+            Some(DisplayEvent::ProfileChanged(display)) => {
+                assert_eq!(display.id, "xrandr-dell");
+                assert!(display.is_primary);
+                assert_eq!(display.primary_source(), Some(PrimarySource::Config));
+            }
+            other => panic!("expected ProfileChanged, got {:?}", other),
+        }
+
+        stub.touch_profile("icc-laptop").unwrap();
+        match watcher.recv_timeout(TIMEOUT) {
+            Some(DisplayEvent::ProfileChanged(display)) => {
+                assert_eq!(display.id, "xrandr-laptop");
+                assert!(!display.is_primary);
+            }
+            other => panic!("expected ProfileChanged, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_watch_keeps_display_numbers() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        // Object paths sort the other way round from colord's device order
+        let stub = StubColord::serve(&bus, vec![monitor("xrandr-b", ""), monitor("xrandr-a", "")])
+            .unwrap();
+        let watcher = watch_stub(&bus, &ProfileConfig::default());
+
+        stub.assign_profile("xrandr-b", profile("icc-b")).unwrap();
+        match watcher.recv_timeout(TIMEOUT) {
+            Some(DisplayEvent::ProfileChanged(display)) => {
+                assert_eq!(display.id, "xrandr-b");
+                assert_eq!(display.name, "Display 1");
+            }
+            other => panic!("expected ProfileChanged, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_watch_primary_changes() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let mut laptop = monitor("xrandr-laptop", "Panel");
+        laptop
+            .metadata
+            .insert("OutputPriority".to_string(), "primary".to_string());
+        let stub = StubColord::serve(&bus, vec![laptop, monitor("xrandr-dell", "U2720Q")]).unwrap();
+        let watcher = watch_stub(&bus, &ProfileConfig::default());
+
+        // colord moves the priority one device at a time
+        stub.set_metadata("xrandr-laptop", "OutputPriority", "other")
+            .unwrap();
+        stub.set_metadata("xrandr-dell", "OutputPriority", "primary")
+            .unwrap();
+        match watcher.recv_timeout(TIMEOUT) {
+            Some(DisplayEvent::PrimaryChanged(display)) => {
+                assert_eq!(display.id, "xrandr-dell");
+                assert_eq!(display.primary_source(), Some(PrimarySource::Colord));
+            }
+            other => panic!("expected PrimaryChanged, got {:?}", other),
+        }
+        // Metadata changes are not profile changes
+        assert_eq!(watcher.recv_timeout(Duration::from_millis(100)), None);
+    }
+
+    #[test]
+    fn test_watch_stops_on_drop() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let _stub = StubColord::serve(&bus, Vec::new()).unwrap();
+        // Dropping joins the watch thread, so this returning at all is the test
+        drop(watch_stub(&bus, &ProfileConfig::default()));
+    }
+}
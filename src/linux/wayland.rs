@@ -0,0 +1,734 @@
+//! Output image descriptions from the Wayland `wp_color_management_v1` protocol.
+//!
+//! On Wayland sessions the compositor decides how each output's colours are
+//! interpreted, so its image description is the authoritative one. For every
+//! `wl_output` the colour manager hands out the output's image description,
+//! whose information is either the ICC profile the compositor uses, passed as a
+//! file descriptor, or a parametric description made of primaries, a transfer
+//! function and luminances.
+
+use crate::{icc, ColorSpace, ProfileError, ProfileInfo};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use wayland_client::protocol::{wl_callback, wl_output, wl_registry};
+use wayland_client::{
+    backend::WaylandError, Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::color_management::v1::client::{
+    wp_color_management_output_v1::{self, WpColorManagementOutputV1},
+    wp_color_manager_v1::{self, WpColorManagerV1},
+    wp_image_description_info_v1::{self, WpImageDescriptionInfoV1},
+    wp_image_description_v1::{self, WpImageDescriptionV1},
+};
+
+/// How often the socket is checked for events while waiting on the compositor
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A `wl_output` and its image description
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct WaylandOutput {
+    /// Connector name from `wl_output.name` (version 4), e.g. `DP-1`
+    pub name: Option<String>,
+    /// Compositor's description of the output, e.g. `Dell Inc. DELL U2720Q (DP-1)`
+    pub description: Option<String>,
+    /// Manufacturer from `wl_output.geometry`
+    pub make: Option<String>,
+    /// Model from `wl_output.geometry`
+    pub model: Option<String>,
+    /// Size of the current mode in pixels
+    pub resolution: Option<(u32, u32)>,
+    /// Position in the compositor's global space from `wl_output.geometry`
+    pub position: Option<(i32, i32)>,
+    /// Integer scale factor from `wl_output.scale` (version 2)
+    pub scale: Option<i32>,
+    /// Refresh rate of the current mode in Hz
+    pub refresh_rate: Option<f64>,
+    /// The output's image description, or why the compositor had none to give
+    pub image: Result<ImageDescription, String>,
+}
+
+/// The information of an image description, with the protocol's fixed-point
+/// values converted to chromaticities and cd/m²
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct ImageDescription {
+    /// ICC profile data, when the description was created from one
+    pub icc: Option<Vec<u8>>,
+    /// Red, green, blue and white point chromaticities as `[x, y]`
+    pub primaries: Option<[[f64; 2]; 4]>,
+    /// Well-known primaries matching `primaries`, as a protocol enum value
+    pub primaries_named: Option<u32>,
+    /// Well-known transfer function, as a protocol enum value
+    pub tf_named: Option<u32>,
+    /// Exponent of a pure power transfer function
+    pub tf_power: Option<f64>,
+    /// Minimum, maximum and reference white luminance
+    pub luminances: Option<[f64; 3]>,
+    /// Primaries of the display the content is targeted at
+    pub target_primaries: Option<[[f64; 2]; 4]>,
+    /// Minimum and maximum luminance of the target display
+    pub target_luminance: Option<[f64; 2]>,
+    /// Maximum content light level in cd/m²
+    pub target_max_cll: Option<u32>,
+    /// Maximum frame-average light level in cd/m²
+    pub target_max_fall: Option<u32>,
+}
+
+impl ImageDescription {
+    /// Describe the image description of `output` (a display name)
+    pub fn info(&self, output: &str) -> ProfileInfo {
+        if let Some(data) = &self.icc {
+            return ProfileInfo {
+                description: Some(format!("Wayland image description of {}", output)),
+                ..icc::profile_info(data, None)
+            };
+        }
+
+        ProfileInfo {
+            name: self.summary(),
+            description: Some(format!(
+                "Wayland parametric image description of {}",
+                output
+            )),
+            file_path: None,
+            color_space: ColorSpace::RGB,
+        }
+    }
+
+    /// Name a parametric description after its primaries and transfer function,
+    /// e.g. `BT.2020 PQ`
+    fn summary(&self) -> String {
+        let primaries = match self.primaries_named {
+            Some(value) => primaries_name(value)
+                .map(|(_, label)| label.to_string())
+                .unwrap_or_else(|| format!("primaries {}", value)),
+            None => "Custom primaries".to_string(),
+        };
+        let transfer = match (self.tf_named, self.tf_power) {
+            (Some(value), _) => tf_name(value)
+                .map(|(_, label)| label.to_string())
+                .unwrap_or_else(|| format!("transfer function {}", value)),
+            (None, Some(power)) => format!("gamma {}", power),
+            (None, None) => return primaries,
+        };
+        format!("{} {}", primaries, transfer)
+    }
+
+    /// The description's values as profile metadata, keyed by the event that
+    /// carried them
+    pub fn metadata(&self) -> BTreeMap<String, String> {
+        let mut metadata = BTreeMap::new();
+        let mut insert = |key: &str, value: String| {
+            metadata.insert(key.to_string(), value);
+        };
+
+        if let Some(primaries) = &self.primaries {
+            insert("primaries", format_primaries(primaries));
+        }
+        if let Some(value) = self.primaries_named {
+            insert("primaries_named", enum_name(primaries_name(value), value));
+        }
+        if let Some(value) = self.tf_named {
+            insert("tf_named", enum_name(tf_name(value), value));
+        }
+        if let Some(power) = self.tf_power {
+            insert("tf_power", power.to_string());
+        }
+        if let Some([min, max, reference]) = self.luminances {
+            insert("luminances", format!("{} {} {}", min, max, reference));
+        }
+        if let Some(primaries) = &self.target_primaries {
+            insert("target_primaries", format_primaries(primaries));
+        }
+        if let Some([min, max]) = self.target_luminance {
+            insert("target_luminance", format!("{} {}", min, max));
+        }
+        if let Some(max_cll) = self.target_max_cll {
+            insert("target_max_cll", max_cll.to_string());
+        }
+        if let Some(max_fall) = self.target_max_fall {
+            insert("target_max_fall", max_fall.to_string());
+        }
+        metadata
+    }
+}
+
+/// Protocol name and label of a `wp_color_manager_v1.primaries` value
+fn primaries_name(value: u32) -> Option<(&'static str, &'static str)> {
+    Some(match value {
+        1 => ("srgb", "sRGB"),
+        2 => ("pal_m", "PAL-M"),
+        3 => ("pal", "PAL"),
+        4 => ("ntsc", "NTSC"),
+        5 => ("generic_film", "Generic film"),
+        6 => ("bt2020", "BT.2020"),
+        7 => ("cie1931_xyz", "CIE 1931 XYZ"),
+        8 => ("dci_p3", "DCI-P3"),
+        9 => ("display_p3", "Display P3"),
+        10 => ("adobe_rgb", "Adobe RGB"),
+        _ => return None,
+    })
+}
+
+/// Protocol name and label of a `wp_color_manager_v1.transfer_function` value
+fn tf_name(value: u32) -> Option<(&'static str, &'static str)> {
+    Some(match value {
+        1 => ("bt1886", "BT.1886"),
+        2 => ("gamma22", "gamma 2.2"),
+        3 => ("gamma28", "gamma 2.8"),
+        4 => ("st240", "SMPTE ST 240"),
+        5 => ("ext_linear", "linear"),
+        6 => ("log_100", "log 100:1"),
+        7 => ("log_316", "log 316:1"),
+        8 => ("xvycc", "xvYCC"),
+        9 => ("srgb", "sRGB"),
+        10 => ("ext_srgb", "extended sRGB"),
+        11 => ("st2084_pq", "PQ"),
+        12 => ("st428", "SMPTE ST 428"),
+        13 => ("hlg", "HLG"),
+        14 => ("compound_power_2_4", "compound power 2.4"),
+        _ => return None,
+    })
+}
+
+/// The protocol name of an enum value, or the number for values newer than this code
+fn enum_name(name: Option<(&str, &str)>, value: u32) -> String {
+    name.map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Format chromaticities as `x,y` pairs in red, green, blue, white order
+fn format_primaries(primaries: &[[f64; 2]; 4]) -> String {
+    primaries
+        .iter()
+        .map(|[x, y]| format!("{},{}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Chromaticities from the protocol's `x * 1M` integers
+fn chromaticities(values: [i32; 8]) -> [[f64; 2]; 4] {
+    let c = |i: usize| f64::from(values[i]) / 1_000_000.0;
+    [[c(0), c(1)], [c(2), c(3)], [c(4), c(5)], [c(6), c(7)]]
+}
+
+fn enum_value<T: Into<u32>>(value: WEnum<T>) -> u32 {
+    match value {
+        WEnum::Value(value) => value.into(),
+        WEnum::Unknown(value) => value,
+    }
+}
+
+/// Read the outputs of the compositor listening on `socket`, or of
+/// `$WAYLAND_DISPLAY` when `None`, waiting at most `timeout` for its replies
+pub(super) fn read_outputs(
+    socket: Option<&Path>,
+    timeout: Duration,
+) -> Result<Vec<WaylandOutput>, ProfileError> {
+    let connection = match socket {
+        Some(path) => UnixStream::connect(path)
+            .map_err(|e| e.to_string())
+            .and_then(|stream| Connection::from_socket(stream).map_err(|e| e.to_string())),
+        None => Connection::connect_to_env().map_err(|e| e.to_string()),
+    }
+    .map_err(|e| ProfileError::SystemError(format!("Failed to connect to Wayland: {}", e)))?;
+
+    let queue = connection.new_event_queue();
+    let mut client = Client {
+        handle: queue.handle(),
+        connection,
+        queue,
+        state: State::default(),
+        deadline: Instant::now() + timeout,
+    };
+    client.read_outputs()
+}
+
+struct Client {
+    connection: Connection,
+    queue: EventQueue<State>,
+    handle: QueueHandle<State>,
+    state: State,
+    deadline: Instant,
+}
+
+impl Client {
+    fn read_outputs(&mut self) -> Result<Vec<WaylandOutput>, ProfileError> {
+        // Globals are bound as they are announced, and the outputs describe
+        // themselves in reply to the bind
+        self.connection.display().get_registry(&self.handle, ());
+        self.roundtrip()?;
+        self.roundtrip()?;
+
+        let Some(manager) = self.state.manager.clone() else {
+            return Err(ProfileError::ProfileNotAvailable(
+                "The compositor does not support wp_color_management_v1".to_string(),
+            ));
+        };
+
+        let mut requested = Vec::new();
+        for (index, output) in self.state.outputs.iter().enumerate() {
+            let color_output = manager.get_output(&output.proxy, &self.handle, index);
+            let description = color_output.get_image_description(&self.handle, index);
+            requested.push((color_output, description));
+        }
+
+        // The compositor may take its time to make a description ready
+        self.wait(|state| state.outputs.iter().all(|output| output.image.is_some()))?;
+
+        for (index, (_, description)) in requested.iter().enumerate() {
+            if matches!(self.state.outputs[index].image, Some(Ok(_))) {
+                description.get_information(&self.handle, index);
+            }
+        }
+        self.roundtrip()?;
+
+        for (color_output, description) in requested {
+            description.destroy();
+            color_output.destroy();
+        }
+        manager.destroy();
+        let _ = self.connection.flush();
+
+        Ok(self
+            .state
+            .outputs
+            .drain(..)
+            .map(|output| WaylandOutput {
+                name: output.name,
+                description: output.description,
+                make: output.make,
+                model: output.model,
+                resolution: output.resolution,
+                position: output.position,
+                scale: output.scale,
+                refresh_rate: output.refresh_rate,
+                image: output
+                    .image
+                    .unwrap_or_else(|| Err("The compositor sent no image description".to_string())),
+            })
+            .collect())
+    }
+
+    /// Wait until the compositor has handled every request sent so far
+    fn roundtrip(&mut self) -> Result<(), ProfileError> {
+        self.state.synced = false;
+        self.connection.display().sync(&self.handle, ());
+        self.wait(|state| state.synced)
+    }
+
+    /// Dispatch events until `done` holds, polling rather than blocking so
+    /// that an unresponsive compositor runs into the deadline
+    fn wait(&mut self, done: impl Fn(&State) -> bool) -> Result<(), ProfileError> {
+        loop {
+            self.queue
+                .dispatch_pending(&mut self.state)
+                .map_err(|e| wayland_error(e.to_string()))?;
+            if done(&self.state) {
+                return Ok(());
+            }
+
+            self.queue
+                .flush()
+                .map_err(|e| wayland_error(e.to_string()))?;
+            if let Some(guard) = self.queue.prepare_read() {
+                match guard.read() {
+                    Ok(_) => continue,
+                    Err(WaylandError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(wayland_error(e.to_string())),
+                }
+            }
+
+            if Instant::now() >= self.deadline {
+                return Err(ProfileError::Timeout(
+                    "The Wayland compositor did not reply in time".to_string(),
+                ));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+fn wayland_error(message: String) -> ProfileError {
+    ProfileError::SystemError(format!("Wayland connection failed: {}", message))
+}
+
+#[derive(Default)]
+struct State {
+    manager: Option<WpColorManagerV1>,
+    outputs: Vec<OutputState>,
+    synced: bool,
+}
+
+struct OutputState {
+    proxy: wl_output::WlOutput,
+    name: Option<String>,
+    description: Option<String>,
+    make: Option<String>,
+    model: Option<String>,
+    resolution: Option<(u32, u32)>,
+    position: Option<(i32, i32)>,
+    scale: Option<i32>,
+    refresh_rate: Option<f64>,
+    /// `None` until the image description is ready or has failed
+    image: Option<Result<ImageDescription, String>>,
+}
+
+impl OutputState {
+    /// The description being filled in by information events, if it is ready
+    fn description(&mut self) -> Option<&mut ImageDescription> {
+        self.image.as_mut()?.as_mut().ok()
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for State {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        handle: &QueueHandle<Self>,
+    ) {
+        let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        else {
+            return;
+        };
+
+        if interface == wl_output::WlOutput::interface().name {
+            // Version 4 adds the connector name
+            let index = state.outputs.len();
+            let proxy = registry.bind(name, version.min(4), handle, index);
+            state.outputs.push(OutputState {
+                proxy,
+                name: None,
+                description: None,
+                make: None,
+                model: None,
+                resolution: None,
+                position: None,
+                scale: None,
+                refresh_rate: None,
+                image: None,
+            });
+        } else if interface == WpColorManagerV1::interface().name {
+            state.manager = Some(registry.bind(name, version.min(2), handle, ()));
+        }
+    }
+}
+
+impl Dispatch<wl_callback::WlCallback, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &wl_callback::WlCallback,
+        event: wl_callback::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_callback::Event::Done { .. } = event {
+            state.synced = true;
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &wl_output::WlOutput,
+        event: wl_output::Event,
+        &index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let output = &mut state.outputs[index];
+        match event {
+            wl_output::Event::Geometry {
+                x, y, make, model, ..
+            } => {
+                output.position = Some((x, y));
+                // Compositors without EDID information report "Unknown"
+                output.make = Some(make).filter(|make| !make.is_empty() && make != "Unknown");
+                output.model = Some(model).filter(|model| !model.is_empty() && model != "Unknown");
+            }
+            wl_output::Event::Mode {
+                flags,
+                width,
+                height,
+                refresh,
+            } => {
+                let current = match flags {
+                    WEnum::Value(flags) => flags.contains(wl_output::Mode::Current),
+                    WEnum::Unknown(_) => false,
+                };
+                if current {
+                    output.resolution = Some((width.max(0) as u32, height.max(0) as u32));
+                    // The refresh rate is in mHz, and 0 when it does not apply
+                    output.refresh_rate = (refresh > 0).then(|| f64::from(refresh) / 1000.0);
+                }
+            }
+            wl_output::Event::Scale { factor } => output.scale = Some(factor),
+            wl_output::Event::Name { name } => output.name = Some(name),
+            wl_output::Event::Description { description } => output.description = Some(description),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WpColorManagerV1, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &WpColorManagerV1,
+        _: wp_color_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // Supported features only matter for creating image descriptions
+    }
+}
+
+impl Dispatch<WpColorManagementOutputV1, usize> for State {
+    fn event(
+        _: &mut Self,
+        _: &WpColorManagementOutputV1,
+        _: wp_color_management_output_v1::Event,
+        _: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // image_description_changed: the next read picks up the new description
+    }
+}
+
+impl Dispatch<WpImageDescriptionV1, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &WpImageDescriptionV1,
+        event: wp_image_description_v1::Event,
+        &index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let output = &mut state.outputs[index];
+        match event {
+            wp_image_description_v1::Event::Ready { .. }
+            | wp_image_description_v1::Event::Ready2 { .. } => {
+                output.image = Some(Ok(ImageDescription::default()))
+            }
+            wp_image_description_v1::Event::Failed { cause, msg } => {
+                let cause = match cause {
+                    WEnum::Value(wp_image_description_v1::Cause::Unsupported) => "unsupported",
+                    WEnum::Value(wp_image_description_v1::Cause::OperatingSystem) => {
+                        "operating system error"
+                    }
+                    WEnum::Value(wp_image_description_v1::Cause::NoOutput) => "no output",
+                    _ => "failed",
+                };
+                output.image = Some(Err(format!("Image description {}: {}", cause, msg)));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WpImageDescriptionInfoV1, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &WpImageDescriptionInfoV1,
+        event: wp_image_description_info_v1::Event,
+        &index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        use wp_image_description_info_v1::Event;
+
+        let output = &mut state.outputs[index];
+        if let Event::IccFile { icc, icc_size } = event {
+            // The file is read from the start, whatever the descriptor's offset
+            let mut data = vec![0; icc_size as usize];
+            if let Err(e) = File::from(icc).read_exact_at(&mut data, 0) {
+                output.image = Some(Err(format!("Failed to read the ICC file: {}", e)));
+                return;
+            }
+            if let Some(description) = output.description() {
+                description.icc = Some(data);
+            }
+            return;
+        }
+
+        let Some(description) = output.description() else {
+            return;
+        };
+        match event {
+            Event::Primaries {
+                r_x,
+                r_y,
+                g_x,
+                g_y,
+                b_x,
+                b_y,
+                w_x,
+                w_y,
+            } => {
+                description.primaries =
+                    Some(chromaticities([r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y]))
+            }
+            Event::PrimariesNamed { primaries } => {
+                description.primaries_named = Some(enum_value(primaries))
+            }
+            Event::TfPower { eexp } => description.tf_power = Some(f64::from(eexp) / 10_000.0),
+            Event::TfNamed { tf } => description.tf_named = Some(enum_value(tf)),
+            Event::Luminances {
+                min_lum,
+                max_lum,
+                reference_lum,
+            } => {
+                description.luminances = Some([
+                    f64::from(min_lum) / 10_000.0,
+                    f64::from(max_lum),
+                    f64::from(reference_lum),
+                ])
+            }
+            Event::TargetPrimaries {
+                r_x,
+                r_y,
+                g_x,
+                g_y,
+                b_x,
+                b_y,
+                w_x,
+                w_y,
+            } => {
+                description.target_primaries =
+                    Some(chromaticities([r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y]))
+            }
+            Event::TargetLuminance { min_lum, max_lum } => {
+                description.target_luminance =
+                    Some([f64::from(min_lum) / 10_000.0, f64::from(max_lum)])
+            }
+            Event::TargetMaxCll { max_cll } => description.target_max_cll = Some(max_cll),
+            Event::TargetMaxFall { max_fall } => description.target_max_fall = Some(max_fall),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod stub;
+
+#[cfg(test)]
+mod tests {
+    use super::stub::{StandIn, StubImage, StubOutput, StubParams};
+    use super::*;
+    use crate::icc::test_support::{mluc, profile};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_read_icc_description() {
+        let data = profile(&[(b"desc", mluc("Compositor profile"))]);
+        let compositor = StandIn::serve(vec![StubOutput {
+            image: StubImage::Icc(data.clone()),
+            ..StubOutput::new("DP-1")
+        }]);
+
+        let outputs = read_outputs(Some(compositor.socket()), TIMEOUT).unwrap();
+        assert_eq!(outputs.len(), 1);
+        let output = &outputs[0];
+        assert_eq!(output.name.as_deref(), Some("DP-1"));
+        assert_eq!(output.make.as_deref(), Some("Dell Inc."));
+        assert_eq!(output.model.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(output.resolution, Some((3840, 2160)));
+        assert_eq!(output.position, Some((0, 0)));
+        assert_eq!(output.scale, Some(2));
+        assert_eq!(output.refresh_rate, Some(60.0));
+
+        let image = output.image.as_ref().unwrap();
+        assert_eq!(image.icc.as_deref(), Some(&data[..]));
+        let info = image.info("DP-1");
+        assert_eq!(info.name, "Compositor profile");
+        assert_eq!(info.color_space, ColorSpace::RGB);
+        assert_eq!(info.file_path, None);
+    }
+
+    #[test]
+    fn test_read_parametric_description() {
+        let compositor = StandIn::serve(vec![
+            StubOutput {
+                image: StubImage::Parametric(StubParams::srgb()),
+                ..StubOutput::new("eDP-1")
+            },
+            StubOutput {
+                image: StubImage::Parametric(StubParams::bt2100_pq()),
+                ..StubOutput::new("HDMI-A-1")
+            },
+        ]);
+
+        let outputs = read_outputs(Some(compositor.socket()), TIMEOUT).unwrap();
+        assert_eq!(outputs.len(), 2);
+
+        let srgb = outputs[0].image.as_ref().unwrap();
+        assert_eq!(srgb.icc, None);
+        assert_eq!(srgb.primaries_named, Some(1));
+        assert_eq!(srgb.tf_named, Some(2));
+        assert_eq!(
+            srgb.primaries,
+            Some([[0.64, 0.33], [0.3, 0.6], [0.15, 0.06], [0.3127, 0.329]])
+        );
+        assert_eq!(srgb.luminances, Some([0.2, 80.0, 80.0]));
+        assert_eq!(srgb.info("eDP-1").name, "sRGB gamma 2.2");
+
+        let hdr = outputs[1].image.as_ref().unwrap();
+        assert_eq!(hdr.info("HDMI-A-1").name, "BT.2020 PQ");
+        let metadata = hdr.metadata();
+        assert_eq!(metadata["primaries_named"], "bt2020");
+        assert_eq!(metadata["tf_named"], "st2084_pq");
+        assert_eq!(metadata["luminances"], "0.005 10000 203");
+        assert_eq!(metadata["target_luminance"], "0.05 1000");
+        assert_eq!(metadata["target_max_cll"], "1000");
+    }
+
+    #[test]
+    fn test_read_failed_description() {
+        let compositor = StandIn::serve(vec![StubOutput {
+            image: StubImage::Failed("output is off".to_string()),
+            ..StubOutput::new("DP-2")
+        }]);
+
+        let outputs = read_outputs(Some(compositor.socket()), TIMEOUT).unwrap();
+        let error = outputs[0].image.as_ref().unwrap_err();
+        assert!(error.contains("output is off"), "{}", error);
+    }
+
+    #[test]
+    fn test_read_without_color_management() {
+        let compositor = StandIn::without_color_management(vec![StubOutput::new("DP-1")]);
+        assert!(matches!(
+            read_outputs(Some(compositor.socket()), TIMEOUT),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_summary_with_power_tf() {
+        let description = ImageDescription {
+            primaries: Some([[0.68, 0.32], [0.265, 0.69], [0.15, 0.06], [0.3127, 0.329]]),
+            tf_power: Some(2.4),
+            ..ImageDescription::default()
+        };
+        assert_eq!(description.summary(), "Custom primaries gamma 2.4");
+        assert_eq!(
+            description.metadata()["primaries"],
+            "0.68,0.32 0.265,0.69 0.15,0.06 0.3127,0.329"
+        );
+    }
+}
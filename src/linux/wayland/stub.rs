@@ -0,0 +1,374 @@
+//! A stand-in compositor on a private socket, for tests.
+//!
+//! [`StandIn`] listens on a socket in a temporary directory and serves
+//! `wl_output` globals together with `wp_color_manager_v1`, answering each
+//! output's image description with the configured ICC profile, parametric
+//! description or failure.
+
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::AsFd;
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tempfile::TempDir;
+use wayland_protocols::wp::color_management::v1::server::{
+    wp_color_management_output_v1::{self, WpColorManagementOutputV1},
+    wp_color_manager_v1::{self, Primaries, TransferFunction, WpColorManagerV1},
+    wp_image_description_info_v1::WpImageDescriptionInfoV1,
+    wp_image_description_v1::{self, WpImageDescriptionV1},
+};
+use wayland_server::backend::ClientData;
+use wayland_server::protocol::wl_output::{self, WlOutput};
+use wayland_server::{
+    Client, DataInit, Dispatch, Display, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+/// An output as the stand-in compositor announces it
+#[derive(Debug, Clone)]
+pub(crate) struct StubOutput {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    pub size: (i32, i32),
+    pub position: (i32, i32),
+    pub scale: i32,
+    pub image: StubImage,
+}
+
+impl StubOutput {
+    /// A 4K Dell monitor on connector `name` at the origin, scaled by 2, with
+    /// an sRGB parametric description
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            make: "Dell Inc.".to_string(),
+            model: "DELL U2720Q".to_string(),
+            size: (3840, 2160),
+            position: (0, 0),
+            scale: 2,
+            image: StubImage::Parametric(StubParams::srgb()),
+        }
+    }
+}
+
+/// What an output's image description turns out to be
+#[derive(Debug, Clone)]
+pub(crate) enum StubImage {
+    /// Created from this ICC profile
+    Icc(Vec<u8>),
+    /// Described by parameters
+    Parametric(StubParams),
+    /// Not available, for the given reason
+    Failed(String),
+}
+
+/// Parametric image description values, in the protocol's fixed-point units
+#[derive(Debug, Clone)]
+pub(crate) struct StubParams {
+    pub primaries: [i32; 8],
+    pub primaries_named: Option<Primaries>,
+    pub tf_named: TransferFunction,
+    pub luminances: (u32, u32, u32),
+    pub target_luminance: Option<(u32, u32)>,
+    pub target_max_cll: Option<u32>,
+}
+
+impl StubParams {
+    /// An SDR sRGB display with gamma 2.2
+    pub fn srgb() -> Self {
+        Self {
+            primaries: [
+                640000, 330000, 300000, 600000, 150000, 60000, 312700, 329000,
+            ],
+            primaries_named: Some(Primaries::Srgb),
+            tf_named: TransferFunction::Gamma22,
+            luminances: (2000, 80, 80),
+            target_luminance: None,
+            target_max_cll: None,
+        }
+    }
+
+    /// An HDR display driven with BT.2100 PQ
+    pub fn bt2100_pq() -> Self {
+        Self {
+            primaries: [
+                708000, 292000, 170000, 797000, 131000, 46000, 312700, 329000,
+            ],
+            primaries_named: Some(Primaries::Bt2020),
+            tf_named: TransferFunction::St2084Pq,
+            luminances: (50, 10000, 203),
+            target_luminance: Some((500, 1000)),
+            target_max_cll: Some(1000),
+        }
+    }
+}
+
+/// A compositor serving its outputs on a private socket, stopped on drop
+pub(crate) struct StandIn {
+    socket: PathBuf,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    _dir: TempDir,
+}
+
+impl StandIn {
+    /// Serve `outputs` with colour management
+    pub fn serve(outputs: Vec<StubOutput>) -> Self {
+        Self::start(outputs, true)
+    }
+
+    /// Serve `outputs` from a compositor without `wp_color_manager_v1`
+    pub fn without_color_management(outputs: Vec<StubOutput>) -> Self {
+        Self::start(outputs, false)
+    }
+
+    /// Path of the compositor's socket
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    fn start(outputs: Vec<StubOutput>, color_management: bool) -> Self {
+        let dir = TempDir::new().unwrap();
+        let socket = dir.path().join("wayland-0");
+        let listener = UnixListener::bind(&socket).unwrap();
+        listener.set_nonblocking(true).unwrap();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            let mut display = Display::<Compositor>::new().unwrap();
+            let mut handle = display.handle();
+            for index in 0..outputs.len() {
+                handle.create_global::<Compositor, WlOutput, usize>(4, index);
+            }
+            if color_management {
+                handle.create_global::<Compositor, WpColorManagerV1, ()>(2, ());
+            }
+
+            let mut compositor = Compositor {
+                outputs,
+                icc_files: Vec::new(),
+                pending: Vec::new(),
+            };
+            while !stopped.load(Ordering::Relaxed) {
+                while let Ok((stream, _)) = listener.accept() {
+                    let _ = handle.insert_client(stream, Arc::new(StubClient));
+                }
+                let _ = display.dispatch_clients(&mut compositor);
+                compositor.send_information();
+                let _ = display.flush_clients();
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        Self {
+            socket,
+            stop,
+            thread: Some(thread),
+            _dir: dir,
+        }
+    }
+}
+
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct StubClient;
+
+impl ClientData for StubClient {}
+
+struct Compositor {
+    outputs: Vec<StubOutput>,
+    /// ICC profiles handed out, kept open until the compositor stops
+    icc_files: Vec<File>,
+    /// Information objects created during the last dispatch, with their output
+    pending: Vec<(WpImageDescriptionInfoV1, usize)>,
+}
+
+impl Compositor {
+    /// Describe the pending information objects.
+    ///
+    /// `done` destroys the object, which the backend only allows once the
+    /// request that created it has been dispatched.
+    fn send_information(&mut self) {
+        for (info, index) in std::mem::take(&mut self.pending) {
+            match self.outputs[index].image.clone() {
+                StubImage::Icc(data) => {
+                    let mut file = tempfile::tempfile().unwrap();
+                    file.write_all(&data).unwrap();
+                    info.icc_file(file.as_fd(), data.len() as u32);
+                    self.icc_files.push(file);
+                }
+                StubImage::Parametric(params) => {
+                    let [r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y] = params.primaries;
+                    info.primaries(r_x, r_y, g_x, g_y, b_x, b_y, w_x, w_y);
+                    if let Some(primaries) = params.primaries_named {
+                        info.primaries_named(primaries);
+                    }
+                    info.tf_named(params.tf_named);
+                    let (min, max, reference) = params.luminances;
+                    info.luminances(min, max, reference);
+                    if let Some((min, max)) = params.target_luminance {
+                        info.target_luminance(min, max);
+                    }
+                    if let Some(max_cll) = params.target_max_cll {
+                        info.target_max_cll(max_cll);
+                    }
+                }
+                StubImage::Failed(_) => {}
+            }
+            info.done();
+        }
+    }
+}
+
+impl GlobalDispatch<WlOutput, usize> for Compositor {
+    fn bind(
+        state: &mut Self,
+        _: &DisplayHandle,
+        _: &Client,
+        resource: New<WlOutput>,
+        &index: &usize,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let output = data_init.init(resource, index);
+        let stub = &state.outputs[index];
+        output.geometry(
+            stub.position.0,
+            stub.position.1,
+            600,
+            340,
+            wl_output::Subpixel::Unknown,
+            stub.make.clone(),
+            stub.model.clone(),
+            wl_output::Transform::Normal,
+        );
+        output.mode(
+            wl_output::Mode::Current | wl_output::Mode::Preferred,
+            stub.size.0,
+            stub.size.1,
+            60000,
+        );
+        if output.version() >= 2 {
+            output.scale(stub.scale);
+        }
+        if output.version() >= 4 {
+            output.name(stub.name.clone());
+            output.description(format!("{} {} ({})", stub.make, stub.model, stub.name));
+        }
+        output.done();
+    }
+}
+
+impl Dispatch<WlOutput, usize> for Compositor {
+    fn request(
+        _: &mut Self,
+        _: &Client,
+        _: &WlOutput,
+        _: wl_output::Request,
+        _: &usize,
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl GlobalDispatch<WpColorManagerV1, ()> for Compositor {
+    fn bind(
+        _: &mut Self,
+        _: &DisplayHandle,
+        _: &Client,
+        resource: New<WpColorManagerV1>,
+        _: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let manager = data_init.init(resource, ());
+        manager.supported_feature(wp_color_manager_v1::Feature::Parametric);
+        manager.supported_primaries_named(Primaries::Srgb);
+        manager.supported_tf_named(TransferFunction::Gamma22);
+        manager.done();
+    }
+}
+
+impl Dispatch<WpColorManagerV1, ()> for Compositor {
+    fn request(
+        _: &mut Self,
+        _: &Client,
+        _: &WpColorManagerV1,
+        request: wp_color_manager_v1::Request,
+        _: &(),
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        if let wp_color_manager_v1::Request::GetOutput { id, output } = request {
+            let index = *output.data::<usize>().unwrap();
+            data_init.init(id, index);
+        }
+    }
+}
+
+impl Dispatch<WpColorManagementOutputV1, usize> for Compositor {
+    fn request(
+        state: &mut Self,
+        _: &Client,
+        _: &WpColorManagementOutputV1,
+        request: wp_color_management_output_v1::Request,
+        &index: &usize,
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        if let wp_color_management_output_v1::Request::GetImageDescription { image_description } =
+            request
+        {
+            let description = data_init.init(image_description, index);
+            match &state.outputs[index].image {
+                StubImage::Failed(message) => {
+                    description.failed(wp_image_description_v1::Cause::Unsupported, message.clone())
+                }
+                _ if description.version() >= 2 => description.ready2(0, index as u32 + 1),
+                _ => description.ready(index as u32 + 1),
+            }
+        }
+    }
+}
+
+impl Dispatch<WpImageDescriptionV1, usize> for Compositor {
+    fn request(
+        state: &mut Self,
+        _: &Client,
+        _: &WpImageDescriptionV1,
+        request: wp_image_description_v1::Request,
+        &index: &usize,
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let wp_image_description_v1::Request::GetInformation { information } = request else {
+            return;
+        };
+        let info = data_init.init(information, ());
+        state.pending.push((info, index));
+    }
+}
+
+impl Dispatch<WpImageDescriptionInfoV1, ()> for Compositor {
+    fn request(
+        _: &mut Self,
+        _: &Client,
+        _: &WpImageDescriptionInfoV1,
+        _: <WpImageDescriptionInfoV1 as Resource>::Request,
+        _: &(),
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
@@ -0,0 +1,432 @@
+//! Display profiles from the X11 `_ICC_PROFILE` root window properties.
+//!
+//! Under the "ICC Profiles in X" convention, colour-managed applications such
+//! as GIMP, Firefox and mpv read `_ICC_PROFILE` for the first screen and
+//! `_ICC_PROFILE_n` for Xinerama screen `n`, so these properties are what takes
+//! effect when colord is not running. With RandR the Xinerama screens are the
+//! active monitors in `RRGetMonitors` order, primary first, which maps each
+//! property to a monitor and its first output.
+//!
+//! RandR also reports the connected outputs with their geometry and refresh
+//! rate, which fill in the displays found through colord or DRM.
+
+use super::RandrOutput;
+use crate::{icc, ProfileError, ProfileInfo};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::randr::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, Window};
+use x11rb::rust_connection::RustConnection;
+
+/// The profile data stored in one `_ICC_PROFILE` property
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct X11Profile {
+    /// Property name, `_ICC_PROFILE` or `_ICC_PROFILE_n`
+    pub atom: String,
+    /// RandR output of the monitor the property belongs to, if known
+    pub output: Option<String>,
+    /// Whether that monitor is the RandR primary
+    pub primary: bool,
+    /// Raw ICC profile data
+    pub data: Vec<u8>,
+}
+
+impl X11Profile {
+    /// Describe the profile, as the file system fallback would the same data
+    pub fn info(&self) -> ProfileInfo {
+        ProfileInfo {
+            description: Some(format!("X11 {} root window property", self.atom)),
+            ..icc::profile_info(&self.data, None)
+        }
+    }
+}
+
+/// Property name for Xinerama screen `index`
+fn atom_name(index: usize) -> String {
+    match index {
+        0 => "_ICC_PROFILE".to_string(),
+        n => format!("_ICC_PROFILE_{}", n),
+    }
+}
+
+/// Read the profiles set on the root window of the X server `display`, or of
+/// `$DISPLAY` when `None`, giving up after `timeout`.
+pub(super) fn read_profiles(
+    display: Option<&str>,
+    timeout: Duration,
+) -> Result<Vec<X11Profile>, ProfileError> {
+    query_root(display, timeout, |connection, root| {
+        read_root_profiles(connection, root)
+    })
+}
+
+/// Read the connected RandR outputs of the X server `display`, or of
+/// `$DISPLAY` when `None`, giving up after `timeout`.
+pub(super) fn read_outputs(
+    display: Option<&str>,
+    timeout: Duration,
+) -> Result<Vec<RandrOutput>, ProfileError> {
+    query_root(display, timeout, |connection, root| {
+        read_root_outputs(connection, root)
+    })
+}
+
+/// Run `query` against the root window of the X server `display`, giving up
+/// after `timeout`.
+///
+/// x11rb only has blocking requests, so they run on a thread of their own that
+/// is left behind if the X server does not answer in time.
+fn query_root<T: Send + 'static>(
+    display: Option<&str>,
+    timeout: Duration,
+    query: impl FnOnce(&RustConnection, Window) -> Result<T, ReplyError> + Send + 'static,
+) -> Result<T, ProfileError> {
+    let display = display.map(str::to_string);
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("display_icc x11".to_string())
+        .spawn(move || {
+            let _ = sender.send(query_root_blocking(display.as_deref(), query));
+        })?;
+
+    receiver.recv_timeout(timeout).map_err(|e| match e {
+        mpsc::RecvTimeoutError::Timeout => {
+            ProfileError::Timeout("The X server did not reply in time".to_string())
+        }
+        mpsc::RecvTimeoutError::Disconnected => {
+            ProfileError::SystemError("X11 request thread panicked".to_string())
+        }
+    })?
+}
+
+fn query_root_blocking<T>(
+    display: Option<&str>,
+    query: impl FnOnce(&RustConnection, Window) -> Result<T, ReplyError>,
+) -> Result<T, ProfileError> {
+    let (connection, screen) = x11rb::connect(display)
+        .map_err(|e| ProfileError::SystemError(format!("Failed to connect to X11: {}", e)))?;
+    let root = connection.setup().roots[screen].root;
+
+    query(&connection, root)
+        .map_err(|e| ProfileError::SystemError(format!("X11 request failed: {}", e)))
+}
+
+fn read_root_profiles(
+    connection: &impl Connection,
+    root: Window,
+) -> Result<Vec<X11Profile>, ReplyError> {
+    let monitors = monitors(connection, root)?;
+
+    let mut profiles = Vec::new();
+    for index in 0..monitors.len().max(1) {
+        let atom = atom_name(index);
+        let property = connection.intern_atom(true, atom.as_bytes())?.reply()?.atom;
+        if property == x11rb::NONE {
+            continue;
+        }
+
+        let data = connection
+            .get_property(false, root, property, AtomEnum::ANY, 0, u32::MAX / 4)?
+            .reply()?
+            .value;
+        if data.is_empty() {
+            continue;
+        }
+
+        let (output, primary) = monitors.get(index).cloned().unwrap_or((None, false));
+        profiles.push(X11Profile {
+            atom,
+            output,
+            primary,
+            data,
+        });
+    }
+    Ok(profiles)
+}
+
+/// Active RandR monitors as (first output name, primary), or none without RandR 1.5
+fn monitors(
+    connection: &impl Connection,
+    root: Window,
+) -> Result<Vec<(Option<String>, bool)>, ReplyError> {
+    if connection
+        .extension_information(randr::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        return Ok(Vec::new());
+    }
+    let version = connection.randr_query_version(1, 5)?.reply()?;
+    if (version.major_version, version.minor_version) < (1, 5) {
+        return Ok(Vec::new());
+    }
+
+    let monitors = connection.randr_get_monitors(root, true)?.reply()?.monitors;
+    monitors
+        .into_iter()
+        .map(|monitor| {
+            let output = match monitor.outputs.first() {
+                Some(&output) => {
+                    let info = connection
+                        .randr_get_output_info(output, x11rb::CURRENT_TIME)?
+                        .reply()?;
+                    Some(String::from_utf8_lossy(&info.name).into_owned())
+                }
+                None => None,
+            };
+            Ok((output, monitor.primary))
+        })
+        .collect()
+}
+
+/// Connected outputs with the geometry and refresh rate of their CRTC, or
+/// none without RandR 1.3
+fn read_root_outputs(
+    connection: &impl Connection,
+    root: Window,
+) -> Result<Vec<RandrOutput>, ReplyError> {
+    if connection
+        .extension_information(randr::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        return Ok(Vec::new());
+    }
+    let version = connection.randr_query_version(1, 3)?.reply()?;
+    if (version.major_version, version.minor_version) < (1, 3) {
+        return Ok(Vec::new());
+    }
+
+    let resources = connection
+        .randr_get_screen_resources_current(root)?
+        .reply()?;
+    let primary = connection.randr_get_output_primary(root)?.reply()?.output;
+
+    let mut outputs = Vec::new();
+    for &output in &resources.outputs {
+        let info = connection
+            .randr_get_output_info(output, resources.config_timestamp)?
+            .reply()?;
+        if info.connection != randr::Connection::CONNECTED {
+            continue;
+        }
+
+        let (mut geometry, mut refresh_rate) = (None, None);
+        if info.crtc != x11rb::NONE {
+            let crtc = connection
+                .randr_get_crtc_info(info.crtc, resources.config_timestamp)?
+                .reply()?;
+            if crtc.mode != x11rb::NONE {
+                geometry = Some((
+                    u32::from(crtc.width),
+                    u32::from(crtc.height),
+                    i32::from(crtc.x),
+                    i32::from(crtc.y),
+                ));
+                refresh_rate = resources
+                    .modes
+                    .iter()
+                    .find(|mode| mode.id == crtc.mode)
+                    .and_then(mode_refresh_rate);
+            }
+        }
+
+        outputs.push(RandrOutput {
+            name: String::from_utf8_lossy(&info.name).into_owned(),
+            primary: output == primary,
+            geometry,
+            refresh_rate,
+        });
+    }
+    Ok(outputs)
+}
+
+/// Refresh rate of a mode in Hz, worked out from its timings as xrandr does
+fn mode_refresh_rate(mode: &randr::ModeInfo) -> Option<f64> {
+    let mut lines = f64::from(mode.vtotal);
+    if mode.mode_flags.contains(randr::ModeFlag::DOUBLE_SCAN) {
+        lines *= 2.0;
+    }
+    if mode.mode_flags.contains(randr::ModeFlag::INTERLACE) {
+        lines /= 2.0;
+    }
+
+    let dots = f64::from(mode.htotal) * lines;
+    (dots > 0.0).then(|| f64::from(mode.dot_clock) / dots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icc::test_support::{mluc, profile};
+    use crate::ColorSpace;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use x11rb::protocol::xproto::PropMode;
+    use x11rb::wrapper::ConnectionExt as _;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// A private Xvfb server, stopped on drop
+    struct XServer {
+        process: Child,
+        display: String,
+    }
+
+    impl XServer {
+        /// Start Xvfb, which must be installed
+        fn start() -> Self {
+            let mut process = Command::new("Xvfb")
+                .args(["-displayfd", "1", "-nolisten", "tcp"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("Xvfb should be installed");
+
+            // Xvfb writes the display number it picked once it is accepting clients
+            let mut number = String::new();
+            BufReader::new(process.stdout.take().unwrap())
+                .read_line(&mut number)
+                .unwrap();
+
+            Self {
+                process,
+                display: format!(":{}", number.trim()),
+            }
+        }
+
+        /// Set a property on the root window, as a colour management tool would
+        fn set_property(&self, name: &str, data: &[u8]) {
+            let (connection, screen) = x11rb::connect(Some(&self.display)).unwrap();
+            let root = connection.setup().roots[screen].root;
+            let atom = connection
+                .intern_atom(false, name.as_bytes())
+                .unwrap()
+                .reply()
+                .unwrap()
+                .atom;
+            connection
+                .change_property8(PropMode::REPLACE, root, atom, AtomEnum::CARDINAL, data)
+                .unwrap()
+                .check()
+                .unwrap();
+        }
+    }
+
+    impl Drop for XServer {
+        fn drop(&mut self) {
+            let _ = self.process.kill();
+            let _ = self.process.wait();
+        }
+    }
+
+    #[test]
+    fn test_atom_name() {
+        assert_eq!(atom_name(0), "_ICC_PROFILE");
+        assert_eq!(atom_name(2), "_ICC_PROFILE_2");
+    }
+
+    #[test]
+    fn test_profile_info() {
+        let x11_profile = X11Profile {
+            atom: "_ICC_PROFILE".to_string(),
+            output: Some("DP-1".to_string()),
+            primary: true,
+            data: profile(&[(b"desc", mluc("Dell U2415 calibrated"))]),
+        };
+        let info = x11_profile.info();
+        assert_eq!(info.name, "Dell U2415 calibrated");
+        assert_eq!(info.color_space, ColorSpace::RGB);
+        assert_eq!(info.file_path, None);
+
+        // Without a description the property name is only in the description
+        let unnamed = X11Profile {
+            data: profile(&[]),
+            ..x11_profile
+        }
+        .info();
+        assert_eq!(unnamed.name, "Unknown Profile");
+        assert_eq!(
+            unnamed.description.as_deref(),
+            Some("X11 _ICC_PROFILE root window property")
+        );
+    }
+
+    #[test]
+    #[ignore = "needs Xvfb; run with --ignored"]
+    fn test_read_profiles_from_xvfb() {
+        let server = XServer::start();
+        assert_eq!(read_profiles(Some(&server.display), TIMEOUT).unwrap(), []);
+
+        let data = profile(&[(b"desc", mluc("Xvfb test profile"))]);
+        server.set_property("_ICC_PROFILE", &data);
+
+        let profiles = read_profiles(Some(&server.display), TIMEOUT).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].atom, "_ICC_PROFILE");
+        assert_eq!(profiles[0].data, data);
+        assert_eq!(profiles[0].info().name, "Xvfb test profile");
+    }
+
+    #[test]
+    #[ignore = "needs Xvfb; run with --ignored"]
+    fn test_read_outputs_from_xvfb() {
+        let server = XServer::start();
+        let outputs = read_outputs(Some(&server.display), TIMEOUT).unwrap();
+
+        // Xvfb has a single output driving its whole screen
+        assert_eq!(outputs.len(), 1);
+        let (width, height, x, y) = outputs[0].geometry.unwrap();
+        assert!(width > 0 && height > 0);
+        assert_eq!((x, y), (0, 0));
+    }
+
+    #[test]
+    fn test_mode_refresh_rate() {
+        // CEA 1080p60 timings
+        let progressive = randr::ModeInfo {
+            dot_clock: 148_500_000,
+            htotal: 2200,
+            vtotal: 1125,
+            ..Default::default()
+        };
+        assert_eq!(mode_refresh_rate(&progressive), Some(60.0));
+
+        // 1080i60 scans half the lines per field
+        let interlaced = randr::ModeInfo {
+            dot_clock: 74_250_000,
+            mode_flags: randr::ModeFlag::INTERLACE,
+            ..progressive
+        };
+        assert_eq!(mode_refresh_rate(&interlaced), Some(60.0));
+
+        assert_eq!(mode_refresh_rate(&randr::ModeInfo::default()), None);
+    }
+
+    #[test]
+    fn test_read_profiles_without_server() {
+        assert!(matches!(
+            read_profiles(Some(":4095"), TIMEOUT),
+            Err(ProfileError::SystemError(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_profiles_times_out() {
+        // A server that accepts the connection but never answers the handshake,
+        // on the TCP port of the first free display number
+        let (_listener, number) = (100..200)
+            .find_map(|number| {
+                let listener = std::net::TcpListener::bind(("127.0.0.1", 6000 + number)).ok()?;
+                Some((listener, number))
+            })
+            .unwrap();
+        let display = format!("127.0.0.1:{}", number);
+
+        assert!(matches!(
+            read_profiles(Some(&display), Duration::from_millis(100)),
+            Err(ProfileError::Timeout(_))
+        ));
+    }
+}
@@ -1,12 +1,14 @@
 //! macOS-specific implementation using CoreGraphics framework
 
+use crate::watch::POLL_INTERVAL;
 use crate::{
-    ColorSpace, Display, DisplayProfileProvider, ProfileConfig, ProfileError, ProfileInfo,
+    ColorSpace, Display, DisplayProfileProvider, PrimarySource, ProfileConfig, ProfileError,
+    ProfileInfo, ProfileWatcher,
 };
 use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
 use core_foundation::data::{CFData, CFDataRef};
 use core_foundation::string::{CFString, CFStringRef};
-use core_graphics::display::CGMainDisplayID;
+use core_graphics::display::{CGDisplay, CGMainDisplayID};
 
 // Raw CoreGraphics types
 type CGColorSpaceRef = *mut std::ffi::c_void;
@@ -129,6 +131,41 @@ fn get_display_name(display_id: u32) -> String {
     }
 }
 
+/// Build a Display with the geometry CoreGraphics reports for it
+fn display_from_id(display_id: u32, is_primary: bool) -> Display {
+    let cg_display = CGDisplay::new(display_id);
+    let bounds = cg_display.bounds();
+
+    let mut display = Display::new(display_id.to_string(), get_display_name(display_id), false)
+        .with_resolution(
+            cg_display.pixels_wide() as u32,
+            cg_display.pixels_high() as u32,
+        )
+        .with_position(bounds.origin.x as i32, bounds.origin.y as i32)
+        .with_builtin(cg_display.is_builtin());
+
+    if let Some(mode) = cg_display.display_mode() {
+        if mode.width() > 0 {
+            display = display.with_scale(mode.pixel_width() as f64 / mode.width() as f64);
+        }
+        // Built-in panels report 0 Hz
+        if mode.refresh_rate() > 0.0 {
+            display = display.with_refresh_rate(mode.refresh_rate());
+        }
+    }
+
+    if is_primary {
+        display = display.with_primary_source(PrimarySource::System);
+    }
+
+    let serial = cg_display.serial_number();
+    if serial != 0 {
+        display = display.with_serial(serial.to_string());
+    }
+
+    display
+}
+
 /// Determine color space from ICC profile data
 fn determine_color_space(icc_data: &[u8]) -> ColorSpace {
     if icc_data.len() < 20 {
@@ -374,12 +411,8 @@ impl DisplayProfileProvider for MacOSProfileProvider {
         let mut displays = Vec::new();
 
         for display_id in display_ids {
-            let display = Display {
-                id: display_id.to_string(),
-                name: get_display_name(display_id),
-                is_primary: unsafe { CGDisplayIsMain(display_id) },
-            };
-            displays.push(display);
+            let is_primary = unsafe { CGDisplayIsMain(display_id) };
+            displays.push(display_from_id(display_id, is_primary));
         }
 
         Ok(displays)
@@ -388,11 +421,7 @@ impl DisplayProfileProvider for MacOSProfileProvider {
     fn get_primary_display(&self) -> Result<Display, ProfileError> {
         let main_display_id = unsafe { CGMainDisplayID() };
 
-        Ok(Display {
-            id: main_display_id.to_string(),
-            name: get_display_name(main_display_id),
-            is_primary: true,
-        })
+        Ok(display_from_id(main_display_id, true))
     }
 
     fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
@@ -402,4 +431,8 @@ impl DisplayProfileProvider for MacOSProfileProvider {
     fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
         get_profile_data_with_fallback(display, &self.config)
     }
+    /// CoreGraphics reconfiguration callbacks need a run loop, so poll instead
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        ProfileWatcher::polling(Self::with_config(self.config.clone()), POLL_INTERVAL)
+    }
 }
@@ -10,6 +10,7 @@
 //! - **Multi-display support**: List and work with all connected displays
 //! - **Profile export**: Save ICC profiles to files for backup or analysis
 //! - **ICC header analysis**: Examine detailed ICC profile metadata
+//! - **Colorimetry**: White point, CCT, primaries, gamma and calibration status
 //! - **Multiple output formats**: Human-readable text and machine-readable JSON
 //! - **Platform-specific options**: Configure behavior for different operating systems
 //!
@@ -31,20 +32,45 @@
 //! # Get output in JSON format
 //! display_icc info --format json
 //!
+//! # Print the JSON Schema for --format json output (with the `schema` feature)
+//! display_icc schema
+//!
 //! # Verbose output with additional details
 //! display_icc list --verbose
 //!
+//! # Show where profiles are searched for, adding a custom directory
+//! display_icc paths --profile-dir ~/profiles
+//!
 //! # Work with specific display (use ID from list command)
 //! display_icc info --display "69733382"
 //! display_icc export --display "69733382" --output external_display.icc
 //!
+//! # Or by stable ID, which survives reboots and reconnects (see `list --verbose`)
+//! display_icc info --display "edid:DEL:a0e8:8LX2N63"
+//!
 //! # Platform-specific options (Linux)
 //! display_icc info --prefer-command --no-fallback
+//! display_icc info --primary DP-1
+//! display_icc list --profile-map ~/.config/display_icc/profiles.conf
+//!
+//! # Serve the displays described in a file instead, e.g. in CI
+//! display_icc list --provider fixture:tests/fixtures/provider/displays.json
+//! DISPLAY_ICC_PROVIDER=fixture:displays.toml display_icc info
 //! ```
 
 use clap::{Parser, Subcommand, ValueEnum};
-use display_icc::{parse_icc_header, ProfileConfig, ProfileError};
+use display_icc::report::{
+    ExportReport, HeaderReport, InfoReport, ListEntry, ListReport, PathsReport, SearchPath,
+};
+#[cfg(feature = "serde")]
+use display_icc::FileProfileProvider;
+use display_icc::{
+    parse_icc_header, AssignedProfile, Colorimetry, Display, DisplayProfileProvider, Edid,
+    ProfileConfig, ProfileError,
+};
 use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Cross-platform tool for retrieving display ICC profiles
 #[derive(Parser)]
@@ -62,6 +88,7 @@ Examples:
   display_icc export --output prof.icc  # Export primary display profile
   display_icc info --json             # Output in JSON format
   display_icc list --verbose          # Show detailed profile information
+  display_icc paths                   # Show profile search directories
 ")]
 struct Cli {
     #[command(subcommand)]
@@ -82,13 +109,50 @@ struct Cli {
     /// Prefer command-line tools over D-Bus API (Linux only)
     #[arg(long, global = true)]
     prefer_command: bool,
+
+    /// Display to treat as primary, by ID or connector name such as DP-1 (Linux only)
+    #[arg(long, value_name = "DISPLAY", global = true)]
+    primary: Option<String>,
+
+    /// Additional directory to search for ICC profiles (can be repeated)
+    #[arg(long = "profile-dir", value_name = "DIR", global = true)]
+    profile_dirs: Vec<PathBuf>,
+
+    /// Time limit in seconds for each system call or external command (Linux only)
+    #[arg(long, value_name = "SECONDS", global = true, default_value = "5", value_parser = parse_timeout)]
+    timeout: Duration,
+
+    /// File assigning profiles to displays found without colord (Linux only)
+    #[arg(long, value_name = "FILE", global = true)]
+    profile_map: Option<PathBuf>,
+
+    /// Where displays come from: `system`, or `fixture:FILE` for the displays
+    /// described in a JSON or TOML file
+    #[arg(
+        long,
+        value_name = "PROVIDER",
+        global = true,
+        env = "DISPLAY_ICC_PROVIDER",
+        default_value = "system",
+        value_parser = parse_provider
+    )]
+    provider: ProviderChoice,
+}
+
+/// The provider selected with `--provider`
+#[derive(Clone)]
+enum ProviderChoice {
+    /// The platform's displays
+    System,
+    /// The displays described in a file
+    Fixture(PathBuf),
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Show information about display profiles
     Info {
-        /// Display ID to query (defaults to primary display)
+        /// Display ID or stable ID to query (defaults to primary display)
         #[arg(short, long)]
         display: Option<String>,
     },
@@ -100,16 +164,21 @@ enum Commands {
         #[arg(short, long)]
         output: String,
 
-        /// Display ID to export (defaults to primary display)
+        /// Display ID or stable ID to export (defaults to primary display)
         #[arg(short, long)]
         display: Option<String>,
     },
     /// Show detailed ICC header information
     Header {
-        /// Display ID to analyze (defaults to primary display)
+        /// Display ID or stable ID to analyze (defaults to primary display)
         #[arg(short, long)]
         display: Option<String>,
     },
+    /// Print the JSON Schema describing `--format json` output
+    #[cfg(feature = "schema")]
+    Schema,
+    /// Show the directories searched for ICC profiles
+    Paths,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -120,14 +189,71 @@ enum OutputFormat {
     Json,
 }
 
+/// Parse a `--timeout` value in (possibly fractional) seconds
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number of seconds", value))?;
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("'{}' is not a valid timeout", value))
+}
+
+/// Parse a `--provider` value
+fn parse_provider(value: &str) -> Result<ProviderChoice, String> {
+    match value.split_once(':') {
+        None if value == "system" => Ok(ProviderChoice::System),
+        Some(("fixture", path)) if !path.is_empty() => Ok(ProviderChoice::Fixture(path.into())),
+        _ => Err(format!(
+            "'{}' is not a provider; use 'system' or 'fixture:FILE'",
+            value
+        )),
+    }
+}
+
+/// The provider selected with `--provider`, configured from the options
+fn create_provider(
+    cli: &Cli,
+    config: ProfileConfig,
+) -> Result<Box<dyn DisplayProfileProvider>, ProfileError> {
+    match &cli.provider {
+        ProviderChoice::System => display_icc::create_provider_with_config(config),
+        #[cfg(feature = "serde")]
+        ProviderChoice::Fixture(path) => Ok(Box::new(FileProfileProvider::load(path)?)),
+        #[cfg(not(feature = "serde"))]
+        ProviderChoice::Fixture(path) => Err(ProfileError::SystemError(format!(
+            "cannot load {}: fixture providers need the serde feature",
+            path.display()
+        ))),
+    }
+}
+
+/// Print a report as `--format json` output
+#[cfg(feature = "serde")]
+fn print_json<T: serde::Serialize>(report: &T) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", serde_json::to_string_pretty(report)?);
+    Ok(())
+}
+
+/// JSON output needs the report types to be serializable
+#[cfg(not(feature = "serde"))]
+fn print_json<T>(_report: &T) -> Result<(), Box<dyn std::error::Error>> {
+    Err("JSON output needs the serde feature".into())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     // Create configuration based on CLI arguments
-    let config = ProfileConfig {
-        linux_prefer_dbus: !cli.prefer_command,
-        fallback_enabled: !cli.no_fallback,
-    };
+    let mut config = ProfileConfig::default()
+        .with_linux_prefer_dbus(!cli.prefer_command)
+        .with_fallback_enabled(!cli.no_fallback)
+        .with_extra_profile_dirs(cli.profile_dirs.clone())
+        .with_timeout(cli.timeout);
+    if let Some(primary) = &cli.primary {
+        config = config.with_primary_display(primary);
+    }
+    if let Some(path) = &cli.profile_map {
+        config = config.with_linux_profile_map(path);
+    }
 
     match &cli.command {
         Commands::Info { display } => {
@@ -142,6 +268,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Header { display } => {
             handle_header_command(display.clone(), &cli, config)?;
         }
+        Commands::Paths => {
+            handle_paths_command(&cli, &config)?;
+        }
+        #[cfg(feature = "schema")]
+        Commands::Schema => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&display_icc::report::json_schema())?
+            );
+        }
     }
 
     Ok(())
@@ -152,15 +288,11 @@ fn handle_info_command(
     cli: &Cli,
     config: ProfileConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let provider = display_icc::create_provider_with_config(config)?;
+    let provider = create_provider(cli, config)?;
 
     let (display, profile) = if let Some(id) = display_id {
         // Find specific display
-        let displays = provider.get_displays()?;
-        let display = displays
-            .into_iter()
-            .find(|d| d.id == id)
-            .ok_or(ProfileError::DisplayNotFound(id))?;
+        let display = find_display(provider.as_ref(), id)?;
         let profile = provider.get_profile(&display)?;
         (display, profile)
     } else {
@@ -170,10 +302,18 @@ fn handle_info_command(
         (display, profile)
     };
 
+    // Colorimetry is always shown; the raw data is fetched once for it and the verbose details
+    let icc_data = provider.get_profile_data(&display);
+    let colorimetry = icc_data
+        .as_ref()
+        .ok()
+        .and_then(|data| Colorimetry::from_icc(data).ok());
+
     match cli.format.as_ref().unwrap_or(&OutputFormat::Text) {
         OutputFormat::Text => {
             println!("Display: {} ({})", display.name, display.id);
-            println!("Primary: {}", display.is_primary);
+            println!("Primary: {}", format_primary(&display));
+            print_display_details(&display, "");
             println!("Profile: {}", profile.name);
 
             if let Some(desc) = &profile.description {
@@ -186,13 +326,17 @@ fn handle_info_command(
 
             println!("Color space: {}", profile.color_space);
 
+            if let Some(colorimetry) = &colorimetry {
+                print_colorimetry(colorimetry);
+            }
+
             if cli.verbose {
                 // Show additional ICC data information
-                match provider.get_profile_data(&display) {
+                match &icc_data {
                     Ok(icc_data) => {
                         println!("ICC profile size: {} bytes", icc_data.len());
 
-                        if let Ok(header) = parse_icc_header(&icc_data) {
+                        if let Ok(header) = parse_icc_header(icc_data) {
                             println!("ICC version: {}.{}", header.version.0, header.version.1);
                             println!("Device class: {}", header.device_class);
                             println!("Data color space: {}", header.data_color_space);
@@ -218,48 +362,147 @@ fn handle_info_command(
             }
         }
         OutputFormat::Json => {
-            let mut json_output = serde_json::json!({
-                "display": {
-                    "id": display.id,
-                    "name": display.name,
-                    "is_primary": display.is_primary
-                },
-                "profile": {
-                    "name": profile.name,
-                    "description": profile.description,
-                    "file_path": profile.file_path.as_ref().map(|p| p.to_string_lossy()),
-                    "color_space": profile.color_space.to_string()
-                }
-            });
+            let mut report = InfoReport::new(display, profile);
+            report.colorimetry = colorimetry;
 
             if cli.verbose {
-                if let Ok(icc_data) = provider.get_profile_data(&display) {
-                    json_output["icc_size"] = serde_json::Value::Number(icc_data.len().into());
-
-                    if let Ok(header) = parse_icc_header(&icc_data) {
-                        json_output["icc_header"] = serde_json::json!({
-                            "version": format!("{}.{}", header.version.0, header.version.1),
-                            "device_class": header.device_class,
-                            "data_color_space": header.data_color_space,
-                            "connection_space": header.connection_space,
-                            "creation_datetime": header.creation_datetime,
-                            "platform": header.platform,
-                            "device_manufacturer": header.device_manufacturer,
-                            "device_model": header.device_model
-                        });
-                    }
+                if let Ok(icc_data) = &icc_data {
+                    report.icc_size = Some(icc_data.len());
+                    report.icc_header = parse_icc_header(icc_data).ok();
                 }
             }
 
-            println!("{}", serde_json::to_string_pretty(&json_output)?);
+            print_json(&report)?;
         }
     }
 
     Ok(())
 }
 
+/// Whether the display is primary, with the source of that decision if known
+fn format_primary(display: &Display) -> String {
+    match display.primary_source() {
+        Some(source) => format!("{} (from {})", display.is_primary, source),
+        None => display.is_primary.to_string(),
+    }
+}
+
+/// The display with backend ID `id`, or else with that stable ID
+fn find_display(
+    provider: &dyn DisplayProfileProvider,
+    id: String,
+) -> Result<Display, ProfileError> {
+    let displays = provider.get_displays()?;
+    if let Some(display) = displays.into_iter().find(|d| d.id == id) {
+        return Ok(display);
+    }
+    provider.find_display(&id)
+}
+
+fn print_display_details(display: &Display, indent: &str) {
+    println!("{}Stable ID: {}", indent, display.stable_id());
+    if let Some(vendor) = display.vendor() {
+        println!("{}Vendor: {}", indent, vendor);
+    }
+    if let Some(model) = display.model() {
+        println!("{}Model: {}", indent, model);
+    }
+    if let Some(serial) = display.serial() {
+        println!("{}Serial: {}", indent, serial);
+    }
+    if let Some(connector) = display.connector() {
+        println!("{}Connector: {}", indent, connector);
+    }
+    if let Some(builtin) = display.is_builtin() {
+        println!(
+            "{}Type: {}",
+            indent,
+            if builtin { "built-in" } else { "external" }
+        );
+    }
+    if let Some((width, height)) = display.resolution() {
+        print!("{}Resolution: {}x{}", indent, width, height);
+        if let Some(refresh_rate) = display.refresh_rate() {
+            print!(" @ {:.2} Hz", refresh_rate);
+        }
+        println!();
+    }
+    if let Some((x, y)) = display.position() {
+        println!("{}Position: {},{}", indent, x, y);
+    }
+    if let Some(scale) = display.scale() {
+        println!("{}Scale: {}", indent, scale);
+    }
+    if let Some(edid) = display.edid() {
+        println!("{}EDID: {} bytes", indent, edid.len());
+        if let Ok(edid) = Edid::parse(edid) {
+            println!(
+                "{}EDID product: {} {:04x}",
+                indent, edid.manufacturer, edid.product_code
+            );
+            if let Some((width, height)) = edid.native_resolution {
+                println!("{}Native resolution: {}x{}", indent, width, height);
+            }
+            if let Some((width, height)) = edid.size_mm {
+                println!("{}Physical size: {}x{} mm", indent, width, height);
+            }
+            if let Some(year) = edid.manufacture_year {
+                match edid.manufacture_week {
+                    Some(week) => println!("{}Manufactured: week {} of {}", indent, week, year),
+                    None => println!("{}Manufactured: {}", indent, year),
+                }
+            }
+        }
+    }
+}
+
+/// Print white point, primaries, gamma, luminance and calibration status
+fn print_colorimetry(colorimetry: &Colorimetry) {
+    if let Some(white) = &colorimetry.white_point {
+        match (white.cct, white.duv) {
+            (Some(cct), Some(duv)) => println!(
+                "White point: x={:.4} y={:.4} ({:.0} K, Duv {:+.4})",
+                white.x, white.y, cct, duv
+            ),
+            _ => println!("White point: x={:.4} y={:.4}", white.x, white.y),
+        }
+    }
+
+    if let Some(primaries) = &colorimetry.primaries {
+        println!(
+            "Primaries: R x={:.4} y={:.4}, G x={:.4} y={:.4}, B x={:.4} y={:.4}",
+            primaries.red.x,
+            primaries.red.y,
+            primaries.green.x,
+            primaries.green.y,
+            primaries.blue.x,
+            primaries.blue.y
+        );
+    }
+
+    if let Some(gamma) = &colorimetry.gamma {
+        println!(
+            "Gamma: R {:.2}, G {:.2}, B {:.2}",
+            gamma.red, gamma.green, gamma.blue
+        );
+    }
+
+    if let Some(luminance) = colorimetry.luminance {
+        println!("Luminance: {:.1} cd/m²", luminance);
+    }
+
+    println!(
+        "Calibration (vcgt): {}",
+        if colorimetry.has_vcgt {
+            "loaded"
+        } else {
+            "none"
+        }
+    );
+}
+
 fn handle_list_command(cli: &Cli, config: ProfileConfig) -> Result<(), Box<dyn std::error::Error>> {
-    let provider = display_icc::create_provider_with_config(config)?;
+    let provider = create_provider(cli, config)?;
     let displays = provider.get_displays()?;
 
     match cli.format.as_ref().unwrap_or(&OutputFormat::Text) {
@@ -268,7 +511,11 @@ fn handle_list_command(cli: &Cli, config: ProfileConfig) -> Result<(), Box<dyn s
 
             for display in displays {
                 println!("\nDisplay: {} ({})", display.name, display.id);
-                println!("  Primary: {}", display.is_primary);
+                println!("  Primary: {}", format_primary(&display));
+
+                if cli.verbose {
+                    print_display_details(&display, "  ");
+                }
 
                 match provider.get_profile(&display) {
                     Ok(profile) => {
@@ -286,6 +533,10 @@ fn handle_list_command(cli: &Cli, config: ProfileConfig) -> Result<(), Box<dyn s
                             if let Ok(icc_data) = provider.get_profile_data(&display) {
                                 println!("  ICC size: {} bytes", icc_data.len());
                             }
+
+                            if let Ok(profiles) = provider.get_profiles(&display) {
+                                print_assigned_profiles(provider.as_ref(), &display, &profiles);
+                            }
                         }
                     }
                     Err(ProfileError::ProfileNotAvailable(_)) => {
@@ -298,47 +549,113 @@ fn handle_list_command(cli: &Cli, config: ProfileConfig) -> Result<(), Box<dyn s
             }
         }
         OutputFormat::Json => {
-            let mut json_displays = Vec::new();
+            let mut entries = Vec::new();
 
             for display in displays {
-                let mut display_json = serde_json::json!({
-                    "id": display.id,
-                    "name": display.name,
-                    "is_primary": display.is_primary
-                });
+                let mut entry = ListEntry::new(display.clone());
 
                 match provider.get_profile(&display) {
                     Ok(profile) => {
-                        display_json["profile"] = serde_json::json!({
-                            "name": profile.name,
-                            "description": profile.description,
-                            "file_path": profile.file_path.as_ref().map(|p| p.to_string_lossy()),
-                            "color_space": profile.color_space.to_string()
-                        });
+                        entry.profile = Some(profile);
 
                         if cli.verbose {
                             if let Ok(icc_data) = provider.get_profile_data(&display) {
-                                display_json["icc_size"] =
-                                    serde_json::Value::Number(icc_data.len().into());
+                                entry.icc_size = Some(icc_data.len());
                             }
+                            entry.profiles = provider.get_profiles(&display).ok();
                         }
                     }
-                    Err(ProfileError::ProfileNotAvailable(_)) => {
-                        display_json["profile"] = serde_json::Value::Null;
-                    }
+                    Err(ProfileError::ProfileNotAvailable(_)) => {}
                     Err(e) => {
-                        display_json["profile_error"] = serde_json::Value::String(e.to_string());
+                        entry.profile_error = Some(e.to_string());
                     }
                 }
 
-                json_displays.push(display_json);
+                entries.push(entry);
             }
 
-            let output = serde_json::json!({
-                "displays": json_displays
-            });
+            let report = ListReport::new(entries);
+            print_json(&report)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_assigned_profiles(
+    provider: &dyn DisplayProfileProvider,
+    display: &Display,
+    profiles: &[AssignedProfile],
+) {
+    println!("  Assigned profiles: {}", profiles.len());
+
+    for (index, assigned) in profiles.iter().enumerate() {
+        let marker = if assigned.is_default {
+            " (default)"
+        } else {
+            ""
+        };
+        println!("    {}. {}{}", index + 1, assigned.profile.name, marker);
+
+        if let Some(id) = &assigned.id {
+            println!("       ID: {}", id);
+        }
+        if let Some(kind) = &assigned.kind {
+            println!("       Kind: {}", kind);
+        }
+        if let Some(path) = &assigned.profile.file_path {
+            println!("       File path: {}", path.display());
+        }
+        if let Ok(icc_data) = provider.get_assigned_profile_data(display, assigned) {
+            println!("       ICC size: {} bytes", icc_data.len());
+        }
+        for (key, value) in &assigned.metadata {
+            println!("       {}: {}", key, value);
+        }
+    }
+}
 
-            println!("{}", serde_json::to_string_pretty(&output)?);
+fn handle_paths_command(
+    cli: &Cli,
+    config: &ProfileConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let search_paths: Vec<SearchPath> = config
+        .profile_search_paths()
+        .into_iter()
+        .map(|path| SearchPath {
+            exists: path.is_dir(),
+            profiles: display_icc::find_icc_profiles(std::slice::from_ref(&path)),
+            path,
+        })
+        .collect();
+
+    match cli.format.as_ref().unwrap_or(&OutputFormat::Text) {
+        OutputFormat::Text => {
+            println!("Profile search paths:");
+
+            for search_path in &search_paths {
+                if search_path.exists {
+                    let count = search_path.profiles.len();
+                    println!(
+                        "  {} ({} profile{})",
+                        search_path.path.display(),
+                        count,
+                        if count == 1 { "" } else { "s" }
+                    );
+                } else {
+                    println!("  {} (not found)", search_path.path.display());
+                }
+
+                if cli.verbose {
+                    for profile in &search_path.profiles {
+                        println!("    {}", profile.display());
+                    }
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let report = PathsReport::new(search_paths);
+            print_json(&report)?;
         }
     }
 
@@ -351,14 +668,10 @@ fn handle_export_command(
     cli: &Cli,
     config: ProfileConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let provider = display_icc::create_provider_with_config(config)?;
+    let provider = create_provider(cli, config)?;
 
     let display = if let Some(id) = display_id {
-        let displays = provider.get_displays()?;
-        displays
-            .into_iter()
-            .find(|d| d.id == id)
-            .ok_or(ProfileError::DisplayNotFound(id))?
+        find_display(provider.as_ref(), id)?
     } else {
         provider.get_primary_display()?
     };
@@ -375,16 +688,8 @@ fn handle_export_command(
             println!("Profile size: {} bytes", icc_data.len());
         }
         OutputFormat::Json => {
-            let output = serde_json::json!({
-                "success": true,
-                "display": {
-                    "id": display.id,
-                    "name": display.name
-                },
-                "output_file": output_path,
-                "size_bytes": icc_data.len()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            let report = ExportReport::new(display, output_path, icc_data.len());
+            print_json(&report)?;
         }
     }
 
@@ -396,14 +701,10 @@ fn handle_header_command(
     cli: &Cli,
     config: ProfileConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let provider = display_icc::create_provider_with_config(config)?;
+    let provider = create_provider(cli, config)?;
 
     let display = if let Some(id) = display_id {
-        let displays = provider.get_displays()?;
-        displays
-            .into_iter()
-            .find(|d| d.id == id)
-            .ok_or(ProfileError::DisplayNotFound(id))?
+        find_display(provider.as_ref(), id)?
     } else {
         provider.get_primary_display()?
     };
@@ -443,26 +744,8 @@ fn handle_header_command(
             }
         }
         OutputFormat::Json => {
-            let output = serde_json::json!({
-                "display": {
-                    "id": display.id,
-                    "name": display.name
-                },
-                "icc_header": {
-                    "profile_size": header.profile_size,
-                    "preferred_cmm": header.preferred_cmm,
-                    "version": format!("{}.{}", header.version.0, header.version.1),
-                    "device_class": header.device_class,
-                    "data_color_space": header.data_color_space,
-                    "connection_space": header.connection_space,
-                    "creation_datetime": header.creation_datetime,
-                    "platform": header.platform,
-                    "flags": format!("0x{:08X}", header.flags),
-                    "device_manufacturer": header.device_manufacturer,
-                    "device_model": header.device_model
-                }
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            let report = HeaderReport::new(display, header);
+            print_json(&report)?;
         }
     }
 
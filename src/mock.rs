@@ -1,39 +1,211 @@
-//! Mock implementations for testing
+//! Mock implementations for testing.
+//!
+//! With the `testing` feature these are available to other crates, so code
+//! written against [`DisplayProfileProvider`] can be tested without real
+//! displays. [`MockProfileProvider::builder`] sets up displays with builtin
+//! or loaded ICC profiles, failures and latency, and the provider records
+//! every call for the `assert_*` helpers to check:
+//!
+//! ```rust
+//! use display_icc::mock::{BuiltinProfile, MockMethod, MockProfileProvider};
+//! use display_icc::{Display, DisplayProfileProvider, ProfileError};
+//!
+//! let provider = MockProfileProvider::builder()
+//!     .display(Display::new("dp-1", "Studio Display", true))
+//!     .builtin_profile("dp-1", BuiltinProfile::DisplayP3)
+//!     .display(Display::new("hdmi-1", "Projector", false))
+//!     .fail_for(
+//!         MockMethod::GetProfile,
+//!         "hdmi-1",
+//!         ProfileError::ProfileNotAvailable("hdmi-1".to_string()),
+//!     )
+//!     .build();
+//!
+//! let primary = provider.get_primary_display().unwrap();
+//! assert_eq!(provider.get_profile(&primary).unwrap().name, "Display P3");
+//!
+//! provider.assert_called(MockMethod::GetPrimaryDisplay);
+//! provider.assert_called_with(MockMethod::GetProfile, "dp-1");
+//! provider.assert_not_called(MockMethod::GetProfileData);
+//! ```
+//!
+//! A clone of the provider is independent of the original, as it has always
+//! been. To hand the code under test a provider that can still be changed and
+//! checked from the test, give it a [`shared`](MockProfileProvider::shared)
+//! handle instead of a clone.
 
-use crate::{ColorSpace, Display, DisplayProfileProvider, ProfileError, ProfileInfo};
+use crate::{
+    icc, AssignedProfile, Chromaticity, ColorSpace, Display, DisplayProfileManager,
+    DisplayProfileProvider, Primaries, ProfileError, ProfileInfo, ProfileWatcher,
+};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-/// Mock implementation of DisplayProfileProvider for testing
-#[derive(Debug, Clone)]
+/// Standard RGB color spaces the mock can hand out as real ICC profiles.
+///
+/// The profiles are v4 matrix/TRC display profiles built on the fly. Their
+/// tone curves are pure power laws (2.2 for sRGB and Display P3), which is
+/// close enough to the real curves for anything a test would check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinProfile {
+    /// sRGB (IEC 61966-2-1), D65 white
+    Srgb,
+    /// Display P3: DCI-P3 primaries with a D65 white
+    DisplayP3,
+    /// Adobe RGB (1998), D65 white
+    AdobeRgb,
+}
+
+impl BuiltinProfile {
+    /// The profile's description, which becomes its [`ProfileInfo::name`]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Srgb => "sRGB IEC61966-2.1",
+            Self::DisplayP3 => "Display P3",
+            Self::AdobeRgb => "Adobe RGB (1998)",
+        }
+    }
+
+    /// ICC profile data for the color space
+    pub fn data(self) -> Vec<u8> {
+        let xy = |x, y| Chromaticity { x, y };
+        let (red, green, blue, gamma) = match self {
+            Self::Srgb => (xy(0.64, 0.33), xy(0.30, 0.60), xy(0.15, 0.06), 2.2),
+            Self::DisplayP3 => (xy(0.68, 0.32), xy(0.265, 0.69), xy(0.15, 0.06), 2.2),
+            Self::AdobeRgb => (
+                xy(0.64, 0.33),
+                xy(0.21, 0.71),
+                xy(0.15, 0.06),
+                563.0 / 256.0,
+            ),
+        };
+        let primaries = Primaries { red, green, blue };
+
+        let mut tags = vec![
+            (b"desc", icc::mluc(self.name())),
+            (b"cprt", icc::mluc("No copyright, use freely")),
+        ];
+        tags.extend(
+            icc::matrix_tags(primaries, xy(0.3127, 0.3290), gamma)
+                .expect("builtin primaries are valid"),
+        );
+        icc::write_profile(&tags, [2024, 1, 1, 0, 0, 0])
+    }
+}
+
+/// A [`DisplayProfileProvider`] method, as recorded by [`MockProfileProvider`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockMethod {
+    /// [`DisplayProfileProvider::get_displays`]
+    GetDisplays,
+    /// [`DisplayProfileProvider::get_primary_display`]
+    GetPrimaryDisplay,
+    /// [`DisplayProfileProvider::get_profile`]
+    GetProfile,
+    /// [`DisplayProfileProvider::get_profile_data`]
+    GetProfileData,
+    /// [`DisplayProfileProvider::get_profiles`]
+    GetProfiles,
+}
+
+impl std::fmt::Display for MockMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::GetDisplays => "get_displays",
+            Self::GetPrimaryDisplay => "get_primary_display",
+            Self::GetProfile => "get_profile",
+            Self::GetProfileData => "get_profile_data",
+            Self::GetProfiles => "get_profiles",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A recorded call to a [`MockProfileProvider`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    /// The method called
+    pub method: MockMethod,
+    /// ID of the display passed, for the methods that take one
+    pub display_id: Option<String>,
+}
+
+impl std::fmt::Display for MockCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.display_id {
+            Some(id) => write!(f, "{}({})", self.method, id),
+            None => write!(f, "{}()", self.method),
+        }
+    }
+}
+
+/// Mock implementation of DisplayProfileProvider for testing.
+///
+/// Clones copy the displays, profiles, failures, latency and call log, and
+/// are independent of the original afterwards. Handles from
+/// [`shared`](Self::shared) share all of them instead, and
+/// [`watch`](DisplayProfileProvider::watch) reports the display and profile
+/// changes made through any handle as events.
+#[derive(Debug)]
 pub struct MockProfileProvider {
+    data: Arc<Mutex<MockData>>,
+    /// How often a watcher compares snapshots
+    watch_interval: Duration,
+    /// Whether this is a watcher's poller, which takes no latency
+    polling: bool,
+    calls: Arc<Mutex<Vec<MockCall>>>,
+}
+
+/// Displays, profiles, failures and latency, shared between handles
+#[derive(Debug, Default, Clone)]
+struct MockData {
     displays: Vec<Display>,
     profiles: HashMap<String, ProfileInfo>,
     profile_data: HashMap<String, Vec<u8>>,
-    should_fail: HashMap<String, ProfileError>,
+    /// Failures by method, for one display or (`None`) for all
+    method_failures: HashMap<(MockMethod, Option<String>), ProfileError>,
+    latency: Duration,
 }
 
 impl MockProfileProvider {
     /// Create a new mock provider with no displays
     pub fn new() -> Self {
         Self {
-            displays: Vec::new(),
-            profiles: HashMap::new(),
-            profile_data: HashMap::new(),
-            should_fail: HashMap::new(),
+            data: Arc::new(Mutex::new(MockData::default())),
+            watch_interval: Duration::from_millis(10),
+            polling: false,
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// A handle on this provider's displays, profiles, failures, latency and
+    /// call log, for handing to the code under test.
+    ///
+    /// Unlike a clone, the handle sees the changes made through this provider
+    /// afterwards, and the calls made through it are recorded here too.
+    pub fn shared(&self) -> Self {
+        Self {
+            data: Arc::clone(&self.data),
+            watch_interval: self.watch_interval,
+            polling: self.polling,
+            calls: Arc::clone(&self.calls),
         }
     }
 
+    /// Start building a mock provider
+    pub fn builder() -> MockProviderBuilder {
+        MockProviderBuilder::default()
+    }
+
     /// Create a mock provider with typical test data
     pub fn with_test_data() -> Self {
         let mut provider = Self::new();
 
         // Add primary display
-        let primary_display = Display {
-            id: "primary".to_string(),
-            name: "Primary Display".to_string(),
-            is_primary: true,
-        };
+        let primary_display =
+            Display::new("primary".to_string(), "Primary Display".to_string(), true);
 
         let primary_profile = ProfileInfo {
             name: "sRGB IEC61966-2.1".to_string(),
@@ -56,11 +228,11 @@ impl MockProfileProvider {
         provider.set_profile_data("primary", icc_data);
 
         // Add secondary display
-        let secondary_display = Display {
-            id: "secondary".to_string(),
-            name: "Secondary Display".to_string(),
-            is_primary: false,
-        };
+        let secondary_display = Display::new(
+            "secondary".to_string(),
+            "Secondary Display".to_string(),
+            false,
+        );
 
         let secondary_profile = ProfileInfo {
             name: "Display P3".to_string(),
@@ -86,30 +258,239 @@ impl MockProfileProvider {
 
     /// Add a display to the mock provider
     pub fn add_display(&mut self, display: Display) {
-        self.displays.push(display);
+        self.data.lock().unwrap().displays.push(display);
+    }
+
+    /// Remove a display, with its profile
+    pub fn remove_display(&mut self, display_id: &str) {
+        let mut data = self.data.lock().unwrap();
+        data.displays.retain(|display| display.id != display_id);
+        data.profiles.remove(display_id);
+        data.profile_data.remove(display_id);
+    }
+
+    /// Make the display with `display_id` the only primary display
+    pub fn set_primary_display(&mut self, display_id: &str) {
+        for display in &mut self.data.lock().unwrap().displays {
+            display.is_primary = display.id == display_id;
+        }
     }
 
     /// Set profile information for a display
     pub fn set_profile(&mut self, display_id: &str, profile: ProfileInfo) {
-        self.profiles.insert(display_id.to_string(), profile);
+        let mut data = self.data.lock().unwrap();
+        data.profiles.insert(display_id.to_string(), profile);
     }
 
     /// Set profile data for a display
     pub fn set_profile_data(&mut self, display_id: &str, data: Vec<u8>) {
-        self.profile_data.insert(display_id.to_string(), data);
+        let mut state = self.data.lock().unwrap();
+        state.profile_data.insert(display_id.to_string(), data);
     }
 
-    /// Configure a method to fail for a specific display
+    /// Make the profile methods fail for the display with `display_id`.
+    ///
+    /// `"get_displays"` and `"get_primary_display"` stand for those methods
+    /// instead.
     pub fn set_failure(&mut self, display_id: &str, error: ProfileError) {
-        self.should_fail.insert(display_id.to_string(), error);
+        match display_id {
+            "get_displays" => self.set_method_failure(MockMethod::GetDisplays, None, error),
+            "get_primary_display" => {
+                self.set_method_failure(MockMethod::GetPrimaryDisplay, None, error)
+            }
+            _ => self.set_display_failure(display_id, error),
+        }
+    }
+
+    /// Make the profile methods fail with `error` for the display with `display_id`
+    pub fn set_display_failure(&mut self, display_id: &str, error: ProfileError) {
+        for method in [
+            MockMethod::GetProfile,
+            MockMethod::GetProfileData,
+            MockMethod::GetProfiles,
+        ] {
+            self.set_method_failure(method, Some(display_id), error.clone());
+        }
+    }
+
+    /// Make `method` fail with `error`, for one display or (`None`) for all
+    pub fn set_method_failure(
+        &mut self,
+        method: MockMethod,
+        display_id: Option<&str>,
+        error: ProfileError,
+    ) {
+        let mut data = self.data.lock().unwrap();
+        data.method_failures
+            .insert((method, display_id.map(str::to_string)), error);
+    }
+
+    /// Remove the failures set up so far
+    pub fn clear_failures(&mut self) {
+        self.data.lock().unwrap().method_failures.clear();
+    }
+
+    /// Make every call take at least `latency`
+    pub fn set_latency(&mut self, latency: Duration) {
+        self.data.lock().unwrap().latency = latency;
+    }
+
+    /// Make watchers compare snapshots every `interval` instead of every 10ms
+    pub fn set_watch_interval(&mut self, interval: Duration) {
+        self.watch_interval = interval;
     }
 
-    /// Remove all displays
+    /// Remove all displays, with their profiles and failures
     pub fn clear_displays(&mut self) {
-        self.displays.clear();
-        self.profiles.clear();
-        self.profile_data.clear();
-        self.should_fail.clear();
+        let mut data = self.data.lock().unwrap();
+        data.displays.clear();
+        data.profiles.clear();
+        data.profile_data.clear();
+        data.method_failures.clear();
+    }
+
+    /// Every call so far, oldest first
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// The number of calls to `method`
+    pub fn call_count(&self, method: MockMethod) -> usize {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|call| call.method == method)
+            .count()
+    }
+
+    /// Forget the calls so far
+    pub fn clear_calls(&self) {
+        self.calls.lock().unwrap().clear();
+    }
+
+    /// Panic unless `method` was called
+    #[track_caller]
+    pub fn assert_called(&self, method: MockMethod) {
+        assert!(
+            self.call_count(method) > 0,
+            "expected a call to {}, got {}",
+            method,
+            self.describe_calls()
+        );
+    }
+
+    /// Panic unless `method` was called for the display with `display_id`
+    #[track_caller]
+    pub fn assert_called_with(&self, method: MockMethod, display_id: &str) {
+        let called = self
+            .calls()
+            .iter()
+            .any(|call| call.method == method && call.display_id.as_deref() == Some(display_id));
+        assert!(
+            called,
+            "expected a call to {}({}), got {}",
+            method,
+            display_id,
+            self.describe_calls()
+        );
+    }
+
+    /// Panic if `method` was called
+    #[track_caller]
+    pub fn assert_not_called(&self, method: MockMethod) {
+        assert!(
+            self.call_count(method) == 0,
+            "expected no call to {}, got {}",
+            method,
+            self.describe_calls()
+        );
+    }
+
+    /// Panic unless `method` was called exactly `count` times
+    #[track_caller]
+    pub fn assert_call_count(&self, method: MockMethod, count: usize) {
+        let actual = self.call_count(method);
+        assert!(
+            actual == count,
+            "expected {} call(s) to {}, got {}: {}",
+            count,
+            method,
+            actual,
+            self.describe_calls()
+        );
+    }
+
+    /// Panic unless exactly these methods were called, in this order
+    #[track_caller]
+    pub fn assert_calls(&self, methods: &[MockMethod]) {
+        let actual: Vec<MockMethod> = self.calls().iter().map(|call| call.method).collect();
+        assert!(
+            actual == methods,
+            "expected calls {:?}, got {}",
+            methods,
+            self.describe_calls()
+        );
+    }
+
+    /// The call log for assertion messages
+    fn describe_calls(&self) -> String {
+        let calls = self.calls();
+        if calls.is_empty() {
+            return "no calls".to_string();
+        }
+        calls
+            .iter()
+            .map(MockCall::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Record a call, wait out the latency and return any failure set up for it
+    fn enter(&self, method: MockMethod, display: Option<&Display>) -> Result<(), ProfileError> {
+        let display_id = display.map(|display| display.id.clone());
+        self.calls.lock().unwrap().push(MockCall {
+            method,
+            display_id: display_id.clone(),
+        });
+        let (latency, failure) = {
+            let data = self.data.lock().unwrap();
+            let failure = data
+                .method_failures
+                .get(&(method, display_id))
+                .or_else(|| data.method_failures.get(&(method, None)))
+                .cloned();
+            (data.latency, failure)
+        };
+        // Sleep without the lock, so that concurrent calls overlap
+        if !self.polling && !latency.is_zero() {
+            std::thread::sleep(latency);
+        }
+
+        match failure {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Profile information for a display, without recording a call
+    fn profile_info(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
+        let data = self.data.lock().unwrap();
+        data.profiles
+            .get(&display.id)
+            .cloned()
+            .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()))
+    }
+}
+
+impl Clone for MockProfileProvider {
+    fn clone(&self) -> Self {
+        Self {
+            data: Arc::new(Mutex::new(self.data.lock().unwrap().clone())),
+            watch_interval: self.watch_interval,
+            polling: self.polling,
+            calls: Arc::new(Mutex::new(self.calls())),
+        }
     }
 }
 
@@ -121,18 +502,14 @@ impl Default for MockProfileProvider {
 
 impl DisplayProfileProvider for MockProfileProvider {
     fn get_displays(&self) -> Result<Vec<Display>, ProfileError> {
-        if self.should_fail.contains_key("get_displays") {
-            return Err(self.should_fail["get_displays"].clone());
-        }
-        Ok(self.displays.clone())
+        self.enter(MockMethod::GetDisplays, None)?;
+        Ok(self.data.lock().unwrap().displays.clone())
     }
 
     fn get_primary_display(&self) -> Result<Display, ProfileError> {
-        if self.should_fail.contains_key("get_primary_display") {
-            return Err(self.should_fail["get_primary_display"].clone());
-        }
-
-        self.displays
+        self.enter(MockMethod::GetPrimaryDisplay, None)?;
+        let data = self.data.lock().unwrap();
+        data.displays
             .iter()
             .find(|d| d.is_primary)
             .cloned()
@@ -140,28 +517,238 @@ impl DisplayProfileProvider for MockProfileProvider {
     }
 
     fn get_profile(&self, display: &Display) -> Result<ProfileInfo, ProfileError> {
-        if let Some(error) = self.should_fail.get(&display.id) {
-            return Err(error.clone());
-        }
+        self.enter(MockMethod::GetProfile, Some(display))?;
+        self.profile_info(display)
+    }
 
-        self.profiles
+    fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
+        self.enter(MockMethod::GetProfileData, Some(display))?;
+        let data = self.data.lock().unwrap();
+        data.profile_data
             .get(&display.id)
             .cloned()
             .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()))
     }
 
-    fn get_profile_data(&self, display: &Display) -> Result<Vec<u8>, ProfileError> {
-        if let Some(error) = self.should_fail.get(&display.id) {
-            return Err(error.clone());
+    fn get_profiles(&self, display: &Display) -> Result<Vec<AssignedProfile>, ProfileError> {
+        self.enter(MockMethod::GetProfiles, Some(display))?;
+        match self.profile_info(display) {
+            Ok(profile) => Ok(vec![AssignedProfile::new(profile, true)]),
+            Err(ProfileError::ProfileNotAvailable(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
         }
+    }
 
-        self.profile_data
-            .get(&display.id)
-            .cloned()
-            .ok_or_else(|| ProfileError::ProfileNotAvailable(display.id.clone()))
+    /// Poll a [`shared`](Self::shared) handle, which sees changes made through
+    /// this provider; the handle's calls are not recorded and take no latency
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        let poller = Self {
+            calls: Arc::new(Mutex::new(Vec::new())),
+            polling: true,
+            ..self.shared()
+        };
+        ProfileWatcher::polling(poller, self.watch_interval)
     }
 }
 
+/// Builder for [`MockProfileProvider`], from [`MockProfileProvider::builder`]
+#[derive(Debug, Default)]
+pub struct MockProviderBuilder {
+    provider: MockProfileProvider,
+}
+
+impl MockProviderBuilder {
+    /// Add a display
+    pub fn display(mut self, display: Display) -> Self {
+        self.provider.add_display(display);
+        self
+    }
+
+    /// Give a display one of the builtin profiles
+    pub fn builtin_profile(self, display_id: &str, profile: BuiltinProfile) -> Self {
+        self.icc_profile(display_id, profile.data())
+    }
+
+    /// Give a display the profile in `data`, named after its `desc` tag
+    pub fn icc_profile(mut self, display_id: &str, data: Vec<u8>) -> Self {
+        let info = icc::profile_info(&data, None);
+        self.provider.set_profile(display_id, info);
+        self.provider.set_profile_data(display_id, data);
+        self
+    }
+
+    /// Give a display the profile in the file at `path`
+    pub fn icc_file(mut self, display_id: &str, path: &Path) -> Result<Self, ProfileError> {
+        let data = std::fs::read(path)
+            .map_err(|e| ProfileError::IoError(format!("{}: {}", path.display(), e)))?;
+        let info = icc::profile_info(&data, Some(path));
+        self.provider.set_profile(display_id, info);
+        self.provider.set_profile_data(display_id, data);
+        Ok(self)
+    }
+
+    /// Give a display profile information, without profile data
+    pub fn profile_info(mut self, display_id: &str, profile: ProfileInfo) -> Self {
+        self.provider.set_profile(display_id, profile);
+        self
+    }
+
+    /// Make every call to `method` fail with `error`
+    pub fn fail(mut self, method: MockMethod, error: ProfileError) -> Self {
+        self.provider.set_method_failure(method, None, error);
+        self
+    }
+
+    /// Make calls to `method` for the display with `display_id` fail with `error`
+    pub fn fail_for(mut self, method: MockMethod, display_id: &str, error: ProfileError) -> Self {
+        self.provider
+            .set_method_failure(method, Some(display_id), error);
+        self
+    }
+
+    /// Make every call take at least `latency`
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.provider.set_latency(latency);
+        self
+    }
+
+    /// Make watchers compare snapshots every `interval`
+    pub fn watch_interval(mut self, interval: Duration) -> Self {
+        self.provider.set_watch_interval(interval);
+        self
+    }
+
+    /// Finish building the provider
+    pub fn build(self) -> MockProfileProvider {
+        self.provider
+    }
+}
+
+/// Mock implementation of DisplayProfileManager for testing
+///
+/// Keeps registered profiles and assignments in memory without touching the
+/// file system. Profile IDs are `mock-` followed by the file stem, and the
+/// first profile assigned to a display is its default.
+#[derive(Debug, Default)]
+pub struct MockProfileManager {
+    state: Mutex<MockProfileState>,
+}
+
+#[derive(Debug, Default)]
+struct MockProfileState {
+    /// Registered profile files by ID
+    profiles: HashMap<String, PathBuf>,
+    /// Assigned profile IDs by display ID, default first
+    assigned: HashMap<String, Vec<String>>,
+}
+
+impl MockProfileManager {
+    /// Create a new mock manager with no displays or profiles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a display with no profiles assigned
+    pub fn add_display(&mut self, display: &Display) {
+        self.state
+            .get_mut()
+            .unwrap()
+            .assigned
+            .insert(display.id.clone(), Vec::new());
+    }
+
+    /// Profile IDs assigned to a display, default first
+    pub fn assigned(&self, display_id: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state.assigned.get(display_id).cloned().unwrap_or_default()
+    }
+
+    /// The file a registered profile points at
+    pub fn profile_path(&self, profile_id: &str) -> Option<PathBuf> {
+        let state = self.state.lock().unwrap();
+        state.profiles.get(profile_id).cloned()
+    }
+
+    /// Check that both IDs are known, then change the display's assignments
+    fn change(
+        &self,
+        display: &Display,
+        profile_id: &str,
+        change: impl FnOnce(&mut Vec<String>) -> Result<(), ProfileError>,
+    ) -> Result<(), ProfileError> {
+        let mut state = self.state.lock().unwrap();
+        if !state.profiles.contains_key(profile_id) {
+            return Err(ProfileError::ProfileNotAvailable(profile_id.to_string()));
+        }
+        let assigned = state
+            .assigned
+            .get_mut(&display.id)
+            .ok_or_else(|| ProfileError::DisplayNotFound(display.id.clone()))?;
+        change(assigned)
+    }
+}
+
+impl DisplayProfileManager for MockProfileManager {
+    fn import_profile(&self, path: &Path) -> Result<String, ProfileError> {
+        self.create_profile(path)
+    }
+
+    fn create_profile(&self, path: &Path) -> Result<String, ProfileError> {
+        let stem = path
+            .file_stem()
+            .ok_or_else(|| ProfileError::IoError(format!("{} is not a file", path.display())))?;
+        let profile_id = format!("mock-{}", stem.to_string_lossy());
+
+        let mut state = self.state.lock().unwrap();
+        state
+            .profiles
+            .insert(profile_id.clone(), path.to_path_buf());
+        Ok(profile_id)
+    }
+
+    fn add_profile(&self, display: &Display, profile_id: &str) -> Result<(), ProfileError> {
+        self.change(display, profile_id, |assigned| {
+            if assigned.iter().any(|id| id == profile_id) {
+                return Err(ProfileError::SystemError(format!(
+                    "{} is already assigned",
+                    profile_id
+                )));
+            }
+            assigned.push(profile_id.to_string());
+            Ok(())
+        })
+    }
+
+    fn make_profile_default(
+        &self,
+        display: &Display,
+        profile_id: &str,
+    ) -> Result<(), ProfileError> {
+        self.change(display, profile_id, |assigned| {
+            let index = position(assigned, profile_id)?;
+            let profile = assigned.remove(index);
+            assigned.insert(0, profile);
+            Ok(())
+        })
+    }
+
+    fn remove_profile(&self, display: &Display, profile_id: &str) -> Result<(), ProfileError> {
+        self.change(display, profile_id, |assigned| {
+            let index = position(assigned, profile_id)?;
+            assigned.remove(index);
+            Ok(())
+        })
+    }
+}
+
+/// Index of `profile_id` among a display's assigned profiles
+fn position(assigned: &[String], profile_id: &str) -> Result<usize, ProfileError> {
+    assigned
+        .iter()
+        .position(|id| id == profile_id)
+        .ok_or_else(|| ProfileError::SystemError(format!("{} is not assigned", profile_id)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,11 +794,7 @@ mod tests {
     fn test_mock_provider_add_display() {
         let mut provider = MockProfileProvider::new();
 
-        let display = Display {
-            id: "test".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display = Display::new("test".to_string(), "Test Display".to_string(), true);
 
         provider.add_display(display.clone());
 
@@ -224,11 +807,7 @@ mod tests {
     fn test_mock_provider_set_profile() {
         let mut provider = MockProfileProvider::new();
 
-        let display = Display {
-            id: "test".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display = Display::new("test".to_string(), "Test Display".to_string(), true);
 
         let profile = ProfileInfo {
             name: "Test Profile".to_string(),
@@ -248,11 +827,7 @@ mod tests {
     fn test_mock_provider_set_profile_data() {
         let mut provider = MockProfileProvider::new();
 
-        let display = Display {
-            id: "test".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display = Display::new("test".to_string(), "Test Display".to_string(), true);
 
         let test_data = vec![1, 2, 3, 4, 5];
 
@@ -267,11 +842,7 @@ mod tests {
     fn test_mock_provider_failures() {
         let mut provider = MockProfileProvider::new();
 
-        let display = Display {
-            id: "test".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display = Display::new("test".to_string(), "Test Display".to_string(), true);
 
         provider.add_display(display.clone());
         provider.set_failure("test", ProfileError::SystemError("Mock error".to_string()));
@@ -290,11 +861,7 @@ mod tests {
     fn test_mock_provider_profile_not_available() {
         let mut provider = MockProfileProvider::new();
 
-        let display = Display {
-            id: "test".to_string(),
-            name: "Test Display".to_string(),
-            is_primary: true,
-        };
+        let display = Display::new("test".to_string(), "Test Display".to_string(), true);
 
         provider.add_display(display.clone());
         // Don't set profile - should return ProfileNotAvailable
@@ -321,4 +888,251 @@ mod tests {
         assert!(provider.get_displays().unwrap().is_empty());
         assert!(provider.get_primary_display().is_err());
     }
+
+    #[test]
+    fn test_builtin_profiles() {
+        use crate::{parse_icc_header, Colorimetry};
+
+        for (profile, green) in [
+            (BuiltinProfile::Srgb, (0.30, 0.60)),
+            (BuiltinProfile::DisplayP3, (0.265, 0.69)),
+            (BuiltinProfile::AdobeRgb, (0.21, 0.71)),
+        ] {
+            let data = profile.data();
+            let header = parse_icc_header(&data).unwrap();
+            assert_eq!(header.device_class, "mntr");
+            assert_eq!(header.data_color_space, "RGB ");
+
+            let colorimetry = Colorimetry::from_icc(&data).unwrap();
+            let primaries = colorimetry.primaries.unwrap();
+            assert!((primaries.green.x - green.0).abs() < 0.001, "{:?}", profile);
+            assert!((primaries.green.y - green.1).abs() < 0.001, "{:?}", profile);
+            let white = colorimetry.white_point.unwrap();
+            assert!((white.x - 0.3127).abs() < 0.001 && (white.y - 0.3290).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("calibrated.icc");
+        std::fs::write(&path, BuiltinProfile::AdobeRgb.data()).unwrap();
+
+        let provider = MockProfileProvider::builder()
+            .display(Display::new("dp-1", "Studio Display", true))
+            .builtin_profile("dp-1", BuiltinProfile::DisplayP3)
+            .display(Display::new("dp-2", "Proof Display", false))
+            .icc_file("dp-2", &path)
+            .unwrap()
+            .display(Display::new("hdmi-1", "Projector", false))
+            .build();
+
+        let displays = provider.get_displays().unwrap();
+        assert_eq!(displays.len(), 3);
+        assert_eq!(provider.get_primary_display().unwrap().id, "dp-1");
+
+        let studio = provider.get_profile(&displays[0]).unwrap();
+        assert_eq!(studio.name, "Display P3");
+        assert_eq!(studio.color_space, ColorSpace::RGB);
+        assert_eq!(studio.file_path, None);
+        assert_eq!(
+            provider.get_profile_data(&displays[0]).unwrap(),
+            BuiltinProfile::DisplayP3.data()
+        );
+
+        let proof = provider.get_profile(&displays[1]).unwrap();
+        assert_eq!(proof.name, "Adobe RGB (1998)");
+        assert_eq!(proof.file_path, Some(path));
+
+        assert!(provider.get_profiles(&displays[2]).unwrap().is_empty());
+        assert!(matches!(
+            MockProfileProvider::builder().icc_file("dp-1", &dir.path().join("gone.icc")),
+            Err(ProfileError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_builder_failures_and_latency() {
+        let provider = MockProfileProvider::builder()
+            .display(Display::new("dp-1", "Studio Display", true))
+            .builtin_profile("dp-1", BuiltinProfile::Srgb)
+            .display(Display::new("dp-2", "Proof Display", false))
+            .builtin_profile("dp-2", BuiltinProfile::Srgb)
+            .fail_for(
+                MockMethod::GetProfileData,
+                "dp-2",
+                ProfileError::IoError("unreadable".to_string()),
+            )
+            .fail(
+                MockMethod::GetProfiles,
+                ProfileError::Timeout("colord".to_string()),
+            )
+            .latency(Duration::from_millis(20))
+            .build();
+
+        let start = std::time::Instant::now();
+        let displays = provider.get_displays().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        assert!(provider.get_profile_data(&displays[0]).is_ok());
+        assert!(matches!(
+            provider.get_profile_data(&displays[1]),
+            Err(ProfileError::IoError(msg)) if msg == "unreadable"
+        ));
+        // Only the data fails, not the profile information
+        assert!(provider.get_profile(&displays[1]).is_ok());
+        for display in &displays {
+            assert!(matches!(
+                provider.get_profiles(display),
+                Err(ProfileError::Timeout(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_clones_are_independent() {
+        let mut provider = MockProfileProvider::with_test_data();
+        let clone = provider.clone();
+        provider.clear_displays();
+        provider.set_latency(Duration::from_secs(60));
+        assert_eq!(clone.get_displays().unwrap().len(), 2);
+        clone.assert_calls(&[MockMethod::GetDisplays]);
+        provider.assert_calls(&[]);
+    }
+
+    #[test]
+    fn test_shared_handles_share_failures_and_latency() {
+        let mut provider = MockProfileProvider::with_test_data();
+        let handle = provider.shared();
+
+        provider.set_method_failure(
+            MockMethod::GetDisplays,
+            None,
+            ProfileError::SystemError("busy".to_string()),
+        );
+        provider.set_latency(Duration::from_millis(20));
+        let start = std::time::Instant::now();
+        assert!(matches!(
+            handle.get_displays(),
+            Err(ProfileError::SystemError(msg)) if msg == "busy"
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        // Clearing the displays clears the failures with them
+        provider.clear_displays();
+        assert_eq!(handle.get_displays().unwrap(), []);
+    }
+
+    #[test]
+    fn test_set_failure_method_names() {
+        let mut provider = MockProfileProvider::with_test_data();
+        provider.set_failure(
+            "get_primary_display",
+            ProfileError::SystemError("busy".to_string()),
+        );
+        assert!(provider.get_primary_display().is_err());
+        assert!(provider.get_displays().is_ok());
+    }
+
+    #[test]
+    fn test_call_recording() {
+        let provider = MockProfileProvider::with_test_data();
+        // Shared handles record their calls in the provider's log
+        let handed_out = provider.shared();
+        provider.assert_calls(&[]);
+
+        let primary = handed_out.get_primary_display().unwrap();
+        handed_out.get_profile(&primary).unwrap();
+        handed_out.get_profile_data(&primary).unwrap();
+        handed_out.get_profile_data(&primary).unwrap();
+
+        provider.assert_called(MockMethod::GetPrimaryDisplay);
+        provider.assert_called_with(MockMethod::GetProfile, "primary");
+        provider.assert_call_count(MockMethod::GetProfileData, 2);
+        provider.assert_not_called(MockMethod::GetDisplays);
+        provider.assert_calls(&[
+            MockMethod::GetPrimaryDisplay,
+            MockMethod::GetProfile,
+            MockMethod::GetProfileData,
+            MockMethod::GetProfileData,
+        ]);
+        assert_eq!(
+            provider.calls()[1],
+            MockCall {
+                method: MockMethod::GetProfile,
+                display_id: Some("primary".to_string()),
+            }
+        );
+
+        // Default methods are recorded as the calls they make
+        handed_out.find_display("id:secondary").unwrap();
+        provider.assert_call_count(MockMethod::GetDisplays, 1);
+
+        provider.clear_calls();
+        assert!(handed_out.calls().is_empty());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "expected a call to get_profile(secondary), got get_profile(primary)"
+    )]
+    fn test_assert_called_with_reports_calls() {
+        let provider = MockProfileProvider::with_test_data();
+        let primary = provider.get_primary_display().unwrap();
+        provider.clear_calls();
+        provider.get_profile(&primary).unwrap();
+        provider.assert_called_with(MockMethod::GetProfile, "secondary");
+    }
+
+    #[test]
+    fn test_mock_manager_install_profile() {
+        let mut manager = MockProfileManager::new();
+        let display = Display::new("test".to_string(), "Test Display".to_string(), true);
+        manager.add_display(&display);
+
+        let edid = manager.create_profile(Path::new("/tmp/edid.icc")).unwrap();
+        manager.add_profile(&display, &edid).unwrap();
+
+        // install_profile's default implementation imports, assigns and makes default
+        let calibrated = manager
+            .install_profile(&display, Path::new("/tmp/calibrated.icc"))
+            .unwrap();
+        assert_eq!(calibrated, "mock-calibrated");
+        assert_eq!(manager.assigned("test"), ["mock-calibrated", "mock-edid"]);
+        assert_eq!(
+            manager.profile_path(&calibrated),
+            Some(PathBuf::from("/tmp/calibrated.icc"))
+        );
+
+        manager.remove_profile(&display, &edid).unwrap();
+        assert_eq!(manager.assigned("test"), ["mock-calibrated"]);
+    }
+
+    #[test]
+    fn test_mock_manager_errors() {
+        let mut manager = MockProfileManager::new();
+        let display = Display::new("test".to_string(), "Test Display".to_string(), true);
+        let unknown = Display::new("gone".to_string(), "Gone".to_string(), false);
+        manager.add_display(&display);
+        let profile = manager.import_profile(Path::new("/tmp/edid.icc")).unwrap();
+
+        assert!(matches!(
+            manager.add_profile(&unknown, &profile),
+            Err(ProfileError::DisplayNotFound(_))
+        ));
+        assert!(matches!(
+            manager.add_profile(&display, "mock-missing"),
+            Err(ProfileError::ProfileNotAvailable(_))
+        ));
+        assert!(matches!(
+            manager.make_profile_default(&display, &profile),
+            Err(ProfileError::SystemError(_))
+        ));
+
+        manager.add_profile(&display, &profile).unwrap();
+        assert!(matches!(
+            manager.add_profile(&display, &profile),
+            Err(ProfileError::SystemError(_))
+        ));
+    }
 }
@@ -0,0 +1,346 @@
+//! JSON documents emitted by the `display_icc` command-line tool.
+//!
+//! Each subcommand run with `--format json` prints exactly one of the report
+//! types in this module. They are public so that downstream tools can
+//! deserialize the CLI output with the same types that produced it, which
+//! implement `Serialize` and `Deserialize` with the `serde` feature.
+//!
+//! # Versioning
+//!
+//! Every document carries a top-level `schema_version` field equal to
+//! [`SCHEMA_VERSION`]. The version is bumped whenever a field is removed,
+//! renamed or changes type; adding a new optional field does not bump it.
+//!
+//! With the `schema` feature, [`json_schema`] returns the JSON Schema describing
+//! all documents. The same schema is checked in at `schema/display_icc.schema.json`
+//! and printed by `display_icc schema`.
+
+use crate::{AssignedProfile, Colorimetry, Display, IccHeader, ProfileInfo};
+use std::path::PathBuf;
+
+/// Version of the JSON documents described in this module.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Document emitted by `display_icc info --format json`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InfoReport {
+    /// Always [`SCHEMA_VERSION`].
+    #[cfg_attr(feature = "schema", schemars(extend("const" = SCHEMA_VERSION)))]
+    pub schema_version: u32,
+    /// The display that was queried.
+    pub display: Display,
+    /// The profile assigned to the display.
+    pub profile: ProfileInfo,
+    /// Size of the raw ICC data in bytes (`--verbose` only, otherwise `null`).
+    pub icc_size: Option<usize>,
+    /// Parsed ICC header (`--verbose` only, otherwise `null`).
+    pub icc_header: Option<IccHeader>,
+    /// White point, primaries, gamma, luminance and calibration status, or
+    /// `null` if the profile data could not be read or has no tag table.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub colorimetry: Option<Colorimetry>,
+}
+
+impl InfoReport {
+    /// Create a report for a display and its profile, without ICC details
+    pub fn new(display: Display, profile: ProfileInfo) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            display,
+            profile,
+            icc_size: None,
+            icc_header: None,
+            colorimetry: None,
+        }
+    }
+}
+
+/// Document emitted by `display_icc list --format json`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ListReport {
+    /// Always [`SCHEMA_VERSION`].
+    #[cfg_attr(feature = "schema", schemars(extend("const" = SCHEMA_VERSION)))]
+    pub schema_version: u32,
+    /// One entry per display, in enumeration order.
+    pub displays: Vec<ListEntry>,
+}
+
+impl ListReport {
+    /// Create a report from a list of entries
+    pub fn new(displays: Vec<ListEntry>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            displays,
+        }
+    }
+}
+
+/// One display in a [`ListReport`].
+///
+/// The display fields are inlined into the entry. At most one of `profile` and
+/// `profile_error` is non-null; both are `null` when the display simply has no
+/// profile assigned.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ListEntry {
+    /// The display this entry describes.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub display: Display,
+    /// The profile assigned to the display, if any.
+    pub profile: Option<ProfileInfo>,
+    /// Error message if the profile lookup failed for a reason other than
+    /// "no profile assigned".
+    pub profile_error: Option<String>,
+    /// Size of the raw ICC data in bytes (`--verbose` only, otherwise `null`).
+    pub icc_size: Option<usize>,
+    /// Every profile assigned to the display in relevance order, default first
+    /// (`--verbose` only, otherwise `null`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub profiles: Option<Vec<AssignedProfile>>,
+}
+
+impl ListEntry {
+    /// Create an entry for a display with no profile information yet
+    pub fn new(display: Display) -> Self {
+        Self {
+            display,
+            profile: None,
+            profile_error: None,
+            icc_size: None,
+            profiles: None,
+        }
+    }
+}
+
+/// Document emitted by `display_icc export --format json`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ExportReport {
+    /// Always [`SCHEMA_VERSION`].
+    #[cfg_attr(feature = "schema", schemars(extend("const" = SCHEMA_VERSION)))]
+    pub schema_version: u32,
+    /// Always `true`; failed exports exit with an error instead.
+    pub success: bool,
+    /// The display whose profile was exported.
+    pub display: Display,
+    /// Path the ICC data was written to, as given on the command line.
+    pub output_file: String,
+    /// Number of bytes written.
+    pub size_bytes: usize,
+}
+
+impl ExportReport {
+    /// Create a report for a successful export
+    pub fn new(display: Display, output_file: String, size_bytes: usize) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            success: true,
+            display,
+            output_file,
+            size_bytes,
+        }
+    }
+}
+
+/// Document emitted by `display_icc header --format json`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HeaderReport {
+    /// Always [`SCHEMA_VERSION`].
+    #[cfg_attr(feature = "schema", schemars(extend("const" = SCHEMA_VERSION)))]
+    pub schema_version: u32,
+    /// The display whose profile header was parsed.
+    pub display: Display,
+    /// The parsed ICC header.
+    pub icc_header: IccHeader,
+}
+
+impl HeaderReport {
+    /// Create a report for a display and its parsed header
+    pub fn new(display: Display, icc_header: IccHeader) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            display,
+            icc_header,
+        }
+    }
+}
+
+/// Document emitted by `display_icc paths --format json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PathsReport {
+    /// Always [`SCHEMA_VERSION`].
+    #[cfg_attr(feature = "schema", schemars(extend("const" = SCHEMA_VERSION)))]
+    pub schema_version: u32,
+    /// Directories searched for ICC profiles, in search order.
+    pub search_paths: Vec<SearchPath>,
+}
+
+impl PathsReport {
+    /// Create a report from a list of search paths
+    pub fn new(search_paths: Vec<SearchPath>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            search_paths,
+        }
+    }
+}
+
+/// One directory in a [`PathsReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SearchPath {
+    /// The directory.
+    pub path: PathBuf,
+    /// Whether the directory exists.
+    pub exists: bool,
+    /// ICC profiles found under the directory, including subdirectories.
+    pub profiles: Vec<PathBuf>,
+}
+
+/// Any document the CLI can emit, used as the root of the JSON Schema.
+#[cfg(feature = "schema")]
+#[derive(schemars::JsonSchema)]
+#[schemars(title = "display_icc CLI output", untagged)]
+#[allow(dead_code, clippy::large_enum_variant)]
+enum CliOutput {
+    Info(InfoReport),
+    List(ListReport),
+    Export(ExportReport),
+    Header(HeaderReport),
+    Paths(PathsReport),
+}
+
+/// Generate the JSON Schema describing every document in this module.
+///
+/// Each report type is available under `$defs` by its Rust name (for example
+/// `#/$defs/InfoReport`), and the root schema accepts any of them.
+#[cfg(feature = "schema")]
+pub fn json_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(CliOutput))
+        .expect("JSON Schema is always serializable")
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::ColorSpace;
+
+    fn test_display() -> Display {
+        Display::new("primary".to_string(), "Primary Display".to_string(), true)
+    }
+
+    #[test]
+    fn test_reports_carry_schema_version() {
+        let profile = ProfileInfo {
+            name: "sRGB".to_string(),
+            description: None,
+            file_path: None,
+            color_space: ColorSpace::RGB,
+        };
+
+        let info = serde_json::to_value(InfoReport::new(test_display(), profile)).unwrap();
+        let list = serde_json::to_value(ListReport::new(Vec::new())).unwrap();
+        let paths = serde_json::to_value(PathsReport::new(Vec::new())).unwrap();
+        let export = serde_json::to_value(ExportReport::new(
+            test_display(),
+            "out.icc".to_string(),
+            128,
+        ))
+        .unwrap();
+
+        for report in [info, list, export, paths] {
+            assert_eq!(report["schema_version"], SCHEMA_VERSION);
+        }
+    }
+
+    #[test]
+    fn test_list_entry_has_stable_keys() {
+        let entry = serde_json::to_value(ListEntry::new(test_display())).unwrap();
+
+        assert_eq!(entry["id"], "primary");
+        assert_eq!(entry["is_primary"], true);
+        assert!(entry["profile"].is_null());
+        assert!(entry["profile_error"].is_null());
+        assert!(entry["icc_size"].is_null());
+        assert!(entry["profiles"].is_null());
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_reports_validate_against_schema() {
+        let mut data = vec![0u8; 128];
+        data[0..4].copy_from_slice(&1024u32.to_be_bytes());
+        data[12..16].copy_from_slice(b"mntr");
+        data[16..20].copy_from_slice(b"RGB ");
+        data[20..24].copy_from_slice(b"XYZ ");
+        let header = IccHeader::parse(&data).unwrap();
+
+        let mut with_profile = ListEntry::new(test_display());
+        with_profile.profile = Some(ProfileInfo {
+            name: "sRGB".to_string(),
+            description: None,
+            file_path: Some("/usr/share/color/icc/sRGB.icc".into()),
+            color_space: ColorSpace::RGB,
+        });
+        with_profile.icc_size = Some(1024);
+        let mut assigned = AssignedProfile::new(with_profile.profile.clone().unwrap(), true);
+        assigned.id = Some("icc-srgb".to_string());
+        assigned
+            .metadata
+            .insert("DATA_source".to_string(), "standard".to_string());
+        with_profile.profiles = Some(vec![assigned]);
+        let mut with_error = ListEntry::new(test_display());
+        with_error.profile_error = Some("System API error: boom".to_string());
+
+        let reports = [
+            serde_json::to_value(ListReport::new(vec![with_profile, with_error])).unwrap(),
+            serde_json::to_value(HeaderReport::new(test_display(), header)).unwrap(),
+            serde_json::to_value(PathsReport::new(vec![SearchPath {
+                path: "/usr/share/color/icc".into(),
+                exists: true,
+                profiles: vec!["/usr/share/color/icc/sRGB.icc".into()],
+            }]))
+            .unwrap(),
+        ];
+
+        let validator = jsonschema::validator_for(&json_schema()).unwrap();
+        for report in &reports {
+            assert!(validator.is_valid(report), "{} should validate", report);
+        }
+
+        let mut wrong_version = reports[0].clone();
+        wrong_version["schema_version"] = serde_json::json!(SCHEMA_VERSION + 1);
+        assert!(!validator.is_valid(&wrong_version));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_checked_in_schema_is_up_to_date() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/schema/display_icc.schema.json"
+        );
+        let generated = serde_json::to_string_pretty(&json_schema()).unwrap() + "\n";
+
+        if std::env::var_os("DISPLAY_ICC_UPDATE_SCHEMA").is_some() {
+            std::fs::write(path, &generated).unwrap();
+        }
+
+        let checked_in = std::fs::read_to_string(path).unwrap_or_default();
+        assert_eq!(
+            checked_in, generated,
+            "schema/display_icc.schema.json is stale; rerun the tests with DISPLAY_ICC_UPDATE_SCHEMA=1"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! ICC profile search paths and directory scanning.
+//!
+//! On Linux the directories follow the XDG Base Directory specification as used
+//! by colord: per-user profiles live in `$XDG_DATA_HOME/icc` (with the legacy
+//! `~/.color/icc`), system profiles in `color/icc` under each of `$XDG_DATA_DIRS`,
+//! and colord keeps generated profiles in `/var/lib/colord/icc`.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Offset of the `acsp` profile file signature in an ICC header
+const ICC_MAGIC_OFFSET: usize = 36;
+
+/// Resolve the profile search paths, with `extra_dirs` searched first.
+///
+/// `env` looks up an environment variable; it is a parameter so the XDG rules
+/// can be tested without touching the process environment.
+pub(crate) fn resolve_search_paths(
+    extra_dirs: &[PathBuf],
+    env: impl Fn(&str) -> Option<OsString>,
+) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = extra_dirs.to_vec();
+
+    #[cfg(target_os = "linux")]
+    paths.extend(xdg_search_paths(env));
+    #[cfg(not(target_os = "linux"))]
+    let _ = env;
+
+    dedup_paths(paths)
+}
+
+/// The per-user profile store, `$XDG_DATA_HOME/icc`, where imported profiles go
+#[cfg(target_os = "linux")]
+pub(crate) fn user_profile_dir(env: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    xdg_home(env, "XDG_DATA_HOME", ".local/share").map(|data_home| data_home.join("icc"))
+}
+
+/// An XDG base directory: `$var` when it is set, else `default` under `$HOME`
+#[cfg(target_os = "linux")]
+pub(crate) fn xdg_home(
+    env: impl Fn(&str) -> Option<OsString>,
+    var: &str,
+    default: &str,
+) -> Option<PathBuf> {
+    env(var).and_then(absolute).or_else(|| {
+        env("HOME")
+            .and_then(absolute)
+            .map(|home| home.join(default))
+    })
+}
+
+/// The spec says relative paths in the XDG variables are invalid and must be ignored
+#[cfg(target_os = "linux")]
+fn absolute(value: OsString) -> Option<PathBuf> {
+    Some(PathBuf::from(value)).filter(|p| p.is_absolute())
+}
+
+/// Linux profile directories, most specific first
+#[cfg(target_os = "linux")]
+fn xdg_search_paths(env: impl Fn(&str) -> Option<OsString>) -> Vec<PathBuf> {
+    let home = env("HOME").and_then(absolute);
+
+    let mut paths = Vec::new();
+
+    paths.extend(user_profile_dir(&env));
+    if let Some(home) = &home {
+        paths.push(home.join(".color/icc"));
+    }
+
+    let data_dirs = env("XDG_DATA_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| OsString::from("/usr/local/share:/usr/share"));
+    paths.extend(
+        std::env::split_paths(&data_dirs)
+            .filter(|dir| dir.is_absolute())
+            .map(|dir| dir.join("color/icc")),
+    );
+
+    paths.push(PathBuf::from("/var/lib/colord/icc"));
+    paths.push(PathBuf::from("/var/lib/color/icc"));
+
+    paths
+}
+
+/// Remove repeated paths, keeping the first occurrence
+fn dedup_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| seen.insert(path.clone()))
+        .collect()
+}
+
+/// Find every ICC profile under `dirs`, recursing into subdirectories.
+///
+/// Files need an `.icc`/`.icm` extension and the ICC `acsp` signature. Profiles
+/// reachable through more than one path (symlinks, overlapping directories) are
+/// reported once, under the first path found. Results keep the order of `dirs`
+/// and are sorted by path within each directory.
+pub(crate) fn find_profiles(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut profiles = Vec::new();
+    let mut seen_files = HashSet::new();
+    let mut seen_dirs = HashSet::new();
+
+    for dir in dirs {
+        scan_dir(dir, &mut profiles, &mut seen_files, &mut seen_dirs);
+    }
+
+    profiles
+}
+
+fn scan_dir(
+    dir: &Path,
+    profiles: &mut Vec<PathBuf>,
+    seen_files: &mut HashSet<PathBuf>,
+    seen_dirs: &mut HashSet<PathBuf>,
+) {
+    // Canonical paths guard against symlink loops and directories listed twice
+    let Ok(canonical) = dir.canonicalize() else {
+        return;
+    };
+    if !seen_dirs.insert(canonical) {
+        return;
+    }
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            scan_dir(&path, profiles, seen_files, seen_dirs);
+        } else if has_profile_extension(&path) && is_icc_file(&path) {
+            let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if seen_files.insert(key) {
+                profiles.push(path);
+            }
+        }
+    }
+}
+
+fn has_profile_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("icc") || ext.eq_ignore_ascii_case("icm"))
+}
+
+/// Check the ICC profile file signature without reading the whole file
+pub(crate) fn is_icc_file(path: &Path) -> bool {
+    let mut header = [0u8; ICC_MAGIC_OFFSET + 4];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|_| &header[ICC_MAGIC_OFFSET..] == b"acsp")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_profile(path: &Path) {
+        let mut data = vec![0u8; 128];
+        data[36..40].copy_from_slice(b"acsp");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<OsString> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| OsString::from(value))
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_xdg_defaults() {
+        let paths = resolve_search_paths(&[], env_from(&[("HOME", "/home/alice")]));
+
+        assert_eq!(
+            paths,
+            [
+                "/home/alice/.local/share/icc",
+                "/home/alice/.color/icc",
+                "/usr/local/share/color/icc",
+                "/usr/share/color/icc",
+                "/var/lib/colord/icc",
+                "/var/lib/color/icc",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_xdg_overrides_and_dedup() {
+        let env = env_from(&[
+            ("HOME", "/home/alice"),
+            ("XDG_DATA_HOME", "/data/alice"),
+            // Relative entries are invalid per the spec, duplicates are dropped
+            (
+                "XDG_DATA_DIRS",
+                "/opt/share:relative/share:/usr/share:/opt/share",
+            ),
+        ]);
+        let paths = resolve_search_paths(&[PathBuf::from("/usr/share/color/icc")], env);
+
+        assert_eq!(
+            paths,
+            [
+                "/usr/share/color/icc",
+                "/data/alice/icc",
+                "/home/alice/.color/icc",
+                "/opt/share/color/icc",
+                "/var/lib/colord/icc",
+                "/var/lib/color/icc",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_xdg_relative_data_home_ignored() {
+        let env = env_from(&[("HOME", "/home/alice"), ("XDG_DATA_HOME", "data")]);
+        let paths = resolve_search_paths(&[], env);
+
+        assert_eq!(paths[0], PathBuf::from("/home/alice/.local/share/icc"));
+    }
+
+    #[test]
+    fn test_find_profiles_recurses_and_checks_magic() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+
+        write_profile(&root.join("b.icc"));
+        write_profile(&root.join("vendor/a.ICM"));
+        std::fs::write(root.join("fake.icc"), b"not an icc profile").unwrap();
+        std::fs::write(root.join("readme.txt"), b"text").unwrap();
+
+        let profiles = find_profiles(&[root.to_path_buf()]);
+        assert_eq!(
+            profiles,
+            vec![root.join("b.icc"), root.join("vendor/a.ICM")]
+        );
+    }
+
+    #[test]
+    fn test_find_profiles_deduplicates() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write_profile(&root.join("icc/display.icc"));
+
+        // The same directory reached directly and through its parent
+        let profiles = find_profiles(&[root.join("icc"), root.to_path_buf()]);
+        assert_eq!(profiles, vec![root.join("icc/display.icc")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_profiles_survives_symlink_loops() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write_profile(&root.join("display.icc"));
+        std::os::unix::fs::symlink(root, root.join("loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("display.icc"), root.join("link.icc")).unwrap();
+
+        let profiles = find_profiles(&[root.to_path_buf()]);
+        assert_eq!(profiles, vec![root.join("display.icc")]);
+    }
+
+    #[test]
+    fn test_missing_directories_are_skipped() {
+        let profiles = find_profiles(&[PathBuf::from("/nonexistent/display_icc/icc")]);
+        assert!(profiles.is_empty());
+    }
+}
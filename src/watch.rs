@@ -0,0 +1,350 @@
+//! Change notifications for displays and their profiles.
+//!
+//! A [`ProfileWatcher`] delivers [`DisplayEvent`]s over a channel as displays
+//! are connected or disconnected and as their profiles change. On Linux with
+//! the `dbus-support` feature it is fed from colord's D-Bus signals; other
+//! backends have no change notifications to listen to, and instead compare
+//! snapshots of their displays and profiles taken at an interval.
+
+use crate::{md5, AssignedProfile, Display, DisplayProfileProvider, ProfileError};
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often backends without change notifications take a snapshot
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A change to the connected displays or their profiles.
+///
+/// Each event carries the display as it is after the change; for
+/// [`Removed`](Self::Removed) that is the display as last seen.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DisplayEvent {
+    /// A display was connected
+    Added(Display),
+    /// A display was disconnected
+    Removed(Display),
+    /// A profile was assigned to or removed from a display, its default profile
+    /// changed, or one of its profiles was modified
+    ProfileChanged(Display),
+    /// Another display became the primary display
+    PrimaryChanged(Display),
+}
+
+/// Receives [`DisplayEvent`]s until dropped.
+///
+/// Dropping the watcher stops watching and releases the resources behind it,
+/// such as a bus connection and its thread.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # fn main() -> Result<(), display_icc::ProfileError> {
+/// use display_icc::{create_provider, DisplayEvent, DisplayProfileProvider};
+///
+/// let provider = create_provider()?;
+/// let watcher = provider.watch()?;
+///
+/// for event in watcher.iter() {
+///     match event {
+///         DisplayEvent::Added(display) => println!("connected: {}", display.name),
+///         DisplayEvent::Removed(display) => println!("disconnected: {}", display.name),
+///         DisplayEvent::ProfileChanged(display) => println!("new profile: {}", display.name),
+///         DisplayEvent::PrimaryChanged(display) => println!("now primary: {}", display.name),
+///         _ => {}
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProfileWatcher {
+    events: Receiver<DisplayEvent>,
+    _source: Box<dyn Send>,
+}
+
+impl ProfileWatcher {
+    /// Create a watcher reading `events`, keeping `source` alive until it is dropped
+    pub(crate) fn new(events: Receiver<DisplayEvent>, source: impl Send + 'static) -> Self {
+        Self {
+            events,
+            _source: Box::new(source),
+        }
+    }
+
+    /// Watch `provider` by comparing snapshots of its displays and their
+    /// profiles, taken every `interval`.
+    ///
+    /// This is how backends without change notifications implement
+    /// [`DisplayProfileProvider::watch`], and is available to other providers
+    /// for the same purpose. A display's profiles count as changed when its
+    /// assigned profiles or its profile data differ from the last snapshot.
+    /// Snapshots that fail are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first snapshot, taken before this returns, so
+    /// that changes made afterwards are reported.
+    pub fn polling<P>(provider: P, interval: Duration) -> Result<Self, ProfileError>
+    where
+        P: DisplayProfileProvider + Send + 'static,
+    {
+        let mut last = Snapshot::take(&provider)?;
+        let (sender, events) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::Builder::new()
+            .name("display_icc polling watch".to_string())
+            .spawn(move || {
+                // Nothing is ever sent on `stop`; it is dropped with the watcher
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let Ok(snapshot) = Snapshot::take(&provider) else {
+                        continue;
+                    };
+                    for event in last.changes(&snapshot) {
+                        if sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                    last = snapshot;
+                }
+            })?;
+
+        Ok(Self::new(
+            events,
+            StopPolling {
+                stop: Some(stop),
+                thread: Some(thread),
+            },
+        ))
+    }
+
+    /// Wait for the next event.
+    ///
+    /// Returns `None` once no more events can arrive, for example because the
+    /// system service being watched went away.
+    pub fn recv(&self) -> Option<DisplayEvent> {
+        self.events.recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<DisplayEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Wait up to `timeout` for the next event, telling a timeout apart from
+    /// the end of events
+    pub(crate) fn poll(&self, timeout: Duration) -> Result<DisplayEvent, RecvTimeoutError> {
+        self.events.recv_timeout(timeout)
+    }
+
+    /// Return the next event if one is already waiting
+    pub fn try_recv(&self) -> Option<DisplayEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Iterate over events as they arrive, until no more can arrive
+    pub fn iter(&self) -> impl Iterator<Item = DisplayEvent> + '_ {
+        self.events.iter()
+    }
+}
+
+/// Stops the polling thread when the watcher is dropped
+struct StopPolling {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for StopPolling {
+    fn drop(&mut self) {
+        // Disconnecting wakes the thread between snapshots
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A display and what its profiles looked like when the snapshot was taken
+#[derive(Debug, PartialEq)]
+struct DisplayState {
+    display: Display,
+    /// Assigned profiles, if they could be read
+    profiles: Option<Vec<AssignedProfile>>,
+    /// Checksum of the profile data, if it could be read
+    data: Option<[u8; 16]>,
+}
+
+/// The displays of a provider at one point in time
+#[derive(Debug)]
+struct Snapshot {
+    displays: Vec<DisplayState>,
+}
+
+impl Snapshot {
+    fn take(provider: &impl DisplayProfileProvider) -> Result<Self, ProfileError> {
+        let displays = provider
+            .get_displays()?
+            .into_iter()
+            .map(|display| DisplayState {
+                profiles: provider.get_profiles(&display).ok(),
+                data: provider
+                    .get_profile_data(&display)
+                    .ok()
+                    .map(|data| md5::digest(&data)),
+                display,
+            })
+            .collect();
+        Ok(Self { displays })
+    }
+
+    fn get(&self, id: &str) -> Option<&DisplayState> {
+        self.displays.iter().find(|state| state.display.id == id)
+    }
+
+    fn primary(&self) -> Option<&Display> {
+        self.displays
+            .iter()
+            .map(|state| &state.display)
+            .find(|display| display.is_primary)
+    }
+
+    /// The events that lead from this snapshot to `next`: removals, then
+    /// additions and profile changes in display order, then a new primary
+    fn changes(&self, next: &Snapshot) -> Vec<DisplayEvent> {
+        let mut events: Vec<DisplayEvent> = self
+            .displays
+            .iter()
+            .filter(|state| next.get(&state.display.id).is_none())
+            .map(|state| DisplayEvent::Removed(state.display.clone()))
+            .collect();
+
+        for state in &next.displays {
+            match self.get(&state.display.id) {
+                None => events.push(DisplayEvent::Added(state.display.clone())),
+                Some(old) if (&old.profiles, old.data) != (&state.profiles, state.data) => {
+                    events.push(DisplayEvent::ProfileChanged(state.display.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(primary) = next.primary() {
+            if self.primary().map(|display| &display.id) != Some(&primary.id) {
+                events.push(DisplayEvent::PrimaryChanged(primary.clone()));
+            }
+        }
+        events
+    }
+}
+
+impl fmt::Debug for ProfileWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfileWatcher").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{BuiltinProfile, MockMethod, MockProfileProvider};
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn mock() -> MockProfileProvider {
+        MockProfileProvider::builder()
+            .display(Display::new("DP-1", "Studio Display", true))
+            .builtin_profile("DP-1", BuiltinProfile::DisplayP3)
+            .display(Display::new("HDMI-1", "Projector", false))
+            .watch_interval(Duration::from_millis(5))
+            .build()
+    }
+
+    /// The events of the next snapshot that reports any
+    fn next_events(watcher: &ProfileWatcher) -> Vec<DisplayEvent> {
+        let mut events = vec![watcher.recv_timeout(TIMEOUT).expect("no event")];
+        // Events of one snapshot are sent together
+        while let Some(event) = watcher.recv_timeout(Duration::from_millis(50)) {
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn test_polling_reports_changes() {
+        let mut provider = mock();
+        let watcher = provider.watch().unwrap();
+        assert_eq!(watcher.recv_timeout(Duration::from_millis(50)), None);
+
+        provider.set_profile_data("HDMI-1", BuiltinProfile::Srgb.data());
+        let projector = Display::new("HDMI-1", "Projector", false);
+        assert_eq!(
+            next_events(&watcher),
+            [DisplayEvent::ProfileChanged(projector.clone())]
+        );
+
+        // Either order of snapshots gives the same events
+        provider.remove_display("HDMI-1");
+        provider.add_display(Display::new("eDP-1", "Built-in", false));
+        assert_eq!(
+            next_events(&watcher),
+            [
+                DisplayEvent::Removed(projector),
+                DisplayEvent::Added(Display::new("eDP-1", "Built-in", false)),
+            ]
+        );
+
+        provider.set_primary_display("eDP-1");
+        assert_eq!(
+            next_events(&watcher),
+            [DisplayEvent::PrimaryChanged(Display::new(
+                "eDP-1", "Built-in", true
+            ))]
+        );
+
+        // The watcher's own snapshots are not recorded
+        provider.assert_calls(&[]);
+    }
+
+    #[test]
+    fn test_polling_errors() {
+        let failing = MockProfileProvider::builder()
+            .fail(
+                MockMethod::GetDisplays,
+                ProfileError::SystemError("no displays".to_string()),
+            )
+            .build();
+        assert!(matches!(failing.watch(), Err(ProfileError::SystemError(_))));
+
+        // A snapshot that fails is skipped rather than reported as a change
+        let mut provider = mock();
+        let watcher = provider.watch().unwrap();
+        provider.set_method_failure(
+            MockMethod::GetDisplays,
+            None,
+            ProfileError::SystemError("busy".to_string()),
+        );
+        assert_eq!(watcher.recv_timeout(Duration::from_millis(50)), None);
+        provider.clear_failures();
+        provider.clear_displays();
+        assert_eq!(
+            next_events(&watcher),
+            [
+                DisplayEvent::Removed(Display::new("DP-1", "Studio Display", true)),
+                DisplayEvent::Removed(Display::new("HDMI-1", "Projector", false)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dropping_stops_polling() {
+        let provider = mock();
+        let watcher = ProfileWatcher::polling(provider.shared(), Duration::from_millis(5)).unwrap();
+        provider.assert_call_count(MockMethod::GetDisplays, 1);
+        drop(watcher);
+
+        let calls = provider.call_count(MockMethod::GetDisplays);
+        std::thread::sleep(Duration::from_millis(50));
+        provider.assert_call_count(MockMethod::GetDisplays, calls);
+    }
+}
@@ -1,7 +1,9 @@
 //! Windows-specific implementation using Win32 API
 
+use crate::watch::POLL_INTERVAL;
 use crate::{
-    ColorSpace, Display, DisplayProfileProvider, ProfileConfig, ProfileError, ProfileInfo,
+    ColorSpace, Display, DisplayProfileProvider, PrimarySource, ProfileConfig, ProfileError,
+    ProfileInfo, ProfileWatcher,
 };
 use std::ffi::{CStr, CString};
 use std::path::PathBuf;
@@ -173,6 +175,27 @@ fn enumerate_monitors() -> Result<Vec<MonitorInfo>, ProfileError> {
     Ok(data.monitors)
 }
 
+/// Build a Display from an enumerated monitor and its desktop rectangle
+fn monitor_to_display(
+    index: usize,
+    monitor: &MonitorInfo,
+    primary_source: Option<PrimarySource>,
+) -> Display {
+    let rect = &monitor.rect;
+
+    let display = Display::new(format!("monitor_{}", index), monitor.name.clone(), false)
+        .with_resolution(
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        )
+        .with_position(rect.left, rect.top);
+
+    match primary_source {
+        Some(source) => display.with_primary_source(source),
+        None => display,
+    }
+}
+
 /// Read ICC profile data from file
 fn read_profile_file(profile_path: &PathBuf) -> Result<Vec<u8>, ProfileError> {
     std::fs::read(profile_path).map_err(|e| ProfileError::IoError(e.to_string()))
@@ -548,12 +571,11 @@ impl DisplayProfileProvider for WindowsProfileProvider {
 
         let mut displays = Vec::new();
         for (index, monitor) in monitors.iter().enumerate() {
-            let display = Display {
-                id: format!("monitor_{}", index),
-                name: monitor.name.clone(),
-                is_primary: monitor.is_primary,
-            };
-            displays.push(display);
+            displays.push(monitor_to_display(
+                index,
+                monitor,
+                monitor.is_primary.then_some(PrimarySource::System),
+            ));
         }
 
         Ok(displays)
@@ -564,21 +586,22 @@ impl DisplayProfileProvider for WindowsProfileProvider {
 
         for (index, monitor) in monitors.iter().enumerate() {
             if monitor.is_primary {
-                return Ok(Display {
-                    id: format!("monitor_{}", index),
-                    name: monitor.name.clone(),
-                    is_primary: true,
-                });
+                return Ok(monitor_to_display(
+                    index,
+                    monitor,
+                    Some(PrimarySource::System),
+                ));
             }
         }
 
         // Fallback: if no primary monitor found, use the first one
         if let Some(monitor) = monitors.first() {
-            Ok(Display {
-                id: "monitor_0".to_string(),
-                name: monitor.name.clone(),
-                is_primary: true, // Treat as primary since it's the only/first one
-            })
+            // Treat as primary since it's the only/first one
+            Ok(monitor_to_display(
+                0,
+                monitor,
+                Some(PrimarySource::FirstDisplay),
+            ))
         } else {
             Err(ProfileError::DisplayNotFound(
                 "No displays found".to_string(),
@@ -662,4 +685,8 @@ impl DisplayProfileProvider for WindowsProfileProvider {
             ))
         }
     }
+    /// `WM_DISPLAYCHANGE` needs a window, so poll instead
+    fn watch(&self) -> Result<ProfileWatcher, ProfileError> {
+        ProfileWatcher::polling(Self::with_config(self.config.clone()), POLL_INTERVAL)
+    }
 }
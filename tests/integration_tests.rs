@@ -41,10 +41,9 @@ fn test_create_provider_default() {
 #[test]
 #[serial]
 fn test_create_provider_with_config() {
-    let config = ProfileConfig {
-        linux_prefer_dbus: false,
-        fallback_enabled: true,
-    };
+    let config = ProfileConfig::default()
+        .with_linux_prefer_dbus(false)
+        .with_fallback_enabled(true);
 
     let provider = create_provider_with_config(config);
     assert!(
@@ -278,18 +277,15 @@ fn test_get_primary_display_profile_data_convenience() {
 #[serial]
 fn test_different_configurations() {
     let configs = [
-        ProfileConfig {
-            linux_prefer_dbus: true,
-            fallback_enabled: true,
-        },
-        ProfileConfig {
-            linux_prefer_dbus: false,
-            fallback_enabled: true,
-        },
-        ProfileConfig {
-            linux_prefer_dbus: true,
-            fallback_enabled: false,
-        },
+        ProfileConfig::default()
+            .with_linux_prefer_dbus(true)
+            .with_fallback_enabled(true),
+        ProfileConfig::default()
+            .with_linux_prefer_dbus(false)
+            .with_fallback_enabled(true),
+        ProfileConfig::default()
+            .with_linux_prefer_dbus(true)
+            .with_fallback_enabled(false),
     ];
 
     for (i, config) in configs.iter().enumerate() {
@@ -357,18 +353,16 @@ mod linux_tests {
     #[test]
     #[serial]
     fn test_linux_specific_behavior() {
-        let provider = create_provider().expect("Should create Linux provider");
+        create_provider().expect("Should create Linux provider");
 
         // Test both D-Bus and command-line approaches
         let configs = [
-            ProfileConfig {
-                linux_prefer_dbus: true,
-                fallback_enabled: true,
-            },
-            ProfileConfig {
-                linux_prefer_dbus: false,
-                fallback_enabled: true,
-            },
+            ProfileConfig::default()
+                .with_linux_prefer_dbus(true)
+                .with_fallback_enabled(true),
+            ProfileConfig::default()
+                .with_linux_prefer_dbus(false)
+                .with_fallback_enabled(true),
         ];
 
         for config in &configs {
@@ -449,7 +443,7 @@ fn test_rapid_calls() {
     for i in 0..10 {
         let displays = provider
             .get_displays()
-            .expect(&format!("Should get displays on iteration {}", i));
+            .unwrap_or_else(|e| panic!("Should get displays on iteration {}: {}", i, e));
 
         assert!(
             !displays.is_empty(),
@@ -459,7 +453,7 @@ fn test_rapid_calls() {
 
         let primary = provider
             .get_primary_display()
-            .expect(&format!("Should get primary display on iteration {}", i));
+            .unwrap_or_else(|e| panic!("Should get primary display on iteration {}: {}", i, e));
 
         assert!(
             primary.is_primary,
@@ -476,20 +470,19 @@ fn test_error_handling_invalid_display() {
     let _provider = create_provider().expect("Should create provider");
 
     // Test with a configuration that disables fallbacks
-    let no_fallback_config = ProfileConfig {
-        linux_prefer_dbus: true,
-        fallback_enabled: false, // Disable fallbacks
-    };
+    let no_fallback_config = ProfileConfig::default()
+        .with_linux_prefer_dbus(true)
+        .with_fallback_enabled(false); // Disable fallbacks
 
     let provider = create_provider_with_config(no_fallback_config)
         .expect("Should create provider with no fallback");
 
     // Create a fake display that doesn't exist
-    let fake_display = display_icc::Display {
-        id: "nonexistent_display_12345".to_string(),
-        name: "Fake Display".to_string(),
-        is_primary: false,
-    };
+    let fake_display = display_icc::Display::new(
+        "nonexistent_display_12345".to_string(),
+        "Fake Display".to_string(),
+        false,
+    );
 
     // Should return appropriate errors when fallbacks are disabled
     let profile_result = provider.get_profile(&fake_display);